@@ -0,0 +1,186 @@
+//! Memory of an imported log and runtime of the features that compare activities and object types,
+//! for the interned `Ocel` and for a copy of the log in the String-keyed model that `Ocel` used before.
+//! Run with `cargo bench --bench interning`, `PMRS_BENCH_EVENTS` sets the number of generated events.
+use ahash::AHashMap;
+use bimap::BiMap;
+use chrono::{DateTime, FixedOffset, Utc};
+use nohash_hasher::{IntMap, IntSet};
+use pmrs::objects::ocel::{AttributeHistory, Ocel};
+use pmrs::objects::ocel::importer::import_ocel;
+use pmrs::algo::transformation::ocel::features::event_point::{activity_ohe, omap_type_counts};
+use pmrs::algo::transformation::ocel::features::event_group::activity_counts;
+use pmrs::algo::transformation::ocel::features::object_group::object_type_count;
+use serde_json::Value;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ACTIVITIES: [&str; 8] = ["place order", "check availability", "pick item", "pay order", "create package", "send package", "failed delivery", "package delivered"];
+const OBJECT_TYPES: [&str; 3] = ["order", "item", "package"];
+
+fn generate_log(file_path: &str, events: usize) -> std::io::Result<()> {
+    let objects = events / 5;
+    let mut f = BufWriter::new(File::create(file_path)?);
+    write!(f, r#"{{"ocel:global-log": {{"ocel:version": "0.1", "ocel:ordering": "timestamp", "ocel:attribute-names": [], "ocel:object-types": ["order", "item", "package"]}},"#)?;
+    write!(f, r#""ocel:global-event": {{"ocel:activity": "__INVALID__"}}, "ocel:global-object": {{"ocel:type": "__INVALID__"}}, "ocel:objects": {{"#)?;
+    for oid in 0..objects {
+        write!(f, r#"{}"o{}": {{"ocel:type": "{}", "ocel:ovmap": {{}}}}"#, if oid == 0 {""} else {","}, oid, OBJECT_TYPES[oid % OBJECT_TYPES.len()])?;
+    }
+    write!(f, r#"}}, "ocel:events": {{"#)?;
+    for eid in 0..events {
+        write!(f, r#"{}"e{}": {{"ocel:activity": "{}", "ocel:timestamp": "2022-01-01T00:00:00Z", "ocel:omap": ["o{}", "o{}", "o{}"], "ocel:vmap": {{}}}}"#,
+               if eid == 0 {""} else {","}, eid, ACTIVITIES[eid % ACTIVITIES.len()], eid % objects, (eid * 7 + 1) % objects, (eid * 13 + 2) % objects)?;
+    }
+    write!(f, "}}}}")?;
+    f.flush()
+}
+
+/// Event of the String-keyed model, every event keeps the name of its activity.
+#[allow(dead_code)]
+struct StringEvent {
+    activity: String,
+    timestamp: DateTime<Utc>,
+    vmap: AHashMap<String, Value>,
+    omap: IntSet<usize>,
+    offset: Option<FixedOffset>,
+    qualifiers: IntMap<usize, Vec<String>>
+}
+
+/// Object of the String-keyed model, every object keeps the name of its type.
+#[allow(dead_code)]
+struct StringObject {
+    obj_type: String,
+    ovmap: AHashMap<String, Value>,
+    events: Vec<usize>,
+    history: AttributeHistory
+}
+
+/// The log as it was stored before interning, with the features written against it.
+#[allow(dead_code)]
+struct StringLog {
+    events: IntMap<usize, StringEvent>,
+    objects: IntMap<usize, StringObject>,
+    event_map: BiMap<String, usize>,
+    object_map: BiMap<String, usize>,
+    activities: Vec<String>
+}
+
+impl StringLog {
+    fn new(log: &Ocel) -> Self {
+        let events = log.events().iter().map(|(eid, ev)| {
+            (*eid, StringEvent { activity: log.activity(ev).to_owned(), timestamp: ev.timestamp, vmap: ev.vmap.clone(), omap: ev.omap.clone(),
+                                 offset: ev.offset, qualifiers: ev.qualifiers.clone() })
+        }).collect();
+        let objects = log.objects().iter().map(|(oid, obj)| {
+            (*oid, StringObject { obj_type: log.object_type(obj).to_owned(), ovmap: obj.ovmap.clone(), events: obj.events.clone(), history: obj.history.clone() })
+        }).collect();
+        StringLog { events, objects, event_map: log.event_map.clone(), object_map: log.object_map.clone(),
+                    activities: log.activities().into_iter().map(|act| act.to_owned()).collect() }
+    }
+
+    fn activity_ohe(&self, eid: &usize) -> Vec<u8> {
+        let mut activity_bools: Vec<u8> = vec![0; self.activities.len()];
+        if let Some(e) = self.events.get(eid) {
+            if let Some(pos) = self.activities.iter().position(|act| e.activity.as_str() == act) {
+                activity_bools[pos] = 1;
+            }
+        }
+        activity_bools
+    }
+
+    fn omap_type_counts(&self, eid: &usize) -> HashMap<String, usize> {
+        let mut omap_counts: HashMap<String, usize> = HashMap::new();
+        if let Some(e) = self.events.get(eid) {
+            e.omap.iter().for_each(|oid| {
+                match self.objects.get(oid) {
+                    Some(obj) => {*omap_counts.entry(obj.obj_type.clone()).or_insert(0) += 1},
+                    None => {*omap_counts.entry("unknown".to_string()).or_insert(0) += 1}
+                }
+            });
+        }
+        omap_counts
+    }
+
+    fn activity_counts(&self) -> HashMap<String, usize> {
+        let mut activity_counter: HashMap<String, usize> = HashMap::new();
+        self.events.values().for_each(|ev| *activity_counter.entry(ev.activity.clone()).or_insert(0) += 1);
+        activity_counter
+    }
+
+    fn object_type_count(&self, otype: &str) -> usize {
+        self.objects.values().filter(|obj| obj.obj_type == otype).count()
+    }
+}
+
+fn timed<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    println!("{:<24} {:>10.1} ms", name, start.elapsed().as_secs_f64() * 1000.0);
+    result
+}
+
+fn main() {
+    let events: usize = std::env::var("PMRS_BENCH_EVENTS").ok().and_then(|n| n.parse().ok()).unwrap_or(1_000_000);
+    let path = std::env::temp_dir().join("pmrs-bench-interning.jsonocel");
+    let path = path.to_str().unwrap();
+    generate_log(path, events).expect("could not write the generated log");
+
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    let log = timed("import", || import_ocel(path).expect("could not import the generated log"));
+    println!("{:<24} {:>10.1} MB", "retained by the log", (ALLOCATED.load(Ordering::SeqCst) - before) as f64 / 1e6);
+    // the index is built on first use, by the activity features among others
+    timed("index", || log.index());
+
+    println!("\ninterned");
+    let eids: Vec<usize> = (0..log.events().len()).collect();
+    let ohe: usize = timed("activity_ohe", || eids.iter().map(|eid| activity_ohe(&log, eid).len()).sum());
+    let counts: usize = timed("omap_type_counts", || eids.iter().map(|eid| omap_type_counts(&log, eid).len()).sum());
+    let activities = timed("activity_counts", || activity_counts(&log));
+    let types: usize = timed("object_type_count", || OBJECT_TYPES.iter().map(|ot| object_type_count(&log, ot)).sum());
+    assert_eq!(ohe, events * ACTIVITIES.len());
+    assert!(counts >= events);
+    assert_eq!(activities.len(), ACTIVITIES.len());
+    assert_eq!(types, log.objects().len());
+
+    println!("\nString-keyed");
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    let strings = StringLog::new(&log);
+    println!("{:<24} {:>10.1} MB", "retained by the log", (ALLOCATED.load(Ordering::SeqCst) - before) as f64 / 1e6);
+    drop(log);
+    let ohe: usize = timed("activity_ohe", || eids.iter().map(|eid| strings.activity_ohe(eid).len()).sum());
+    let counts: usize = timed("omap_type_counts", || eids.iter().map(|eid| strings.omap_type_counts(eid).len()).sum());
+    let activities = timed("activity_counts", || strings.activity_counts());
+    let types: usize = timed("object_type_count", || OBJECT_TYPES.iter().map(|ot| strings.object_type_count(ot)).sum());
+    assert_eq!(ohe, events * ACTIVITIES.len());
+    assert!(counts >= events);
+    assert_eq!(activities.len(), ACTIVITIES.len());
+    assert_eq!(types, strings.objects.len());
+
+    drop(strings);
+    let _ = std::fs::remove_file(path);
+}
@@ -2,31 +2,33 @@
 use std::collections::hash_map::Entry;
 
 use ahash::{AHashMap, AHashSet};
+use bimap::BiMap;
 use nohash_hasher::IntMap;
 use petgraph::{Direction::Incoming, visit::Dfs};
 use serde_json::Value;
 
-use crate::objects::{ocel::Ocel, ocdg::{Relations, OcdgRelations, generate_ocdg, RelationError, Ocdg}, linker::link_objects};
+use crate::objects::{ocel::Ocel, ocdg::{Relations, OcdgRelations, generate_ocdg, Ocdg}, linker::link_objects};
+use crate::Error;
 
 
-pub fn generate_feature_sharing_map(log: &Ocel, prop_relations: Vec<Relations>, oids: Vec<usize>) -> Result<IntMap<usize, Option<AHashMap<String, Value>>>, RelationError> {
+pub fn generate_feature_sharing_map(log: &Ocel, prop_relations: Vec<Relations>, oids: Vec<usize>) -> Result<IntMap<usize, Option<AHashMap<String, Value>>>, Error> {
 
     // share properties between property relations defined by user
     let shared_property_values = share_property_values(log, prop_relations)?;
     
     // flow all properties per object type using ascendants
-    Ok(flow_property_values(log, shared_property_values, &oids))
+    flow_property_values(log, shared_property_values, &oids)
 }
 
-fn share_property_values(log: &Ocel, prop_relations: Vec<Relations>) -> Result<IntMap<usize, AHashMap<String, Value>>, RelationError> {
+fn share_property_values(log: &Ocel, prop_relations: Vec<Relations>) -> Result<IntMap<usize, AHashMap<String, Value>>, Error> {
     // Return None if invalid property relations
     for rel in &prop_relations {
         if rel.is_directed() {
-            return Err(RelationError);
+            return Err(Error::UnsupportedRelation(format!("{} is directed, properties can only be shared over undirected relations.", rel)));
         }
     }
 
-    let property_graph: Ocdg = generate_ocdg(&log, &prop_relations);
+    let property_graph: Ocdg = generate_ocdg(log, &prop_relations)?;
     let log_property_link = link_objects(&log.object_map, &property_graph.object_map);
 
     let mut new_properties: IntMap<usize, AHashMap<String, Value>> = IntMap::default();
@@ -34,13 +36,14 @@ fn share_property_values(log: &Ocel, prop_relations: Vec<Relations>) -> Result<I
     // extract all omaps and shared features
     for (oid, oid_obj) in &log.objects {
         let mut oid_ovmap: AHashMap<String, Value> = oid_obj.ovmap.clone();
-        let graph_oid = log_property_link.get_by_left(oid).expect("This cannot fail");
-        let mut edges = property_graph.net.neighbors_directed(*property_graph.inodes.get(graph_oid).expect("this cannot fail"), Incoming);
         let mut ot_check: AHashMap<String, Vec<&usize>> = AHashMap::default();
 
-        while let Some(node) = edges.next() {
-            let curr_log_oid = log_property_link.get_by_right(&property_graph.net[node]).expect("This cannot fail");
-            ot_check.entry(property_graph.node_attributes[&property_graph.net[node]].node_type.clone()).or_insert(vec![]).push(curr_log_oid);
+        // objects without events are not part of the graph and share nothing
+        if let Some(graph_node) = log_property_link.get_by_left(oid).and_then(|graph_oid| property_graph.inodes.get(graph_oid)) {
+            for node in property_graph.net.neighbors_directed(*graph_node, Incoming) {
+                let (curr_log_oid, node_type) = linked_node(&property_graph, &log_property_link, property_graph.net[node])?;
+                ot_check.entry(node_type.to_owned()).or_default().push(curr_log_oid);
+            }
         }
 
 
@@ -48,8 +51,8 @@ fn share_property_values(log: &Ocel, prop_relations: Vec<Relations>) -> Result<I
             let mut value_holders: AHashMap<String, Value> = AHashMap::default();
             let mut to_remove: AHashSet<&str> = AHashSet::default();
             for n in neighs {
-                let neigh_obj = &log.objects.get(n).expect("this cannot fail");
-                if ot.as_str() != oid_obj.obj_type.as_str() {
+                let neigh_obj = log.objects.get(n).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not part of the log.", n)))?;
+                if ot.as_str() != log.object_type(oid_obj) {
                     for (key, value) in &neigh_obj.ovmap {
                         match value_holders.entry(key.to_owned()) {
                             Entry::Vacant(ent) => {ent.insert(value.to_owned());},
@@ -74,21 +77,22 @@ fn share_property_values(log: &Ocel, prop_relations: Vec<Relations>) -> Result<I
     Ok(new_properties)
 } 
 
-fn flow_property_values(log: &Ocel, new_properties: IntMap<usize, AHashMap<String, Value>>, oids: &Vec<usize>) -> IntMap<usize, Option<AHashMap<String, Value>>> {
+fn flow_property_values(log: &Ocel, new_properties: IntMap<usize, AHashMap<String, Value>>, oids: &Vec<usize>) -> Result<IntMap<usize, Option<AHashMap<String, Value>>>, Error> {
     let tc_relations = vec![Relations::ASCENDANTS]; // support only ascendants for now
-    let tc_graph = generate_ocdg(&log, &tc_relations);
+    let tc_graph = generate_ocdg(log, &tc_relations)?;
     let log_tc_link = link_objects(&log.object_map, &tc_graph.object_map);
     let mut flowed_properties: IntMap<usize, Option<AHashMap<String, Value>>> = IntMap::default();
     
     for oid in oids {
         if let Some(oid_obj) = &log.objects.get(oid) {
-            let graph_oid = log_tc_link.get_by_left(oid).expect("This cannot fail");
-            let mut edges = Dfs::new(&tc_graph.net, *tc_graph.inodes.get(graph_oid).expect("this cannot fail"));
             let mut ot_check: AHashMap<String, Vec<&usize>> = AHashMap::default();
 
-            while let Some(node) = edges.next(&tc_graph.net) {
-                let curr_log_oid = log_tc_link.get_by_right(&tc_graph.net[node]).expect("This cannot fail");
-                ot_check.entry(tc_graph.node_attributes[&tc_graph.net[node]].node_type.clone()).or_insert(vec![]).push(curr_log_oid);
+            if let Some(graph_node) = log_tc_link.get_by_left(oid).and_then(|graph_oid| tc_graph.inodes.get(graph_oid)) {
+                let mut edges = Dfs::new(&tc_graph.net, *graph_node);
+                while let Some(node) = edges.next(&tc_graph.net) {
+                    let (curr_log_oid, node_type) = linked_node(&tc_graph, &log_tc_link, tc_graph.net[node])?;
+                    ot_check.entry(node_type.to_owned()).or_default().push(curr_log_oid);
+                }
             }
 
             let mut oid_ovmap: AHashMap<String, Value> = oid_obj.ovmap.clone();
@@ -99,7 +103,7 @@ fn flow_property_values(log: &Ocel, new_properties: IntMap<usize, AHashMap<Strin
 
                 for n in neighs {
                     if let Some(neigh_obj) = new_properties.get(n) {
-                        if ot.as_str() != oid_obj.obj_type.as_str() {
+                        if ot.as_str() != log.object_type(oid_obj) {
                             for (key, value) in neigh_obj {
                                 match value_holders.entry(key.to_owned()) {
                                     Entry::Vacant(ent) => {ent.insert(value.to_owned());},
@@ -124,7 +128,14 @@ fn flow_property_values(log: &Ocel, new_properties: IntMap<usize, AHashMap<Strin
             flowed_properties.entry(*oid).or_insert(None);
         }
     }
-    flowed_properties
+    Ok(flowed_properties)
+}
+
+/// Log id and object type of a node of the graph.
+fn linked_node<'a>(graph: &'a Ocdg, link: &BiMap<&'a usize, &'a usize>, graph_oid: usize) -> Result<(&'a usize, &'a str), Error> {
+    let log_oid = *link.get_by_right(&&graph_oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Node {} of the graph is not part of the log.", graph_oid)))?;
+    let node_type = graph.node_attributes.get(&graph_oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Node {} has no attributes.", graph_oid)))?;
+    Ok((log_oid, node_type.node_type.as_str()))
 }
 
 #[cfg(test)]
@@ -138,8 +149,16 @@ mod tests {
 
     #[test]
     fn test_feature_sharing_invalid_input() {
-        let expected = Err(RelationError);
-        assert_eq!(generate_feature_sharing_map(&OCEL, vec![Relations::DESCENDANTS], vec![]), expected);
+        assert!(matches!(generate_feature_sharing_map(&OCEL, vec![Relations::DESCENDANTS], vec![]), Err(Error::UnsupportedRelation(_))));
+    }
+
+    #[test]
+    fn test_feature_sharing_object_without_events() {
+        let mut log = OCEL.clone();
+        let oid = *log.object_map.get_by_left("o2").unwrap();
+        log.objects_mut().get_mut(&oid).unwrap().events.clear();
+        let shared = generate_feature_sharing_map(&log, vec![Relations::COBIRTH], vec![oid]).expect("objects without events are valid");
+        assert_eq!(shared[&oid], Some(log.objects[&oid].ovmap.clone()));
     }
 
     #[test]
@@ -198,7 +217,7 @@ mod tests {
             raw_properties.entry(*oid).or_insert(oid_obj.ovmap.clone());
         }
 
-        let flowed_prop = flow_property_values(&OCEL, raw_properties, &oids);
+        let flowed_prop = flow_property_values(&OCEL, raw_properties, &oids).unwrap();
 
         let values_package = flowed_prop.get(&focus).expect("this cannot fail").as_ref().expect("this cannot fail");
 
@@ -218,7 +237,7 @@ mod tests {
             raw_properties.entry(*oid).or_insert(oid_obj.ovmap.clone());
         }
 
-        let flowed_prop = flow_property_values(&OCEL, raw_properties, &oids);
+        let flowed_prop = flow_property_values(&OCEL, raw_properties, &oids).unwrap();
 
         let values_route = flowed_prop.get(&focus).expect("this cannot fail").as_ref().expect("this cannot fail");
 
@@ -244,7 +263,7 @@ mod tests {
             raw_properties.entry(*oid).or_insert(oid_obj.ovmap.clone());
         }
 
-        let flowed_prop = flow_property_values(&OCEL, raw_properties, &oids);
+        let flowed_prop = flow_property_values(&OCEL, raw_properties, &oids).unwrap();
 
         let values_package = flowed_prop.get(&focus).expect("this cannot fail").as_ref().expect("this cannot fail");
 
@@ -1,3 +1,8 @@
 pub mod features;
 pub mod situations;
 pub mod timeseries;
+pub mod flattening;
+pub mod filtering;
+pub mod query;
+pub mod sampling;
+pub mod executions;
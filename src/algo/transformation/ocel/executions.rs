@@ -0,0 +1,148 @@
+use itertools::Itertools;
+use nohash_hasher::{IntMap, IntSet};
+
+use crate::objects::ocdg::object_neighbours;
+use crate::objects::ocel::Ocel;
+use crate::algo::transformation::ocel::filtering::sublog;
+
+/// How the objects of a log are grouped into process executions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ExecutionStrategy {
+    /// Connected components of the objects that share events, the `INTERACTS` relation of an OCDG.
+    #[default]
+    ConnectedComponents,
+    /// One execution per object of the leading type with the objects at most `hops` interactions away.
+    /// Other objects of the leading type are neither part of the execution nor traversed.
+    LeadingType { object_type: String, hops: usize }
+}
+
+/// Events and objects of one process execution.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ProcessExecution {
+    pub events: IntSet<usize>,
+    pub objects: IntSet<usize>
+}
+
+impl ProcessExecution {
+    /// Sub-log of the execution, links to events and objects outside of it are removed.
+    pub fn to_ocel(&self, log: &Ocel) -> Ocel {
+        sublog(log, &self.events, &self.objects)
+    }
+}
+
+/// Process executions of the log, ordered by their smallest object id for connected components and by the
+/// id of the leading object otherwise. Objects without events are not part of any execution, the events of
+/// an execution are all events of its objects.
+pub fn extract_process_executions(log: &Ocel, strategy: &ExecutionStrategy) -> Vec<ProcessExecution> {
+    let neighbours = object_neighbours(log);
+    let object_sets: Vec<IntSet<usize>> = match strategy {
+        ExecutionStrategy::ConnectedComponents => connected_components(&neighbours),
+        ExecutionStrategy::LeadingType { object_type, hops } => {
+            log.objects_of_type(object_type).iter()
+               .filter(|oid| neighbours.contains_key(oid))
+               .map(|oid| k_hop_neighbourhood(log, &neighbours, *oid, *hops))
+               .collect()
+        }
+    };
+
+    object_sets.into_iter().map(|objects| ProcessExecution {
+        events: objects.iter().flat_map(|oid| log.objects[oid].events.iter().copied()).collect(),
+        objects
+    }).collect()
+}
+
+fn connected_components(neighbours: &IntMap<usize, IntSet<usize>>) -> Vec<IntSet<usize>> {
+    let mut visited: IntSet<usize> = IntSet::default();
+    let mut components: Vec<IntSet<usize>> = vec![];
+
+    for start in neighbours.keys().copied().sorted() {
+        if !visited.insert(start) {
+            continue;
+        }
+        let mut component: IntSet<usize> = IntSet::from_iter([start]);
+        let mut queue = vec![start];
+        while let Some(oid) = queue.pop() {
+            for neigh in &neighbours[&oid] {
+                if visited.insert(*neigh) {
+                    component.insert(*neigh);
+                    queue.push(*neigh);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+fn k_hop_neighbourhood(log: &Ocel, neighbours: &IntMap<usize, IntSet<usize>>, leading: usize, hops: usize) -> IntSet<usize> {
+    let leading_type = log.objects[&leading].obj_type;
+    let mut reached: IntSet<usize> = IntSet::from_iter([leading]);
+    let mut frontier = vec![leading];
+
+    for _ in 0..hops {
+        let mut next = vec![];
+        for oid in frontier {
+            for neigh in &neighbours[&oid] {
+                if log.objects[neigh].obj_type != leading_type && reached.insert(*neigh) {
+                    next.push(*neigh);
+                }
+            }
+        }
+        frontier = next;
+    }
+    reached
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ocdg::{generate_ocdg, Relations};
+    use crate::objects::ocel::builder::LogBuilder;
+    use crate::objects::ocel::importer::import_ocel;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let executions = extract_process_executions(&OCEL, &ExecutionStrategy::ConnectedComponents);
+        let ocdg = generate_ocdg(&OCEL, &[Relations::INTERACTS]).unwrap();
+
+        let mut seen: IntSet<usize> = IntSet::default();
+        for execution in &executions {
+            assert!(execution.objects.iter().all(|oid| seen.insert(*oid)));
+            for oid in &execution.objects {
+                let interacting = ocdg.irels.get(oid).into_iter().flat_map(|targets| targets.keys());
+                assert!(interacting.into_iter().all(|tar| execution.objects.contains(tar)));
+            }
+            execution.to_ocel(&OCEL).build().expect("the execution is consistent");
+        }
+        assert_eq!(seen.len(), ocdg.node_attributes.len());
+        assert_eq!(executions.iter().map(|execution| execution.events.len()).sum::<usize>(), OCEL.events.len());
+    }
+
+    #[test]
+    fn test_leading_type() {
+        let strategy = ExecutionStrategy::LeadingType { object_type: "order".to_owned(), hops: 1 };
+        let executions = extract_process_executions(&OCEL, &strategy);
+        assert_eq!(executions.len(), OCEL.objects_of_type("order").len());
+
+        for (execution, leading) in executions.iter().zip(OCEL.objects_of_type("order")) {
+            let sub = execution.to_ocel(&OCEL);
+            assert_eq!(sub.objects_of_type("order"), &[*leading]);
+            let neighbours: IntSet<usize> = OCEL.objects[leading].events.iter().flat_map(|eid| OCEL.events[eid].omap.iter().copied()).filter(|oid| OCEL.object_type(&OCEL.objects[oid]) != "order").collect();
+            assert!(neighbours.iter().all(|oid| execution.objects.contains(oid)));
+            assert_eq!(execution.objects.len(), neighbours.len() + 1);
+            sub.build().expect("the execution is consistent");
+        }
+
+        let two_hops = extract_process_executions(&OCEL, &ExecutionStrategy::LeadingType { object_type: "order".to_owned(), hops: 2 });
+        for (one, two) in executions.iter().zip(&two_hops) {
+            assert!(one.objects.is_subset(&two.objects));
+        }
+        let zero_hops = extract_process_executions(&OCEL, &ExecutionStrategy::LeadingType { object_type: "order".to_owned(), hops: 0 });
+        assert!(zero_hops.iter().all(|execution| execution.objects.len() == 1));
+    }
+}
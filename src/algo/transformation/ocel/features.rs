@@ -3,3 +3,31 @@ pub mod object_group;
 pub mod event_point;
 pub mod event_group;
 pub mod operator;
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::Error;
+use crate::objects::ocel::Ocel;
+
+/// Feature parameters are given as json strings.
+pub(crate) fn param_str(value: &Value) -> Result<&str, Error> {
+    value.as_str().ok_or_else(|| Error::Schema(format!("The feature parameter {} is not a string.", value)))
+}
+
+/// Parse a string parameter into an `Operator`, `Relations` or other named variant.
+pub(crate) fn param_enum<T: FromStr>(value: &Value) -> Result<T, Error> {
+    let s = param_str(value)?;
+    T::from_str(s).map_err(|_| Error::Parse(format!("{} is not a valid feature parameter.", s)))
+}
+
+/// Object types in the order of the log header, which fixes the order of per-type feature columns.
+pub(crate) fn object_type_order(log: &Ocel) -> Result<Vec<&str>, Error> {
+    log.global_log.get("ocel:object-types")
+                  .and_then(|types| types.as_array())
+                  .ok_or_else(|| Error::Schema("The log has no ocel:object-types.".to_string()))?
+                  .iter()
+                  .map(param_str)
+                  .collect()
+}
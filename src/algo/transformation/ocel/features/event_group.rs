@@ -1,13 +1,16 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
 use chrono::Duration;
+use nohash_hasher::IntMap;
 use polars::prelude::{DataFrame, Series, NamedFrom};
 use serde_json::Value;
 use strum::{EnumString, IntoStaticStr, Display};
 
-use crate::objects::{ocel::Ocel, ocdg::Ocdg};
+use crate::objects::{ocel::{Ocel, interner::{ActivityId, ObjectTypeId}}, ocdg::Ocdg};
 
 use super::operator::Operator;
+use super::{param_enum, param_str};
+use crate::Error;
 
 #[derive(EnumString, IntoStaticStr, Display, Hash, Eq, PartialEq, Debug)]
 pub enum EventGroup {
@@ -26,14 +29,14 @@ pub struct EventGroupConfig<'a> {
 
 }
 
-pub fn event_group_features(config: EventGroupConfig) -> DataFrame {
+pub fn event_group_features(config: EventGroupConfig) -> Result<DataFrame, Error> {
     // let object_linker = link_objects(&config.ocel.object_map, &config.ocdg.object_map);
     let mut series_vec: Vec<Series> = vec![];
 
     for (feature, params) in config.params {
         match feature {
             EventGroup::ActivityCounts => {
-                let counts = activity_counts(&config.ocel);
+                let counts = activity_counts(config.ocel);
                 counts.iter().for_each(|(k, v)| {
                     series_vec.push(Series::new(format!("{:?}:{:?}:count", feature, k).as_str(), vec![*v as u64]));
                 });
@@ -43,9 +46,9 @@ pub fn event_group_features(config: EventGroupConfig) -> DataFrame {
                     let op_val: Option<&Value> = f_params.get("operator");
                     let act_val: Option<&Value> = f_params.get("activity");
                     if let (Some(act_valid), Some(op_valid)) = (act_val, op_val) {
-                        let act_str = act_valid.as_str().unwrap();
-                        let op_enum = Operator::from_str(op_valid.as_str().unwrap()).unwrap();
-                        let res = activity_attr_operator(&config.ocel, act_str, &op_enum);
+                        let act_str = param_str(act_valid)?;
+                        let op_enum = param_enum::<Operator>(op_valid)?;
+                        let res = activity_attr_operator(config.ocel, act_str, &op_enum);
 
                         res.iter().for_each(|(k, v)| {
                             series_vec.push(Series::new(format!("{:?}:{:?}:{:?}:{:?}", feature, act_str, k, op_enum).as_str(), vec![*v]))
@@ -59,8 +62,8 @@ pub fn event_group_features(config: EventGroupConfig) -> DataFrame {
                 if let Some(f_params) = params {
                     let op_val: Option<&Value> = f_params.get("operator");
                     if let Some(op_valid) = op_val {
-                        let op_enum = Operator::from_str(op_valid.as_str().unwrap()).unwrap();
-                        let res = activity_otype_operator(&config.ocel, &op_enum);
+                        let op_enum = param_enum::<Operator>(op_valid)?;
+                        let res = activity_otype_operator(config.ocel, &op_enum);
 
                         res.iter().for_each(|(act, ot_map)| {
                             ot_map.iter().for_each(|(ot, val)| {
@@ -77,9 +80,9 @@ pub fn event_group_features(config: EventGroupConfig) -> DataFrame {
                     let op_val: Option<&Value> = f_params.get("operator");
                     let act_val: Option<&Value> = f_params.get("activity");
                     if let (Some(act_valid), Some(op_valid)) = (act_val, op_val) {
-                        let act_str = act_valid.as_str().unwrap();
-                        let op_enum = Operator::from_str(op_valid.as_str().unwrap()).unwrap();
-                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}", feature, act_str, op_enum).as_str(), vec![activity_active_time_operator(&config.ocel, act_str, &op_enum)]));
+                        let act_str = param_str(act_valid)?;
+                        let op_enum = param_enum::<Operator>(op_valid)?;
+                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}", feature, act_str, op_enum).as_str(), vec![activity_active_time_operator(config.ocel, act_str, &op_enum)]));
                     }
                 }
             },
@@ -88,9 +91,9 @@ pub fn event_group_features(config: EventGroupConfig) -> DataFrame {
                     let op_val: Option<&Value> = f_params.get("operator");
                     let act_val: Option<&Value> = f_params.get("activity");
                     if let (Some(act_valid), Some(op_valid)) = (act_val, op_val) {
-                        let act_str = act_valid.as_str().unwrap();
-                        let op_enum = Operator::from_str(op_valid.as_str().unwrap()).unwrap();
-                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}", feature, act_str, op_enum).as_str(), vec![activity_wait_time_operator(&config.ocel, act_str, &op_enum)]));
+                        let act_str = param_str(act_valid)?;
+                        let op_enum = param_enum::<Operator>(op_valid)?;
+                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}", feature, act_str, op_enum).as_str(), vec![activity_wait_time_operator(config.ocel, act_str, &op_enum)]));
                     }
                 }
 
@@ -99,24 +102,23 @@ pub fn event_group_features(config: EventGroupConfig) -> DataFrame {
     }
 
 
-    DataFrame::new(series_vec).unwrap()
+    Ok(DataFrame::new(series_vec)?)
 }
 
 pub fn activity_counts(log: &Ocel) -> HashMap<String, usize> {
-    let mut activity_counter: HashMap<String, usize> = HashMap::new();
-    log.events.iter().for_each(|(_eid, values)| {
-                                    *activity_counter.entry(values.activity.clone()).or_insert(0) += 1;
-                                });
-
-    activity_counter
+    let index = log.index();
+    log.activity_table.names().enumerate()
+                      .map(|(act, name)| (name.to_owned(), index.events_of_activity(act as ActivityId).len()))
+                      .filter(|(_, count)| *count > 0)
+                      .collect()
 }
 
 pub fn activity_attr_operator(log: &Ocel, activity: &str, op: &Operator) -> HashMap<String, f64> {
     let mut activity_attrs: HashMap<String, Vec<f64>> = HashMap::new();
-    log.events.iter().filter(|(_eid, values)| values.activity == activity)
-                     .for_each(|(_eid, values)| {
-                         values.vmap.iter().for_each(|(attr, val)| {
-                             if let Some(valid) = val.as_f64() {
+    log.events_of_activity(activity).iter()
+                     .for_each(|eid| {
+                         log.events[eid].vmap.keys().for_each(|attr| {
+                             if let Some(valid) = log.event_attribute(eid, attr).and_then(|val| val.as_f64()) {
                                 activity_attrs.entry(attr.to_owned()).or_default().push(valid);
                              }
                          });
@@ -126,23 +128,21 @@ pub fn activity_attr_operator(log: &Ocel, activity: &str, op: &Operator) -> Hash
 
     activity_attrs.iter()
                   .for_each(|(attr, values)| {attr_operated.entry(attr.to_owned())
-                                                          .or_insert(op.execute(values.iter().map(|v| *v)).unwrap_or(0.0));
+                                                          .or_insert(op.execute(values.iter().copied()).unwrap_or(0.0));
                   });
     attr_operated
 }
 
 pub fn activity_otype_operator(log: &Ocel, op: &Operator) -> HashMap<String, HashMap<String, f64>> {
-    let mut activity_otype_vecmap: HashMap<String, HashMap<String, Vec<f64>>> = HashMap::new();
+    // objects missing from the log are counted under `None`
+    let mut activity_otype_vecmap: IntMap<ActivityId, HashMap<Option<ObjectTypeId>, Vec<f64>>> = IntMap::default();
     log.events.iter().for_each(|(_eid, values)| {
-        let mut omap_counts: HashMap<String, usize> = HashMap::new();
+        let mut omap_counts: HashMap<Option<ObjectTypeId>, usize> = HashMap::new();
         values.omap.iter().for_each(|oid| {
-            match log.objects.get(oid) {
-                Some(obj) => {*omap_counts.entry(obj.obj_type.clone()).or_insert(0) += 1},
-                None => {*omap_counts.entry("unknown".to_string()).or_insert(0) += 1}
-            }
+            *omap_counts.entry(log.objects.get(oid).map(|obj| obj.obj_type)).or_insert(0) += 1;
         });
         for (ot, amount) in omap_counts {
-            activity_otype_vecmap.entry(values.activity.clone()).or_default().entry(ot).or_default().push(amount as f64);
+            activity_otype_vecmap.entry(values.activity).or_default().entry(ot).or_default().push(amount as f64);
         }
 
     });
@@ -151,51 +151,50 @@ pub fn activity_otype_operator(log: &Ocel, op: &Operator) -> HashMap<String, Has
 
     activity_otype_vecmap.iter().for_each(|(act, othash)| {
         othash.iter()
-              .for_each(|(ot, vals)| {*activity_otype_opmap.entry(act.to_owned()).or_default().entry(ot.to_owned()).or_default() = op.execute(vals.iter().map(|v| *v)).unwrap_or(0.0)})
+              .for_each(|(ot, vals)| {
+                  let ot_name = ot.map_or("unknown", |ot| log.object_type_table.name(ot)).to_owned();
+                  *activity_otype_opmap.entry(log.activity_table.name(*act).to_owned()).or_default().entry(ot_name).or_default() = op.execute(vals.iter().copied()).unwrap_or(0.0)
+              })
     });
 
     activity_otype_opmap
 }
 
 pub fn activity_active_time_operator(log: &Ocel, act: &str, op: &Operator) -> f64 {
-    op.execute(log.events.iter()
-              .filter(|(_eid, values)| values.activity == act)
-              .map(|(eid, values)| {
-                  values.omap.iter()
-                             .filter(|oid| log.objects.contains_key(*oid))
-                             .map(|oid| {
-                                 let pos = log.objects.get(oid).expect("cannot fail").events.iter().position(|v| v == eid).unwrap();
-                                 let last_pos = log.objects.get(oid).expect("cannot fail").events.len() - 1;
-                                 if pos != last_pos {
-                                     let eid2 = log.objects.get(oid).expect("cannot fail").events.get(pos+1).expect("cannot fail");
-                                     (log.events[&eid2].timestamp - log.events[&eid].timestamp).num_milliseconds()
-                                 } else {
-                                     Duration::max_value().num_milliseconds()
-                                 }
-                             }).max().unwrap() as f64
+    op.execute(log.events_of_activity(act).iter()
+              .filter_map(|eid| {
+                  let ev = log.events.get(eid)?;
+                  ev.omap.iter()
+                         .filter_map(|oid| {
+                             let obj = log.objects.get(oid)?;
+                             let pos = log.lifecycle_position(*eid, *oid)?;
+                             match obj.events.get(pos + 1).and_then(|eid2| log.events.get(eid2)) {
+                                 Some(next) => Some((next.timestamp - ev.timestamp).num_milliseconds()),
+                                 None => Some(Duration::max_value().num_milliseconds())
+                             }
+                         }).max().map(|millis| millis as f64)
               })).unwrap_or(0.0)
 
 }
 
 pub fn activity_wait_time_operator(log: &Ocel, act: &str, op: &Operator) -> f64 {
-    op.execute(log.events.iter()
-              .filter(|(_eid, values)| values.activity == act)
-              .map(|(eid, values)| {
-                  values.omap.iter()
-                             .filter(|oid| log.objects.contains_key(*oid))
-                             .map(|oid| {
-                                 let pos = log.objects.get(oid).expect("cannot fail").events.iter().position(|v| v == eid).unwrap();
-                                 if pos != 0 {
-                                     let eid2 = log.objects.get(oid).expect("cannot fail").events.get(pos-1).expect("cannot fail");
-                                     (log.events[&eid].timestamp - log.events[&eid2].timestamp).num_milliseconds()
-                                 } else {
-                                     Duration::max_value().num_milliseconds()
-                                 }
-                             }).min().unwrap() as f64
+    op.execute(log.events_of_activity(act).iter()
+              .filter_map(|eid| {
+                  let ev = log.events.get(eid)?;
+                  ev.omap.iter()
+                         .filter_map(|oid| {
+                             let obj = log.objects.get(oid)?;
+                             let pos = log.lifecycle_position(*eid, *oid)?;
+                             match pos.checked_sub(1).and_then(|prev| obj.events.get(prev)).and_then(|eid2| log.events.get(eid2)) {
+                                 Some(previous) => Some((ev.timestamp - previous.timestamp).num_milliseconds()),
+                                 None => Some(Duration::max_value().num_milliseconds())
+                             }
+                         }).min().map(|millis| millis as f64)
               })).unwrap_or(0.0)
 
 }
 
+
 #[cfg(test)]
 mod tests {
     use crate::objects::{ocel::importer::import_ocel, ocdg::{generate_ocdg, Relations}};
@@ -204,7 +203,7 @@ mod tests {
 
     lazy_static::lazy_static!{
         static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
-        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &vec![Relations::DESCENDANTS]);
+        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &[Relations::DESCENDANTS]).unwrap();
     }
 
     #[test]
@@ -247,12 +246,41 @@ mod tests {
         assert_eq!(activity_wait_time_operator(&OCEL, "place order", &Operator::Max), Duration::max_value().num_milliseconds() as f64);
     }
 
+    #[test]
+    fn test_stale_index() {
+        let mut log = OCEL.clone();
+        log.index();
+        let oid = *log.object_map.get_by_left("o1").unwrap();
+        log.objects_mut().get_mut(&oid).unwrap().events.remove(0);
+        assert!(activity_wait_time_operator(&log, "receive payment", &Operator::Max) > 0.0);
+        assert!(activity_active_time_operator(&log, "place order", &Operator::Mean) > 0.0);
+
+        log.objects_mut().get_mut(&oid).unwrap().events.clear();
+        assert!(log.index().lifecycle_position(OCEL.objects[&oid].events[1], oid).is_none());
+    }
+
     #[test]
     fn test_user_facing_suite() {
-        let mut feature_vec: Vec<(EventGroup, Option<Value>)> = vec![];
-        feature_vec.push((EventGroup::ActivityCounts, None));
+        let feature_vec: Vec<(EventGroup, Option<Value>)> = vec![
+            (EventGroup::ActivityCounts, None)
+        ];
         let config = EventGroupConfig {ocel: &OCEL, ocdg: &OCDG, params: &feature_vec};
-        let res = event_group_features(config);
+        let res = event_group_features(config).expect("the parameters are valid");
         assert_eq!(res["ActivityCounts:\"pick item\":count"].sum::<i8>().unwrap(), 6);
     }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let feature_vec: Vec<(EventGroup, Option<Value>)> = vec![
+            (EventGroup::ActivityActiveTimeOperator, Some(serde_json::json!({"activity": "place order", "operator": "Average"})))
+        ];
+        let config = EventGroupConfig {ocel: &OCEL, ocdg: &OCDG, params: &feature_vec};
+        assert!(matches!(event_group_features(config), Err(Error::Parse(_))));
+
+        let feature_vec: Vec<(EventGroup, Option<Value>)> = vec![
+            (EventGroup::ActivityWaitTimeOperator, Some(serde_json::json!({"activity": 3, "operator": "Max"})))
+        ];
+        let config = EventGroupConfig {ocel: &OCEL, ocdg: &OCDG, params: &feature_vec};
+        assert!(matches!(event_group_features(config), Err(Error::Schema(_))));
+    }
 }
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
+use nohash_hasher::IntMap;
 use polars::prelude::{DataFrame, Series, NamedFrom};
 use rayon::prelude::*;
 use serde_json::Value;
 use strum::{EnumString, IntoStaticStr, Display, IntoEnumIterator};
 
-use crate::objects::{ocel::Ocel, ocdg::{Ocdg, Relations}, linker::link_objects};
+use crate::objects::{ocel::{Ocel, interner::ObjectTypeId}, ocdg::{Ocdg, Relations}, linker::link_objects};
+use crate::Error;
+use super::object_type_order;
 
 #[derive(EnumString, IntoStaticStr, Display, Hash, Eq, PartialEq, Debug)]
 pub enum EventPoint {
@@ -34,9 +37,11 @@ where
 }
 
 
-pub fn event_point_features(config: EventPointConfig) -> DataFrame {
+pub fn event_point_features(config: EventPointConfig) -> Result<DataFrame, Error> {
     // let object_linker = link_objects(&config.ocel.object_map, &config.ocdg.object_map);
-    let ev_str_vec: Vec<&str> = config.ocel.events.keys().map(|eid| config.ocel.event_map.get_by_right(eid).unwrap().as_str()).collect();
+    let ev_str_vec: Vec<&str> = config.ocel.events.keys()
+                                      .map(|eid| config.ocel.event_map.get_by_right(eid).map(|name| name.as_str()).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} has no id in the event map.", eid))))
+                                      .collect::<Result<_, Error>>()?;
 
     let mut series_vec: Vec<Series> = vec![Series::new("eids", ev_str_vec.clone())];
 
@@ -47,7 +52,7 @@ pub fn event_point_features(config: EventPointConfig) -> DataFrame {
                 feature_values.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                *v = relations_created_counts(&config.ocel, &config.ocdg, &i).iter().map(|rc| *rc as u64).collect();
+                                *v = relations_created_counts(config.ocel, config.ocdg, &i).iter().map(|rc| *rc as u64).collect();
                               });
 
                 for (v, rel) in transpose(feature_values).iter().zip(Relations::iter()) {
@@ -55,17 +60,16 @@ pub fn event_point_features(config: EventPointConfig) -> DataFrame {
                 }
             },
             EventPoint::OmapTypeCounts => {
-                let ot_order = config.ocel.global_log["ocel:object-types"].as_array().unwrap().to_vec();
-                let ot_order_str: Vec<&str> = ot_order.iter().map(|s| s.as_str().unwrap()).collect();
-                let mut feature_values: Vec<Vec<u64>> = vec![vec![0;ot_order.len()]; ev_str_vec.len()];
+                let ot_order_str: Vec<&str> = object_type_order(config.ocel)?;
+                let mut feature_values: Vec<Vec<u64>> = vec![vec![0;ot_order_str.len()]; ev_str_vec.len()];
                 feature_values.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  let feature = omap_type_counts(&config.ocel, &i);
+                                  let feature = omap_type_counts(config.ocel, &i);
                                   let map_to_vec = ot_order_str.iter().map(|ot| {
                                                                         match feature.get(*ot) {
                                                                             Some(res) => *res as u64,
-                                                                            None => 0 as u64
+                                                                            None => 0_u64
                                                                         }
                                                                         })
                                                                   .collect::<Vec<u64>>();
@@ -78,17 +82,16 @@ pub fn event_point_features(config: EventPointConfig) -> DataFrame {
                 }
             },
             EventPoint::OutputObjectTypeCounts => {
-                let ot_order = config.ocel.global_log["ocel:object-types"].as_array().unwrap().to_vec();
-                let ot_order_str: Vec<&str> = ot_order.iter().map(|s| s.as_str().unwrap()).collect();
-                let mut feature_values: Vec<Vec<u64>> = vec![vec![0;ot_order.len()]; ev_str_vec.len()];
+                let ot_order_str: Vec<&str> = object_type_order(config.ocel)?;
+                let mut feature_values: Vec<Vec<u64>> = vec![vec![0;ot_order_str.len()]; ev_str_vec.len()];
                 feature_values.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  let feature = output_object_type_count(&config.ocel, &i);
+                                  let feature = output_object_type_count(config.ocel, &i);
                                   let map_to_vec = ot_order_str.iter().map(|ot| {
                                                                         match feature.get(*ot) {
                                                                             Some(res) => *res as u64,
-                                                                            None => 0 as u64
+                                                                            None => 0_u64
                                                                         }
                                                                         })
                                                                   .collect::<Vec<u64>>();
@@ -102,17 +105,16 @@ pub fn event_point_features(config: EventPointConfig) -> DataFrame {
                 
             },
             EventPoint::InputObjectTypeCounts => {
-                let ot_order = config.ocel.global_log["ocel:object-types"].as_array().unwrap().to_vec();
-                let ot_order_str: Vec<&str> = ot_order.iter().map(|s| s.as_str().unwrap()).collect();
-                let mut feature_values: Vec<Vec<u64>> = vec![vec![0;ot_order.len()]; ev_str_vec.len()];
+                let ot_order_str: Vec<&str> = object_type_order(config.ocel)?;
+                let mut feature_values: Vec<Vec<u64>> = vec![vec![0;ot_order_str.len()]; ev_str_vec.len()];
                 feature_values.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  let feature = input_object_type_count(&config.ocel, &i);
+                                  let feature = input_object_type_count(config.ocel, &i);
                                   let map_to_vec = ot_order_str.iter().map(|ot| {
                                                                         match feature.get(*ot) {
                                                                             Some(res) => *res as u64,
-                                                                            None => 0 as u64
+                                                                            None => 0_u64
                                                                         }
                                                                         })
                                                                   .collect::<Vec<u64>>();
@@ -126,14 +128,14 @@ pub fn event_point_features(config: EventPointConfig) -> DataFrame {
 
             },
             EventPoint::ActivityOhe => {
-                let mut feature_values: Vec<Vec<u8>> = vec![vec![0;config.ocel.activities.len()];ev_str_vec.len()];
+                let mut feature_values: Vec<Vec<u8>> = vec![vec![0;config.ocel.activities().len()];ev_str_vec.len()];
                 feature_values.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                *v = activity_ohe(&config.ocel, &i).iter().map(|rc| *rc as u8).collect();
+                                *v = activity_ohe(config.ocel, &i).to_vec();
                               });
 
-                for (v, act) in transpose(feature_values).iter().zip(config.ocel.activities.iter()) {
+                for (v, act) in transpose(feature_values).iter().zip(config.ocel.activities()) {
                     series_vec.push(Series::new(format!("{:?}:{:?}:count", feature, act).as_str(), v));
                 }
                 
@@ -141,7 +143,7 @@ pub fn event_point_features(config: EventPointConfig) -> DataFrame {
         }
     }
 
-    DataFrame::new(series_vec).unwrap()
+    Ok(DataFrame::new(series_vec)?)
 }
 
 
@@ -151,11 +153,10 @@ pub fn relations_created_counts(log: &Ocel, ocdg: &Ocdg, eid: &usize) -> Vec<usi
 
     if let Some(e) = log.events.get(eid) {
         e.omap.iter()
-              .filter(|oid1| ocdg.irels.contains_key(linker.get_by_left(oid1).expect("cannot fail")))
-              .for_each(|oid1| {
-                  let oid1_convert = linker.get_by_left(oid1).expect("cannot fail");
+              .filter_map(|oid1| linker.get_by_left(oid1))
+              .for_each(|oid1_convert| {
                   if let Some(relsrc) = ocdg.irels.get(oid1_convert) {
-                      relsrc.iter().filter(|(oid2, _)| e.omap.contains(linker.get_by_right(oid2).expect("cannot fail")))
+                      relsrc.iter().filter(|(oid2, _)| linker.get_by_right(oid2).is_some_and(|oid2| e.omap.contains(oid2)))
                                    .for_each(|(_oid2, rels)| {
                                        for (rel, rel_events) in rels {
                                            if rel_events.contains(eid) {
@@ -170,59 +171,60 @@ pub fn relations_created_counts(log: &Ocel, ocdg: &Ocdg, eid: &usize) -> Vec<usi
 }
 
 pub fn omap_type_counts(log:&Ocel, eid: &usize) -> HashMap<String, usize> {
-    let mut omap_counts: HashMap<String, usize> = HashMap::new();
+    let mut type_counts: IntMap<ObjectTypeId, usize> = IntMap::default();
+    let mut unknown: usize = 0;
     if let Some(e) = log.events.get(eid) {
         e.omap.iter().for_each(|oid| {
             match log.objects.get(oid) {
-                Some(obj) => {*omap_counts.entry(obj.obj_type.clone()).or_insert(0) += 1},
-                None => {*omap_counts.entry("unknown".to_string()).or_insert(0) += 1}
+                Some(obj) => {*type_counts.entry(obj.obj_type).or_insert(0) += 1},
+                None => unknown += 1
             }
         });
     }
+    let mut omap_counts = type_names(log, type_counts);
+    if unknown > 0 {
+        omap_counts.insert("unknown".to_string(), unknown);
+    }
     omap_counts
 }
 
 
 pub fn output_object_type_count(log:&Ocel, eid: &usize) -> HashMap<String, usize> {
-    let mut otype_counts: HashMap<String, usize> = HashMap::new();
+    let mut otype_counts: IntMap<ObjectTypeId, usize> = IntMap::default();
     if let Some(e) = log.events.get(eid) {
-        e.omap.iter().filter(|oid|{
-                                if let Some(obj) = log.objects.get(oid) {
-                                    return obj.events.first().expect("cannot fail") == eid;
-                                }
-                                false
-                            })
-                     .for_each(|oid| {
-                         *otype_counts.entry(log.objects.get(oid).expect("cannot fail").obj_type.clone()).or_insert(0) += 1;
+        e.omap.iter().filter_map(|oid| log.objects.get(oid))
+                     .filter(|obj| obj.events.first() == Some(eid))
+                     .for_each(|obj| {
+                         *otype_counts.entry(obj.obj_type).or_insert(0) += 1;
                      });
     }
-    otype_counts
+    type_names(log, otype_counts)
 } 
 
 
 pub fn input_object_type_count(log:&Ocel, eid: &usize) -> HashMap<String, usize> {
-    let mut otype_counts: HashMap<String, usize> = HashMap::new();
+    let mut otype_counts: IntMap<ObjectTypeId, usize> = IntMap::default();
     if let Some(e) = log.events.get(eid) {
-        e.omap.iter().filter(|oid|{
-                                if let Some(obj) = log.objects.get(oid) {
-                                    return obj.events.first().expect("cannot fail") != eid;
-                                }
-                                false
-                            })
-                     .for_each(|oid| {
-                         *otype_counts.entry(log.objects.get(oid).expect("cannot fail").obj_type.clone()).or_insert(0) += 1;
+        e.omap.iter().filter_map(|oid| log.objects.get(oid))
+                     .filter(|obj| obj.events.first().is_some_and(|first| first != eid))
+                     .for_each(|obj| {
+                         *otype_counts.entry(obj.obj_type).or_insert(0) += 1;
                      });
     }
-    otype_counts
+    type_names(log, otype_counts)
+}
+
+// counts are kept per type id and only named for the output
+fn type_names(log: &Ocel, counts: IntMap<ObjectTypeId, usize>) -> HashMap<String, usize> {
+    counts.into_iter().map(|(obj_type, count)| (log.object_type_table.name(obj_type).to_owned(), count)).collect()
 }
 
 
 pub fn activity_ohe(log: &Ocel, eid: &usize) -> Vec<u8> {
-    let mut activity_bools: Vec<u8> = vec![0; log.activities.len()];
-    if let Some(e) = log.events.get(eid) {
-        if let Some(pos) = log.activities.iter().position(|act| e.activity.as_str() == act) {
-            activity_bools[pos] = 1;
-        }
+    let activities = log.index().activities();
+    let mut activity_bools: Vec<u8> = vec![0; activities.len()];
+    if let Some(pos) = log.events.get(eid).and_then(|e| activities.binary_search(&e.activity).ok()) {
+        activity_bools[pos] = 1;
     }
     activity_bools
 }
@@ -236,7 +238,7 @@ mod tests {
 
     lazy_static::lazy_static!{
         static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
-        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &vec![Relations::DESCENDANTS]);
+        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &[Relations::DESCENDANTS]).unwrap();
     }
 
     #[test]
@@ -261,12 +263,12 @@ mod tests {
     fn test_output_object_type_counts() {
         assert_eq!(output_object_type_count(&OCEL, &29)["package"], 1);
         assert_eq!(output_object_type_count(&OCEL, &32)["route"], 1);
-        assert_eq!(output_object_type_count(&OCEL, &1).is_empty(), true);
+        assert!(output_object_type_count(&OCEL, &1).is_empty());
     }
 
     #[test]
     fn test_input_object_type_counts() {
-        assert_eq!(input_object_type_count(&OCEL, &0).is_empty(), true);
+        assert!(input_object_type_count(&OCEL, &0).is_empty());
         assert_eq!(input_object_type_count(&OCEL, &33)["route"], 1);
         assert_eq!(input_object_type_count(&OCEL, &33)["package"], 1);
         assert_eq!(input_object_type_count(&OCEL, &33)["item"], 3);
@@ -278,8 +280,8 @@ mod tests {
         
         activity_ohe(&OCEL, &0).iter().enumerate().for_each(|(i, val)| {
             match val {
-                0 => {assert!(!correct.contains_key(&OCEL.activities[i].as_str()))},
-                _ => {assert_eq!(*val, correct[OCEL.activities[i].as_str()])}
+                0 => {assert!(!correct.contains_key(&OCEL.activities()[i]))},
+                _ => {assert_eq!(*val, correct[OCEL.activities()[i]])}
             }
         
         });
@@ -287,8 +289,8 @@ mod tests {
         let correct: HashMap<&str, u8> = HashMap::from_iter([("failed delivery", 1)]);
         activity_ohe(&OCEL, &18).iter().enumerate().for_each(|(i, val)| {
             match val {
-                0 => {assert!(!correct.contains_key(&OCEL.activities[i].as_str()))},
-                _ => {assert_eq!(*val, correct[OCEL.activities[i].as_str()])}
+                0 => {assert!(!correct.contains_key(&OCEL.activities()[i]))},
+                _ => {assert_eq!(*val, correct[OCEL.activities()[i]])}
             }
         
         });
@@ -296,11 +298,12 @@ mod tests {
     
     #[test]
     fn test_user_facing_suite() {
-        let mut feature_vec: Vec<(EventPoint, Option<Value>)> = vec![];
-        feature_vec.push((EventPoint::RelationCreatedCounts, None));
-        feature_vec.push((EventPoint::ActivityOhe, None));
+        let feature_vec: Vec<(EventPoint, Option<Value>)> = vec![
+            (EventPoint::RelationCreatedCounts, None),
+            (EventPoint::ActivityOhe, None)
+        ];
         let config = EventPointConfig {ocel: &OCEL, ocdg: &OCDG, params: &feature_vec};
-        let res = event_point_features(config);
+        let res = event_point_features(config).expect("the parameters are valid");
         assert_eq!(res["RelationCreatedCounts:DESCENDANTS:count"].sum::<usize>().unwrap(), 9);
         assert_eq!(res["ActivityOhe:\"place order\":count"].sum::<usize>().unwrap(), 3);
 
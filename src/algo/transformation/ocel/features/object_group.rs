@@ -1,7 +1,6 @@
-use std::str::FromStr;
 
 use ahash::AHashSet;
-use petgraph::{EdgeDirection::{Incoming, Outgoing}, graph::NodeIndex};
+use petgraph::{Direction, EdgeDirection::{Incoming, Outgoing}, graph::NodeIndex};
 use polars::prelude::{DataFrame, Series, NamedFrom};
 use serde_json::Value;
 use strum::{EnumString, IntoStaticStr, Display};
@@ -10,6 +9,8 @@ use strum::{EnumString, IntoStaticStr, Display};
 use crate::objects::{ocel::Ocel, ocdg::{Ocdg, Relations}};
 
 use super::operator::Operator;
+use super::{param_enum, param_str};
+use crate::Error;
 
 #[derive(EnumString, IntoStaticStr, Display, Hash, Eq, PartialEq, Debug)]
 pub enum ObjectGroup {
@@ -28,7 +29,7 @@ pub struct ObjectGroupConfig<'a> {
     pub params: &'a Vec<(ObjectGroup, Option<Value>)>
 }
 
-pub fn object_group_features(config: ObjectGroupConfig) -> DataFrame {
+pub fn object_group_features(config: ObjectGroupConfig) -> Result<DataFrame, Error> {
     // let object_linker = link_objects(&config.ocel.object_map, &config.ocdg.object_map);
     let mut series_vec: Vec<Series> = vec![];
 
@@ -37,8 +38,8 @@ pub fn object_group_features(config: ObjectGroupConfig) -> DataFrame {
             ObjectGroup::ObjectTypeCount => {
                 if let Some(f_params) = params {
                     if let Some(otype) = f_params.get("otype") {
-                        let otype_str = otype.as_str().unwrap();
-                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, otype).as_str(), vec![object_type_count(&config.ocel, otype_str) as u64]));
+                        let otype_str = param_str(otype)?;
+                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, otype).as_str(), vec![object_type_count(config.ocel, otype_str) as u64]));
                     }
                 }
             },
@@ -48,10 +49,10 @@ pub fn object_group_features(config: ObjectGroupConfig) -> DataFrame {
                     let attr: Option<&Value> = f_params.get("attr");
                     let op: Option<&Value> = f_params.get("op");
                     if let (Some(otype_valid), Some(attr_valid), Some(op_valid)) = (otype, attr, op) {
-                        let otype_str = otype_valid.as_str().unwrap();
-                        let attr_str = attr_valid.as_str().unwrap();
-                        let op_enum = Operator::from_str(op_valid.as_str().unwrap()).unwrap();
-                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}:{:?}", feature, otype_str, attr_str, op_enum).as_str(), vec![object_type_attr_operator(&config.ocel, otype_str, attr_str, &op_enum)]));
+                        let otype_str = param_str(otype_valid)?;
+                        let attr_str = param_str(attr_valid)?;
+                        let op_enum = param_enum::<Operator>(op_valid)?;
+                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}:{:?}", feature, otype_str, attr_str, op_enum).as_str(), vec![object_type_attr_operator(config.ocel, otype_str, attr_str, &op_enum)]));
                     } 
                 }
             },
@@ -61,27 +62,27 @@ pub fn object_group_features(config: ObjectGroupConfig) -> DataFrame {
                     let ot2: Option<&Value> = f_params.get("otype2");
                     let relation: Option<&Value> = f_params.get("relations");
                     if let (Some(ot1_valid), Some(ot2_valid), Some(relation_valid)) = (ot1, ot2, relation) {
-                        let ot1_str = ot1_valid.as_str().unwrap();
-                        let ot2_str = ot2_valid.as_str().unwrap();
-                        let relation_enum = Relations::from_str(relation_valid.as_str().unwrap()).unwrap();
+                        let ot1_str = param_str(ot1_valid)?;
+                        let ot2_str = param_str(ot2_valid)?;
+                        let relation_enum = param_enum::<Relations>(relation_valid)?;
 
-                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}:{:?}", feature, ot1_str, ot2_str, relation_enum).as_str(), vec![ot_ot_interactions(&config.ocdg, ot1_str, ot2_str, &relation_enum)]));
+                        series_vec.push(Series::new(format!("{:?}:{:?}:{:?}:{:?}", feature, ot1_str, ot2_str, relation_enum).as_str(), vec![ot_ot_interactions(config.ocdg, ot1_str, ot2_str, &relation_enum)?]));
                     }
                 }
             },
             ObjectGroup::RootNodeCount => {
                 if let Some(f_params) = params {
                     if let Some(otype_valid) = f_params.get("otype") {
-                        let otype_str = otype_valid.as_str().unwrap();
-                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, otype_str).as_str(), vec![root_node_count(&config.ocdg, otype_str) as u64]));
+                        let otype_str = param_str(otype_valid)?;
+                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, otype_str).as_str(), vec![root_node_count(config.ocdg, otype_str)? as u64]));
                     }
                 }
             },
             ObjectGroup::LeafNodeCount => {
                 if let Some(f_params) = params {
                     if let Some(otype_valid) = f_params.get("otype") {
-                        let otype_str = otype_valid.as_str().unwrap();
-                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, otype_str).as_str(), vec![leaf_node_count(&config.ocdg, otype_str) as u64]));
+                        let otype_str = param_str(otype_valid)?;
+                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, otype_str).as_str(), vec![leaf_node_count(config.ocdg, otype_str)? as u64]));
                     }
                 }
 
@@ -89,46 +90,67 @@ pub fn object_group_features(config: ObjectGroupConfig) -> DataFrame {
             ObjectGroup::SeparationComplexityOperator => {
                 if let Some(f_params) = params {
                     if let Some(op_valid) = f_params.get("operator") {
-                        let op_enum = Operator::from_str(op_valid.as_str().unwrap()).unwrap();
-                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, op_enum).as_str(), vec![separation_complexity_operator(&config.ocdg, &op_enum)]));
+                        let op_enum = param_enum::<Operator>(op_valid)?;
+                        series_vec.push(Series::new(format!("{:?}:{:?}", feature, op_enum).as_str(), vec![separation_complexity_operator(config.ocdg, &op_enum)]));
                     }
                 }
             }
         }
     }
-    DataFrame::new(series_vec).unwrap()
+    Ok(DataFrame::new(series_vec)?)
 }
 
 
 pub fn object_type_count(log: &Ocel, otype: &str) -> usize {
-    log.objects.iter().filter(|(_, values)| values.obj_type == otype).count() 
+    log.objects_of_type(otype).len()
 }
 
 pub fn object_type_attr_operator(log: &Ocel, otype: &str, attr: &str, op: &Operator) -> f64 {
-    op.execute(log.objects.iter()
-                          .filter(|(_, values)| values.obj_type == otype && values.ovmap.contains_key(attr))
-                          .map(|(_, values)| values.ovmap[attr].as_f64().unwrap())).unwrap()
+    op.execute(log.objects_of_type(otype).iter()
+                          .filter_map(|oid| log.object_attribute(oid, attr)?.as_f64())).unwrap_or(0.0)
 }
 
-pub fn ot_ot_interactions(ocdg: &Ocdg, ot1: &str, ot2: &str, relation: &Relations) -> u64 {
-   ocdg.inodes.iter()
-              .filter(|(oid, _node)| ocdg.node_attributes.get(oid).unwrap().node_type == ot1 && ocdg.irels.contains_key(oid))
-              .map(|(oid, _node)| ocdg.irels[oid].iter().filter(|(oid2, rel)| ocdg.node_attributes.get(oid2).unwrap().node_type == ot2 && rel.contains_key(&relation.relation_index())).count() as u64)
-              .sum() 
+pub fn ot_ot_interactions(ocdg: &Ocdg, ot1: &str, ot2: &str, relation: &Relations) -> Result<u64, Error> {
+    let mut interactions = 0;
+    for oid in ocdg.inodes.keys() {
+        let Some(targets) = ocdg.irels.get(oid) else {
+            continue;
+        };
+        if node_type(ocdg, oid)? != ot1 {
+            continue;
+        }
+        for (oid2, rels) in targets {
+            if node_type(ocdg, oid2)? == ot2 && rels.contains_key(&relation.relation_index()) {
+                interactions += 1;
+            }
+        }
+    }
+    Ok(interactions)
+}
+
+pub fn root_node_count(ocdg: &Ocdg, otype: &str) -> Result<usize, Error> {
+    unconnected_node_count(ocdg, otype, Incoming)
+}
+
+pub fn leaf_node_count(ocdg: &Ocdg, otype: &str) -> Result<usize, Error> {
+    unconnected_node_count(ocdg, otype, Outgoing)
 }
 
-pub fn root_node_count(ocdg: &Ocdg, otype: &str) -> usize {
-    ocdg.inodes.iter()
-               .filter(|(oid, _node)| ocdg.node_attributes.get(oid).unwrap().node_type == otype)
-               .filter(|(_oid, node)| ocdg.net.neighbors_directed(**node, Incoming).count() == 0)
-               .count()
+/// Nodes of the object type without edges in the direction.
+fn unconnected_node_count(ocdg: &Ocdg, otype: &str, direction: Direction) -> Result<usize, Error> {
+    let mut count = 0;
+    for (oid, node) in &ocdg.inodes {
+        if node_type(ocdg, oid)? == otype && ocdg.net.neighbors_directed(*node, direction).next().is_none() {
+            count += 1;
+        }
+    }
+    Ok(count)
 }
 
-pub fn leaf_node_count(ocdg: &Ocdg, otype: &str) -> usize {
-    ocdg.inodes.iter()
-               .filter(|(oid, _node)| ocdg.node_attributes.get(oid).unwrap().node_type == otype)
-               .filter(|(_oid, node)| ocdg.net.neighbors_directed(**node, Outgoing).count() == 0)
-               .count()
+fn node_type<'a>(ocdg: &'a Ocdg, oid: &usize) -> Result<&'a str, Error> {
+    ocdg.node_attributes.get(oid)
+                        .map(|attributes| attributes.node_type.as_str())
+                        .ok_or_else(|| Error::ReferentialIntegrity(format!("Node {} has no attributes.", oid)))
 }
 
 pub fn separation_complexity_operator(ocdg: &Ocdg, op: &Operator) -> f64 {
@@ -137,18 +159,17 @@ pub fn separation_complexity_operator(ocdg: &Ocdg, op: &Operator) -> f64 {
                                                                               .map(|(_oid, node)| node.to_owned()));
     let mut complexity_vec: Vec<usize> = vec![curr_nodes.len()];
 
-    while curr_nodes.len() != 0 {
+    while !curr_nodes.is_empty() {
         let new_nodes: AHashSet<NodeIndex> = AHashSet::from_iter(curr_nodes.iter()
-                                                                                .map(|node| ocdg.net.neighbors_directed(*node, Outgoing).into_iter()
-                                                                                                                                         .collect::<Vec<NodeIndex>>())
-                                                                                .flatten());
+                                                                                .flat_map(|node| ocdg.net.neighbors_directed(*node, Outgoing)
+                                                                                                                                         .collect::<Vec<NodeIndex>>()));
         
         complexity_vec.push(new_nodes.len());
         curr_nodes = new_nodes;
     }
     complexity_vec.pop(); // remove the zero
 
-    op.execute(complexity_vec.iter().map(|item| *item as f64)).unwrap()
+    op.execute(complexity_vec.iter().map(|item| *item as f64)).unwrap_or(0.0)
  
 }
 
@@ -165,7 +186,7 @@ mod tests {
 
     lazy_static::lazy_static!{
         static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
-        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &vec![Relations::DESCENDANTS]);
+        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &[Relations::DESCENDANTS]).unwrap();
     }
 
     #[test]
@@ -180,30 +201,46 @@ mod tests {
     fn test_object_type_attr_operator() {
         assert_eq!(object_type_attr_operator(&OCEL, "item", "price", &Operator::Max), 2000.0);
         assert_eq!(object_type_attr_operator(&OCEL, "package", "weight", &Operator::Mean), 10.0);
+
+        // a text value turns the attribute into a string attribute instead of failing
+        let mut log = OCEL.clone();
+        let oid = *log.object_map.get_by_left("i1").unwrap();
+        log.objects_mut().get_mut(&oid).unwrap().ovmap.insert("price".to_string(), json!("unknown"));
+        log.infer_schema();
+        assert_eq!(object_type_attr_operator(&log, "item", "price", &Operator::Max), 0.0);
     }
 
     #[test]
     fn test_ot_ot_interactions() {
-        assert_eq!(ot_ot_interactions(&OCDG, "item", "package", &Relations::DESCENDANTS), 6);
-        assert_eq!(ot_ot_interactions(&OCDG, "order", "item", &Relations::DESCENDANTS), 0);
-        assert_eq!(ot_ot_interactions(&OCDG, "package", "item", &Relations::DESCENDANTS), 0);
-        assert_eq!(ot_ot_interactions(&OCDG, "package", "route", &Relations::DESCENDANTS), 3);
+        assert_eq!(ot_ot_interactions(&OCDG, "item", "package", &Relations::DESCENDANTS).unwrap(), 6);
+        assert_eq!(ot_ot_interactions(&OCDG, "order", "item", &Relations::DESCENDANTS).unwrap(), 0);
+        assert_eq!(ot_ot_interactions(&OCDG, "package", "item", &Relations::DESCENDANTS).unwrap(), 0);
+        assert_eq!(ot_ot_interactions(&OCDG, "package", "route", &Relations::DESCENDANTS).unwrap(), 3);
     }
 
     #[test]
     fn test_root_node_count() {
-        assert_eq!(root_node_count(&OCDG, "order"), 3);
-        assert_eq!(root_node_count(&OCDG, "item"), 6);
-        assert_eq!(root_node_count(&OCDG, "package"), 0);
-        assert_eq!(root_node_count(&OCDG, "route"), 0);
+        assert_eq!(root_node_count(&OCDG, "order").unwrap(), 3);
+        assert_eq!(root_node_count(&OCDG, "item").unwrap(), 6);
+        assert_eq!(root_node_count(&OCDG, "package").unwrap(), 0);
+        assert_eq!(root_node_count(&OCDG, "route").unwrap(), 0);
     }
 
     #[test]
     fn test_leaf_node_count() {
-        assert_eq!(leaf_node_count(&OCDG, "order"), 3);
-        assert_eq!(leaf_node_count(&OCDG, "item"), 0);
-        assert_eq!(leaf_node_count(&OCDG, "package"), 0);
-        assert_eq!(leaf_node_count(&OCDG, "route"), 2);
+        assert_eq!(leaf_node_count(&OCDG, "order").unwrap(), 3);
+        assert_eq!(leaf_node_count(&OCDG, "item").unwrap(), 0);
+        assert_eq!(leaf_node_count(&OCDG, "package").unwrap(), 0);
+        assert_eq!(leaf_node_count(&OCDG, "route").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_missing_node_attributes() {
+        let mut ocdg = generate_ocdg(&OCEL, &[Relations::DESCENDANTS]).unwrap();
+        let oid = *ocdg.object_map.get_by_left("i1").unwrap();
+        ocdg.node_attributes.remove(&oid);
+        assert!(matches!(root_node_count(&ocdg, "item"), Err(Error::ReferentialIntegrity(_))));
+        assert!(matches!(ot_ot_interactions(&ocdg, "item", "package", &Relations::DESCENDANTS), Err(Error::ReferentialIntegrity(_))));
     }
 
     #[test]
@@ -214,10 +251,11 @@ mod tests {
 
     #[test]
     fn test_user_facing_suite() {
-        let mut feature_vec: Vec<(ObjectGroup, Option<Value>)> = vec![];
-        feature_vec.push((ObjectGroup::LeafNodeCount, Some(json!(HashMap::<&str, Option<&str>>::from_iter(vec![("otype", Some("order"))])))));
+        let feature_vec: Vec<(ObjectGroup, Option<Value>)> = vec![
+            (ObjectGroup::LeafNodeCount, Some(json!(HashMap::<&str, Option<&str>>::from_iter(vec![("otype", Some("order"))]))))
+        ];
         let config = ObjectGroupConfig {ocel: &OCEL, ocdg: &OCDG, params: &feature_vec};
-        let res = object_group_features(config);
+        let res = object_group_features(config).expect("the parameters are valid");
         assert_eq!(res["LeafNodeCount:\"order\""].sum::<i8>().unwrap(), 3);
 
     }
@@ -1,19 +1,21 @@
 use std::collections::HashMap;
-use std::str::FromStr;
-use ahash::{AHashSet, AHashMap};
+use ahash::AHashMap;
 use chrono::Duration;
 use petgraph::EdgeDirection::Outgoing;
 use itertools::Itertools;
+use nohash_hasher::IntSet;
 use petgraph::graph::NodeIndex;
 use polars::prelude::{Series, NamedFromOwned, DataFrame, NamedFrom};
 use serde_json::Value;
 use strum::{EnumString, Display, IntoStaticStr};
 use rayon::prelude::*;
 
-use crate::objects::ocel::Ocel;
+use crate::objects::ocel::{Ocel, interner::ActivityId};
 use crate::objects::ocdg::{Ocdg, Relations};
 use crate::algo::transformation::ocel::features::event_point::{input_object_type_count, output_object_type_count};
 use super::operator::Operator;
+use super::{object_type_order, param_enum, param_str};
+use crate::Error;
 
 #[derive(EnumString, IntoStaticStr, Display, Hash, Eq, PartialEq, Debug)]
 pub enum ObjectPoint {
@@ -32,7 +34,8 @@ pub enum ObjectPoint {
     ObjectDirectRelationCount,
     SubgraphExistenceCount,
     ObjectInputs,
-    ObjectOutputs
+    ObjectOutputs,
+    ObjectAttributeValue
 }
 
 
@@ -54,8 +57,11 @@ where
 }
 
 
-pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
-    let obj_str_vec: Vec<&str> = config.ocel.objects.keys().map(|oid| config.ocel.object_map.get_by_right(oid).unwrap().as_str()).collect();
+pub fn object_point_features(config: ObjectPointConfig) -> Result<DataFrame, Error> {
+    let oid_vec: Vec<usize> = config.ocel.objects.keys().copied().collect();
+    let obj_str_vec: Vec<&str> = oid_vec.iter()
+                                        .map(|oid| config.ocel.object_map.get_by_right(oid).map(|name| name.as_str()).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} has no id in the object map.", oid))))
+                                        .collect::<Result<_, Error>>()?;
 
     let mut series_vec: Vec<Series> = vec![Series::new("oids", obj_str_vec.clone())];
 
@@ -63,36 +69,37 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
         match feature {
             ObjectPoint::UniqueNeighborCount => {
                 let mut feature_vector: Vec<u64> = vec![0;obj_str_vec.len()];
+                let ocdg_oids = graph_oids(config.ocdg, &obj_str_vec)?;
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  *v = unique_neighbor_count(config.ocdg, config.ocdg.object_map.get_by_left(obj_str_vec[i]).unwrap()) as u64
+                                  *v = unique_neighbor_count(config.ocdg, &ocdg_oids[i]) as u64
                               });
                 
                 series_vec.push(Series::from_vec(feature.into(), feature_vector));
                 
             },
             ObjectPoint::ActivityExistence => {
-                let mut feature_vector: Vec<Vec<u8>> = vec![vec![0;config.ocel.activities.len()];obj_str_vec.len()];
+                let mut feature_vector: Vec<Vec<u8>> = vec![vec![0;config.ocel.activities().len()];obj_str_vec.len()];
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  *v = activity_existence(config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap());
+                                  *v = activity_existence(config.ocel, &oid_vec[i]);
                               });
                 
-                for (v, act) in transpose(feature_vector).iter().zip(&config.ocel.activities) {
+                for (v, act) in transpose(feature_vector).iter().zip(config.ocel.activities()) {
                     series_vec.push(Series::new(format!("{:?}:{:?}:exists", feature, act).as_str(), v));
                 }
             },
             ObjectPoint::ActivityExistenceCount => {
-                let mut feature_vector: Vec<Vec<u64>> = vec![vec![0;config.ocel.activities.len()];obj_str_vec.len()];
+                let mut feature_vector: Vec<Vec<u64>> = vec![vec![0;config.ocel.activities().len()];obj_str_vec.len()];
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  *v = activity_existence_count(config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap()).iter().map(|c| *c as u64).collect();
+                                  *v = activity_existence_count(config.ocel, &oid_vec[i]).iter().map(|c| *c as u64).collect();
                               });
                 
-                for (v, act) in transpose(feature_vector).iter().zip(&config.ocel.activities) {
+                for (v, act) in transpose(feature_vector).iter().zip(config.ocel.activities()) {
                     series_vec.push(Series::new(format!("{:?}:{:?}:count", feature, act).as_str(), v));
                 }
             },
@@ -101,7 +108,7 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  *v = object_lifetime(config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap()).num_milliseconds();
+                                  *v = object_lifetime(config.ocel, &oid_vec[i]).num_milliseconds();
                               });
                 series_vec.push(Series::from_vec(feature.into(), feature_vector));
             },
@@ -110,7 +117,7 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  *v = object_unit_set_ratio(config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap());
+                                  *v = object_unit_set_ratio(config.ocel, &oid_vec[i]);
                               });
                 series_vec.push(Series::from_vec(feature.into(), feature_vector));
             },
@@ -119,7 +126,7 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  *v = object_average_event_interaction(config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap());
+                                  *v = object_average_event_interaction(config.ocel, &oid_vec[i]);
                               });
                 series_vec.push(Series::from_vec(feature.into(), feature_vector));
             },
@@ -128,13 +135,13 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
                     let attr: Option<&Value> = f_params.get("attribute");
                     let op: Option<&Value> = f_params.get("operator");
                     if let (Some(attr_valid), Some(op_valid)) = (attr, op) {
-                        let attr_str = attr_valid.as_str().unwrap();
-                        let op_enum = Operator::from_str(op_valid.as_str().unwrap()).unwrap();
+                        let attr_str = param_str(attr_valid)?;
+                        let op_enum = param_enum::<Operator>(op_valid)?;
                         let mut feature_vector: Vec<f64> = vec![0.0; obj_str_vec.len()];
                         feature_vector.par_iter_mut()
                                       .enumerate()
                                       .for_each(|(i, v)| {
-                                        *v = activity_value_operator(&config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap(), attr_str, &op_enum);
+                                        *v = activity_value_operator(config.ocel, &oid_vec[i], attr_str, &op_enum);
                                       });
                         series_vec.push(Series::from_vec(format!("{:?}:{:?}:{:?}", feature, attr_str, op_enum).as_str(), feature_vector));
                     }
@@ -143,12 +150,13 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
             ObjectPoint::ObjectTypeInteraction => {
                 if let Some(f_params) = params {
                     if let Some(otype_valid) = f_params.get("object_type") {
-                        let otype_str = otype_valid.as_str().unwrap();
+                        let otype_str = param_str(otype_valid)?;
                         let mut feature_vector: Vec<u64> = vec![0;obj_str_vec.len()];
+                        let ocdg_oids = graph_oids(config.ocdg, &obj_str_vec)?;
                         feature_vector.par_iter_mut()
                                       .enumerate()
                                       .for_each(|(i, v)| {
-                                        *v = object_type_interaction(&config.ocdg, config.ocdg.object_map.get_by_left(obj_str_vec[i]).unwrap(), otype_str) as u64;
+                                        *v = object_type_interaction(config.ocdg, &ocdg_oids[i], otype_str) as u64;
                                       });
 
                         series_vec.push(Series::from_vec(format!("{:?}:{:?}", feature, otype_str).as_str(), feature_vector));
@@ -160,13 +168,13 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
                     let act1: Option<&Value> = f_params.get("activity_src");
                     let act2: Option<&Value> = f_params.get("activity_tar");
                     if let (Some(act1_valid), Some(act2_valid)) = (act1, act2) {
-                        let act1_str = act1_valid.as_str().unwrap();
-                        let act2_str = act2_valid.as_str().unwrap();
+                        let act1_str = param_str(act1_valid)?;
+                        let act2_str = param_str(act2_valid)?;
                         let mut feature_vector: Vec<i64> = vec![0;obj_str_vec.len()];
                         feature_vector.par_iter_mut()
                                       .enumerate()
                                       .for_each(|(i, v)| {
-                                        *v = object_wait_time(&config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap(), act1_str, act2_str).num_milliseconds();
+                                        *v = object_wait_time(config.ocel, &oid_vec[i], act1_str, act2_str).num_milliseconds();
                                       });
                         series_vec.push(Series::from_vec(format!("{:?}:{:?}:{:?}", feature, act1_str, act2_str).as_str(), feature_vector));
                     }
@@ -175,12 +183,13 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
             ObjectPoint::ObjectDirectRelationCount => {
                 if let Some(f_params) = params {
                     if let Some(relations_valid) = f_params.get("relations") {
-                        let rel_enum = Relations::from_str(relations_valid.as_str().unwrap()).unwrap();
+                        let rel_enum = param_enum::<Relations>(relations_valid)?;
                         let mut feature_vector: Vec<u64> = vec![0;obj_str_vec.len()];
+                        let ocdg_oids = graph_oids(config.ocdg, &obj_str_vec)?;
                         feature_vector.par_iter_mut()
                                       .enumerate()
                                       .for_each(|(i, v)| {
-                                        *v = object_direct_rel_count(&config.ocdg, config.ocdg.object_map.get_by_left(obj_str_vec[i]).unwrap(), &rel_enum) as u64;
+                                        *v = object_direct_rel_count(config.ocdg, &ocdg_oids[i], &rel_enum) as u64;
                                       });
                         series_vec.push(Series::from_vec(format!("{:?}:{:?}", feature, rel_enum).as_str(), feature_vector));
 
@@ -189,18 +198,17 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
 
             },
             ObjectPoint::ObjectEventsDirectlyFollows => {
-                let act_act_order: Vec<(&str, &str)> = config.ocel.activities.iter()
-                                                                             .cartesian_product(&config.ocel.activities)
-                                                                             .map(|(act1, act2)| {
-                                                                                (act1.as_str(), act2.as_str())
-                                                                             })
-                                                                             .collect();
+                let activities = config.ocel.activities();
+                let act_act_order: Vec<(&str, &str)> = activities.iter()
+                                                                 .cartesian_product(&activities)
+                                                                 .map(|(act1, act2)| (*act1, *act2))
+                                                                 .collect();
                 let mut feature_vector: Vec<Vec<u64>> = vec![vec![0;act_act_order.len()];obj_str_vec.len()];
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                let oe_df = object_events_directly_follows(&config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap());
-                                let oe_vec = (0..act_act_order.len()).into_iter()
+                                let oe_df = object_events_directly_follows(config.ocel, &oid_vec[i]);
+                                let oe_vec = (0..act_act_order.len())
                                                                      .map(|j| {
                                                                          let curr_pair = act_act_order[j];
                                                                          if let Some(ac1) = oe_df.get(curr_pair.0) {
@@ -220,17 +228,16 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
 
             },
             ObjectPoint::ObjectInputs => {
-                let ot_order = config.ocel.global_log["ocel:object-types"].as_array().unwrap().to_vec();
-                let ot_order_str: Vec<&str> = ot_order.iter().map(|s| s.as_str().unwrap()).collect();
-                let mut feature_vector: Vec<Vec<u64>> = vec![vec![0;ot_order.len()]; obj_str_vec.len()];
+                let ot_order_str: Vec<&str> = object_type_order(config.ocel)?;
+                let mut feature_vector: Vec<Vec<u64>> = vec![vec![0;ot_order_str.len()]; obj_str_vec.len()];
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  let feature = object_inputs(&config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap());
+                                  let feature = object_inputs(config.ocel, &oid_vec[i]);
                                   let map_to_vec = ot_order_str.iter().map(|ot| {
                                                                         match feature.get(*ot) {
                                                                             Some(res) => *res as u64,
-                                                                            None => 0 as u64
+                                                                            None => 0_u64
                                                                         }
                                                                         })
                                                                   .collect::<Vec<u64>>();
@@ -243,17 +250,16 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
 
             },
             ObjectPoint::ObjectOutputs => {
-                let ot_order = config.ocel.global_log["ocel:object-types"].as_array().unwrap().to_vec();
-                let ot_order_str: Vec<&str> = ot_order.iter().map(|s| s.as_str().unwrap()).collect();
-                let mut feature_vector: Vec<Vec<u64>> = vec![vec![0;ot_order.len()]; obj_str_vec.len()];
+                let ot_order_str: Vec<&str> = object_type_order(config.ocel)?;
+                let mut feature_vector: Vec<Vec<u64>> = vec![vec![0;ot_order_str.len()]; obj_str_vec.len()];
                 feature_vector.par_iter_mut()
                               .enumerate()
                               .for_each(|(i, v)| {
-                                  let feature = object_outputs(&config.ocel, config.ocel.object_map.get_by_left(obj_str_vec[i]).unwrap());
+                                  let feature = object_outputs(config.ocel, &oid_vec[i]);
                                   let map_to_vec = ot_order_str.iter().map(|ot| {
                                                                         match feature.get(*ot) {
                                                                             Some(res) => *res as u64,
-                                                                            None => 0 as u64
+                                                                            None => 0_u64
                                                                         }
                                                                         })
                                                                   .collect::<Vec<u64>>();
@@ -265,33 +271,59 @@ pub fn object_point_features(config: ObjectPointConfig) -> DataFrame {
                 }
                 
             },
+            ObjectPoint::ObjectAttributeValue => {
+                if let Some(f_params) = params {
+                    if let Some(attr_valid) = f_params.get("attribute") {
+                        let attr_str = param_str(attr_valid)?;
+                        let act_str: Option<&str> = f_params.get("activity").map(param_str).transpose()?;
+                        let mut feature_vector: Vec<Option<f64>> = vec![None; obj_str_vec.len()];
+                        feature_vector.par_iter_mut()
+                                      .enumerate()
+                                      .for_each(|(i, v)| {
+                                        *v = object_attribute_value(config.ocel, &oid_vec[i], attr_str, act_str);
+                                      });
+                        let name = match act_str {
+                            Some(act) => format!("{:?}:{:?}:{:?}", feature, attr_str, act),
+                            None => format!("{:?}:{:?}", feature, attr_str)
+                        };
+                        series_vec.push(Series::new(name.as_str(), feature_vector));
+                    }
+                }
+            },
             _ => {}
         }
     }
-    DataFrame::new(series_vec).unwrap()
+    Ok(DataFrame::new(series_vec)?)
+}
+
+/// Node ids of the objects in the graph, which need not be the ids of the log.
+fn graph_oids(ocdg: &Ocdg, obj_str_vec: &[&str]) -> Result<Vec<usize>, Error> {
+    obj_str_vec.iter()
+               .map(|name| ocdg.object_map.get_by_left(*name).copied().ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not a node of the graph.", name))))
+               .collect()
 }
 
 pub fn unique_neighbor_count(ocdg: &Ocdg, oid: &usize) -> usize {
     let curr_oid: NodeIndex = ocdg.inodes[oid];
-    ocdg.net.neighbors_directed(curr_oid, Outgoing).into_iter()
+    ocdg.net.neighbors_directed(curr_oid, Outgoing)
                                                    .unique()
                                                    .count()
 }
 
 pub fn activity_existence(log: &Ocel, oid: &usize) -> Vec<u8> {
-    let oe_activities: AHashSet<&String> = AHashSet::from_iter(log.objects[&oid].events.iter()
-                                            .map(|oe| &log.events[&oe].activity));
-    log.activities.iter()
-                   .map(|act| {if oe_activities.contains(act) {1} else {0}})
-                   .collect_vec()
+    let oe_activities: IntSet<ActivityId> = IntSet::from_iter(log.objects[oid].events.iter()
+                                            .map(|oe| log.events[oe].activity));
+    log.index().activities().iter()
+                           .map(|act| {if oe_activities.contains(act) {1} else {0}})
+                           .collect_vec()
 }
 
 
 pub fn activity_existence_count(log: &Ocel, oid: &usize) -> Vec<usize> {
-    let oe_activities: HashMap<&String, usize> = log.objects[&oid].events.iter()
-                                                                         .map(|oe| &log.events[&oe].activity)
+    let oe_activities: HashMap<ActivityId, usize> = log.objects[oid].events.iter()
+                                                                         .map(|oe| log.events[oe].activity)
                                                                          .counts();
-    log.activities.iter()
+    log.index().activities().iter()
               .map(|act| {match oe_activities.get(act) {
                             Some(v) => *v,
                             None => 0
@@ -300,27 +332,36 @@ pub fn activity_existence_count(log: &Ocel, oid: &usize) -> Vec<usize> {
 }
 
 pub fn activity_value_operator(log: &Ocel, oid: &usize, attr: &str, op: &Operator) -> f64 {
-    op.execute(log.objects[&oid].events.iter()
-                            .filter(|oe| log.events[&oe].vmap.contains_key(&attr.to_string()))
-                            .map(|oe| match &log.events[&oe].vmap[&attr.to_string()] {
-                                        Value::Number(v) => v.as_f64().unwrap(),
-                                        _ => 0.0
-                                    })).unwrap()
+    op.execute(log.objects[oid].events.iter()
+                            .filter(|oe| log.events[oe].vmap.contains_key(attr))
+                            .map(|oe| log.event_attribute(oe, attr).and_then(|val| val.as_f64()).unwrap_or(0.0))).unwrap_or(0.0)
 
 }
 
 pub fn object_type_relations_value_operator() {todo!();}
 
-pub fn object_lifetime(log: &Ocel, oid: &usize) -> Duration {
-    if let Some(node) = log.objects.get(oid) {
-        let initial = node.events.first().unwrap();
-        let end = node.events.last().unwrap();
+/// Numeric value of an attribute as of the first event of the object with the activity,
+/// the initial value if there is no activity.
+pub fn object_attribute_value(log: &Ocel, oid: &usize, attr: &str, activity: Option<&str>) -> Option<f64> {
+    let value = match activity {
+        Some(act) => {
+            let act = log.activity_id(act)?;
+            let eid = log.objects.get(oid)?.events.iter().find(|eid| log.events[eid].activity == act)?;
+            log.object_attribute_at(oid, attr, &log.events[eid].timestamp)
+        },
+        None => log.object_attribute(oid, attr)
+    };
+    value?.as_f64()
+}
 
-        if log.events.contains_key(&initial) && log.events.contains_key(&end) {
-            return log.events[&end].timestamp - log.events[&initial].timestamp;
-        }
+pub fn object_lifetime(log: &Ocel, oid: &usize) -> Duration {
+    let Some(node) = log.objects.get(oid) else {
+        return Duration::zero();
+    };
+    match (node.events.first().and_then(|eid| log.events.get(eid)), node.events.last().and_then(|eid| log.events.get(eid))) {
+        (Some(initial), Some(end)) => end.timestamp - initial.timestamp,
+        _ => Duration::zero()
     }
-    Duration::zero()
 }
 
 pub fn object_unit_set_ratio(log: &Ocel, oid: &usize) -> f64 {
@@ -337,7 +378,7 @@ pub fn object_unit_set_ratio(log: &Ocel, oid: &usize) -> f64 {
                                 return 0;
                               }
                               1
-                          }).fold(0, |accum, item| accum + item);
+                          }).sum::<i32>();
 
         return unitset as f64 / node.events.len() as f64
     }
@@ -352,7 +393,7 @@ pub fn object_average_event_interaction(log: &Ocel, oid: &usize) -> f64 {
                                             return log.events[ev].omap.len() - 1;
                                         }
                                         0})
-                                     .fold(0, |accum, item| accum + item);
+                                     .sum::<usize>();
 
         return interaction as f64 / node.events.len() as f64
 
@@ -365,7 +406,7 @@ pub fn object_type_interaction(ocdg: &Ocdg, oid: &usize, otype: &str) -> usize {
         let neighs = ocdg.net.neighbors_directed(*node, Outgoing);
         return neighs.map(|oid2| {if oid != &ocdg.net[oid2] 
                               && otype == ocdg.node_attributes[&ocdg.net[oid2]].node_type {1} else {0}})
-              .fold(0, |accum, item| accum + item);
+              .sum();
 
     }
     0
@@ -375,23 +416,24 @@ pub fn object_type_interaction(ocdg: &Ocdg, oid: &usize, otype: &str) -> usize {
 pub fn object_events_directly_follows(log: &Ocel, oid: &usize) -> AHashMap<String, AHashMap<String, usize>> {
     let mut df: AHashMap<String, AHashMap<String, usize>> = AHashMap::default();
     if let Some(obj) = log.objects.get(oid) {
-        (0..obj.events.len() - 1).into_iter()
-                                 .for_each(|i| {
-                                    let src = &log.events[&obj.events[i]].activity;
-                                    let tar = &log.events[&obj.events[i+1]].activity;
-                                    let df_srctar = df.entry(src.to_owned())
-                                      .or_insert(AHashMap::default())
-                                      .entry(tar.to_owned())
-                                      .or_insert(0);
-
-                                    *df_srctar += 1;
-                                 });
+        for pair in obj.events.windows(2) {
+            let (Some(src), Some(tar)) = (log.events.get(&pair[0]), log.events.get(&pair[1])) else {
+                continue;
+            };
+            *df.entry(log.activity(src).to_owned())
+               .or_default()
+               .entry(log.activity(tar).to_owned())
+               .or_insert(0) += 1;
+        }
     }
     df
 }
 
 pub fn object_wait_time(log: &Ocel, oid: &usize, act1: &str, act2: &str) -> Duration {
     let mut time_diff = Duration::zero();
+    let (Some(act1), Some(act2)) = (log.activity_id(act1), log.activity_id(act2)) else {
+        return time_diff;
+    };
     if let Some(obj) = log.objects.get(oid) {
         let mut ev1: usize = usize::MAX;
         let mut ev2: usize = usize::MAX;
@@ -401,15 +443,14 @@ pub fn object_wait_time(log: &Ocel, oid: &usize, act1: &str, act2: &str) -> Dura
                     if curr.activity == act2 {
                         ev2 = *item; 
                     } 
-                } else if ev1 == usize::MAX {
-                    if curr.activity == act1 {
+                } else if ev1 == usize::MAX
+                    && curr.activity == act1 {
                         ev1 = *item;
                         time_diff = log.events[&ev2].timestamp - log.events[&ev1].timestamp;
                         if time_diff < Duration::zero() {
                             time_diff = Duration::zero();
                         }
-                    }
-                } 
+                    } 
             }
         });
     }
@@ -421,7 +462,7 @@ pub fn object_oe_root(log: &Ocel, oid: &usize) -> bool {
         if let Some(root_ev) = obj.events.first() {
             let root1 = &log.events[root_ev];
             for oid2 in root1.omap.iter() {
-                if let Some(other) = log.objects.get(&oid2) {
+                if let Some(other) = log.objects.get(oid2) {
                     if let Some(root_ev2) = other.events.first() {
                         let root2 = &log.events[root_ev2];
                         if root1.timestamp > root2.timestamp {
@@ -447,7 +488,7 @@ pub fn object_oe_leaf(log: &Ocel, oid: &usize) -> bool {
         if let Some(leaf_ev) = obj.events.last() {
             let leaf1 = &log.events[leaf_ev];
             for oid2 in leaf1.omap.iter() {
-                if let Some(other) = log.objects.get(&oid2) {
+                if let Some(other) = log.objects.get(oid2) {
                     if let Some(leaf_ev2) = other.events.last() {
                         let leaf2 = &log.events[leaf_ev2];
                         if leaf1.timestamp < leaf2.timestamp {
@@ -470,15 +511,15 @@ pub fn object_oe_leaf(log: &Ocel, oid: &usize) -> bool {
 pub fn object_direct_rel_count(ocdg: &Ocdg, oid: &usize, rel: &Relations) -> usize {
     if let Some(obj) = ocdg.inodes.get(oid) {
         let neighs = ocdg.net.neighbors_directed(*obj, Outgoing);
-        return neighs.enumerate().map(|(_i, neigh)| {
+        return neighs.map(|neigh| {
             let neigh_id = &ocdg.net[neigh];
-            let conn = ocdg.irels.get(oid).unwrap().get(neigh_id).unwrap();
-            if conn.contains_key(&(rel.relation_index())) {
+            let conn = ocdg.irels.get(oid).and_then(|targets| targets.get(neigh_id));
+            if conn.is_some_and(|conn| conn.contains_key(&(rel.relation_index()))) {
                 1
             } else {
                 0
             }
-        }).fold(0 as usize, |accum, item| accum + item);
+        }).sum();
         
     }
 0
@@ -486,21 +527,19 @@ pub fn object_direct_rel_count(ocdg: &Ocdg, oid: &usize, rel: &Relations) -> usi
 
 
 pub fn object_inputs(log: &Ocel, oid: &usize) -> HashMap<String, usize> {
-    if let Some(obj_src) = log.objects.get(oid) {
-        let first_event = obj_src.events.first().unwrap(); 
-        input_object_type_count(log, first_event)
-    } else {
-        HashMap::<String, usize>::new()
+    match log.objects.get(oid).and_then(|obj_src| obj_src.events.first()) {
+        Some(first_event) => input_object_type_count(log, first_event),
+        None => HashMap::<String, usize>::new()
     }
 }
 
 pub fn object_outputs(log: &Ocel, oid: &usize) -> HashMap<String, usize> {
     let mut obj_outputs = HashMap::<String, usize>::new();
     if let Some(obj_src) = log.objects.get(oid) {
-        for ev in &obj_src.events[1..] {
+        for ev in obj_src.events.iter().skip(1) {
             for (k, v) in output_object_type_count(log, ev) {
                 let ot_entry = obj_outputs.entry(k.clone()).or_default();
-                if k == obj_src.obj_type {
+                if k == log.object_type(obj_src) {
                     *ot_entry += v - 1; // remove self
                 } else {
                     *ot_entry += v
@@ -520,7 +559,7 @@ mod tests {
 
     use serde_json::json;
 
-    use crate::objects::{ocel::importer::import_ocel, ocdg::generate_ocdg};
+    use crate::objects::{ocel::importer::{import_ocel, import_ocel_from_str}, ocdg::generate_ocdg};
 
     use super::*;
 
@@ -528,7 +567,7 @@ mod tests {
 
     lazy_static::lazy_static!{
         static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
-        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &vec![Relations::INTERACTS]);
+        static ref OCDG: Ocdg = generate_ocdg(&import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?"), &[Relations::INTERACTS]).unwrap();
     }
 
 
@@ -545,8 +584,8 @@ mod tests {
         assert_eq!(activity_existence(&OCEL, oid).iter().sum::<u8>(), 5);
         activity_existence(&OCEL, oid).iter().enumerate().for_each(|(i, val)| {
             match val {
-                0 => {assert!(!correct.contains(&OCEL.activities[i].as_str()))},
-                _ => {assert!(correct.contains(&OCEL.activities[i].as_str()))}
+                0 => {assert!(!correct.contains(&OCEL.activities()[i]))},
+                _ => {assert!(correct.contains(&OCEL.activities()[i]))}
             }
         });
     }
@@ -559,8 +598,8 @@ mod tests {
 
         activity_existence_count(&OCEL, oid).iter().enumerate().for_each(|(i, val)| {
             match val {
-                0 => {assert!(!correct.contains_key(&OCEL.activities[i].as_str()))},
-                _ => {assert_eq!(*val, correct[OCEL.activities[i].as_str()])}
+                0 => {assert!(!correct.contains_key(&OCEL.activities()[i]))},
+                _ => {assert_eq!(*val, correct[OCEL.activities()[i]])}
             }
         
         });
@@ -573,12 +612,54 @@ mod tests {
         assert_eq!(activity_value_operator(&OCEL, oid, attr, &Operator::Max), 1000.0); 
     }
 
+    #[test]
+    fn test_object_attribute_value() {
+        let mut log = OCEL.clone();
+        let oid = *log.object_map.get_by_left("i1").expect("cannot fail");
+        let paid = *log.objects[&oid].events.iter().find(|eid| log.activity(&log.events[eid]) == "receive payment").unwrap();
+        let initial = log.objects[&oid].ovmap["price"].as_f64().unwrap();
+        let changed_at = log.events[&paid].timestamp;
+        log.objects_mut().get_mut(&oid).unwrap().history.insert("price".to_string(), vec![(changed_at, json!(initial + 1.0))]);
+
+        assert_eq!(object_attribute_value(&log, &oid, "price", None), Some(initial));
+        assert_eq!(object_attribute_value(&log, &oid, "price", Some("place order")), Some(initial));
+        assert_eq!(object_attribute_value(&log, &oid, "price", Some("receive payment")), Some(initial + 1.0));
+        assert_eq!(object_attribute_value(&log, &oid, "price", Some("not an activity")), None);
+    }
+
     #[test]
     fn test_object_lifetime() {
         let oid = OCEL.object_map.get_by_left("i1").expect("cannot fail");
         assert_eq!(object_lifetime(&OCEL, oid).num_milliseconds(), 1980000);
     }
 
+    #[test]
+    fn test_object_without_events() {
+        let mut log = OCEL.clone();
+        let oid = *log.object_map.get_by_left("i1").expect("cannot fail");
+        log.objects_mut().get_mut(&oid).unwrap().events.clear();
+        assert_eq!(activity_value_operator(&log, &oid, "prepaid-amount", &Operator::Max), 0.0);
+        assert_eq!(activity_value_operator(&log, &oid, "prepaid-amount", &Operator::Median), 0.0);
+        assert_eq!(object_lifetime(&log, &oid), Duration::zero());
+        assert!(object_inputs(&log, &oid).is_empty());
+        assert!(object_outputs(&log, &oid).is_empty());
+        assert!(object_events_directly_follows(&log, &oid).is_empty());
+    }
+
+    #[test]
+    fn test_declared_object_without_events() {
+        let input = r#"{"ocel:global-log": {}, "ocel:global-event": {}, "ocel:global-object": {},
+            "ocel:objects": {"o1": {"ocel:type": "order", "ocel:ovmap": {}}, "o2": {"ocel:type": "order", "ocel:ovmap": {}}},
+            "ocel:events": {"e1": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T10:00:00Z", "ocel:omap": ["o1"], "ocel:vmap": {}},
+                            "e2": {"ocel:activity": "b", "ocel:timestamp": "2022-01-01T11:00:00Z", "ocel:omap": ["o1"], "ocel:vmap": {}}}}"#;
+        let log = import_ocel_from_str(input).unwrap();
+        let o1 = log.object_map.get_by_left("o1").unwrap();
+        let o2 = log.object_map.get_by_left("o2").unwrap();
+        assert_eq!(object_events_directly_follows(&log, o1)["a"]["b"], 1);
+        assert!(object_events_directly_follows(&log, o2).is_empty());
+        assert_eq!(object_lifetime(&log, o2), Duration::zero());
+    }
+
     #[test]
     fn test_unit_set_ratio() {
         let oid = OCEL.object_map.get_by_left("i1").expect("cannot fail");
@@ -631,20 +712,20 @@ mod tests {
     #[test]
     fn test_object_oe_leaf() {
         let oid = OCEL.object_map.get_by_left("o3").expect("cannot fail");
-        assert_eq!(object_oe_leaf(&OCEL, oid), true);
+        assert!(object_oe_leaf(&OCEL, oid));
 
         let oid = OCEL.object_map.get_by_left("r1").expect("cannot fail");
-        assert_eq!(object_oe_leaf(&OCEL, oid), false);
+        assert!(!object_oe_leaf(&OCEL, oid));
         
     }
 
     #[test]
     fn test_object_oe_root() {
         let oid = OCEL.object_map.get_by_left("o3").expect("cannot fail");
-        assert_eq!(object_oe_root(&OCEL, oid), true);
+        assert!(object_oe_root(&OCEL, oid));
 
         let oid = OCEL.object_map.get_by_left("r1").expect("cannot fail");
-        assert_eq!(object_oe_root(&OCEL, oid), false);
+        assert!(!object_oe_root(&OCEL, oid));
 
     }
 
@@ -661,38 +742,38 @@ mod tests {
     fn test_object_inputs() {
         let oid = OCEL.object_map.get_by_left("o3").expect("cannot fail");
         let obj_inputs_1 = object_inputs(&OCEL, oid);
-        assert_eq!(obj_inputs_1.is_empty(), true);
+        assert!(obj_inputs_1.is_empty());
 
         let oid = OCEL.object_map.get_by_left("r1").expect("cannot fail");
         let obj_inputs_2 = object_inputs(&OCEL, oid);
-        assert_eq!(obj_inputs_2.is_empty(), false);
-        assert_eq!(obj_inputs_2.contains_key("package"), true);
-        assert_eq!(*obj_inputs_2.get("package").unwrap() as usize, 1);
+        assert!(!obj_inputs_2.is_empty());
+        assert!(obj_inputs_2.contains_key("package"));
+        assert_eq!(*obj_inputs_2.get("package").unwrap(), 1);
 
         let oid = OCEL.object_map.get_by_left("p1").expect("cannot fail");
         let obj_inputs_3 = object_inputs(&OCEL, oid);
-        assert_eq!(obj_inputs_3.is_empty(), false);
-        assert_eq!(obj_inputs_3.contains_key("order"), false);
-        assert_eq!(obj_inputs_3.contains_key("item"), true);
-        assert_eq!(*obj_inputs_3.get("item").unwrap() as usize, 3);
+        assert!(!obj_inputs_3.is_empty());
+        assert!(!obj_inputs_3.contains_key("order"));
+        assert!(obj_inputs_3.contains_key("item"));
+        assert_eq!(*obj_inputs_3.get("item").unwrap(), 3);
     }
 
     #[test]
     fn test_object_outputs() {
         let oid = OCEL.object_map.get_by_left("o3").expect("cannot fail");
         let obj_outputs_1 = object_outputs(&OCEL, oid);
-        assert_eq!(obj_outputs_1.is_empty(), true);
+        assert!(obj_outputs_1.is_empty());
 
         let oid = OCEL.object_map.get_by_left("r1").expect("cannot fail");
         let obj_outputs_2 = object_outputs(&OCEL, oid);
-        assert_eq!(obj_outputs_2.is_empty(), true);
+        assert!(obj_outputs_2.is_empty());
 
         let oid = OCEL.object_map.get_by_left("p1").expect("cannot fail");
         let obj_outputs_3 = object_outputs(&OCEL, oid);
-        assert_eq!(obj_outputs_3.is_empty(), false);
-        assert_eq!(obj_outputs_3.contains_key("order"), false);
-        assert_eq!(obj_outputs_3.contains_key("route"), true);
-        assert_eq!(*obj_outputs_3.get("route").unwrap() as usize, 2);
+        assert!(!obj_outputs_3.is_empty());
+        assert!(!obj_outputs_3.contains_key("order"));
+        assert!(obj_outputs_3.contains_key("route"));
+        assert_eq!(*obj_outputs_3.get("route").unwrap(), 2);
     }
 
     #[test]
@@ -703,7 +784,7 @@ mod tests {
         feature_vec.push((ObjectPoint::ObjectInputs, None));
         feature_vec.push((ObjectPoint::ObjectOutputs, None));
         let config = ObjectPointConfig {ocel: &OCEL, ocdg: &OCDG, params: &feature_vec};
-        let res = object_point_features(config);
+        let res = object_point_features(config).expect("the parameters are valid");
         // println!("{}",res);
         assert_eq!(res["UniqueNeighborCount"].sum::<usize>().unwrap(), 66);
         assert_eq!(res["ObjectInputs:\"package\":count"].sum::<usize>().unwrap(), 3);
@@ -35,7 +35,7 @@ impl Operator {
             }, 
             Operator::Median => {
                 let ordered_iterator = input_iter.sorted_by(|a,b| a.partial_cmp(b).expect("A NaN value got into the iterator!"));
-                T::from_f64(stats::median(ordered_iterator).unwrap())
+                stats::median(ordered_iterator).and_then(T::from_f64)
             },
             // any order
             Operator::Mode => {
@@ -95,8 +95,8 @@ mod tests {
         
         assert!((op.execute(iov.iter().map(|i| *i as f64)).unwrap().to_owned() - 5.0).abs() < ERROR);
         assert!((op.execute(ipv.iter().map(|i| *i as f64)).unwrap().to_owned() - 4.6).abs() < ERROR);
-        assert!((op.execute(fov.iter().map(|i| *i)).unwrap().to_owned() - 3.3).abs() < ERROR);
-        assert!((op.execute(fpv.iter().map(|i| *i)).unwrap().to_owned() - 3.12).abs() < ERROR);
+        assert!((op.execute(fov.iter().copied()).unwrap().to_owned() - 3.3).abs() < ERROR);
+        assert!((op.execute(fpv.iter().copied()).unwrap().to_owned() - 3.12).abs() < ERROR);
 
         // mess with ordering
         let riov: Vec<&i32> = iov.iter().rev().collect();
@@ -121,8 +121,8 @@ mod tests {
         
         assert!((op.execute(iov.iter().map(|i| *i as f64)).unwrap().to_owned() - 5.0).abs() < ERROR);
         assert!((op.execute(ipv.iter().map(|i| *i as f64)).unwrap().to_owned() - 5.0).abs() < ERROR);
-        assert!((op.execute(fov.iter().map(|i| *i)).unwrap().to_owned() - 3.3).abs() < ERROR);
-        assert!((op.execute(fpv.iter().map(|i| *i)).unwrap().to_owned() - 3.3).abs() < ERROR);
+        assert!((op.execute(fov.iter().copied()).unwrap().to_owned() - 3.3).abs() < ERROR);
+        assert!((op.execute(fpv.iter().copied()).unwrap().to_owned() - 3.3).abs() < ERROR);
 
         // mess with ordering
         let riov: Vec<&i32> = iov.iter().rev().collect();
@@ -147,8 +147,8 @@ mod tests {
         
         assert_eq!(op.execute(iov.iter().map(|i| *i as f64)), None);
         assert!((op.execute(ipv.iter().map(|i| *i as f64)).unwrap().to_owned() - 5.0).abs() < ERROR);
-        assert_eq!(op.execute(fov.iter().map(|i| *i as f64)), None);
-        assert!((op.execute(fpv.iter().map(|i| *i)).unwrap().to_owned() - 3.3).abs() < ERROR);
+        assert_eq!(op.execute(fov.iter().copied()), None);
+        assert!((op.execute(fpv.iter().copied()).unwrap().to_owned() - 3.3).abs() < ERROR);
 
         // mess with ordering
         let riov: Vec<&i32> = iov.iter().rev().collect();
@@ -158,7 +158,7 @@ mod tests {
         
         assert_eq!(op.execute(riov.iter().map(|i| **i as f64)), None);
         assert!((op.execute(ripv.iter().map(|i| **i as f64)).unwrap().to_owned() - 5.0).abs() < ERROR);
-        assert_eq!(op.execute(rfov.iter().map(|i| **i as f64)), None);
+        assert_eq!(op.execute(rfov.iter().map(|i| **i)), None);
         assert!((op.execute(rfpv.iter().map(|i| **i)).unwrap().to_owned() - 3.3).abs() < ERROR);
     }
 
@@ -173,8 +173,8 @@ mod tests {
 
         assert!((op.execute(iov.iter().map(|i| *i as f64)).unwrap().to_owned() - 2.82842712).abs() < ERROR);
         assert!((op.execute(ipv.iter().map(|i| *i as f64)).unwrap().to_owned() - 2.65329983).abs() < ERROR);
-        assert!((op.execute(fov.iter().map(|i| *i)).unwrap().to_owned() - 1.2727922061).abs() < ERROR);
-        assert!((op.execute(fpv.iter().map(|i| *i)).unwrap().to_owned() - 1.1939849245).abs() < ERROR);
+        assert!((op.execute(fov.iter().copied()).unwrap().to_owned() - 1.2727922061).abs() < ERROR);
+        assert!((op.execute(fpv.iter().copied()).unwrap().to_owned() - 1.1939849245).abs() < ERROR);
 
         // mess with ordering
         let riov: Vec<&i32> = iov.iter().rev().collect();
@@ -199,8 +199,8 @@ mod tests {
 
         assert!((op.execute(iov.iter().map(|i| *i as f64)).unwrap().to_owned() - 8.0).abs() < ERROR);
         assert!((op.execute(ipv.iter().map(|i| *i as f64)).unwrap().to_owned() - 7.04).abs() < ERROR);
-        assert!((op.execute(fov.iter().map(|i| *i)).unwrap().to_owned() - 1.62).abs() < ERROR);
-        assert!((op.execute(fpv.iter().map(|i| *i)).unwrap().to_owned() - 1.4256).abs() < ERROR);
+        assert!((op.execute(fov.iter().copied()).unwrap().to_owned() - 1.62).abs() < ERROR);
+        assert!((op.execute(fpv.iter().copied()).unwrap().to_owned() - 1.4256).abs() < ERROR);
 
         // mess with ordering
         let riov: Vec<&i32> = iov.iter().rev().collect();
@@ -225,8 +225,8 @@ mod tests {
         
         assert_eq!(op.execute(iov.iter().map(|i| *i as f64)).unwrap() as i32, 1);
         assert_eq!(op.execute(ipv.iter().map(|i| *i as f64)).unwrap() as i32, 1);
-        assert_eq!(op.execute(fov.iter().map(|i| *i as f64)).unwrap(), 1.5);
-        assert_eq!(op.execute(fpv.iter().map(|i| *i as f64)).unwrap(), 1.5);
+        assert_eq!(op.execute(fov.iter().copied()).unwrap(), 1.5);
+        assert_eq!(op.execute(fpv.iter().copied()).unwrap(), 1.5);
 
         // mess with ordering
         let riov: Vec<&i32> = iov.iter().rev().collect();
@@ -236,8 +236,8 @@ mod tests {
 
         assert_eq!(op.execute(riov.iter().map(|i| **i as f64)).unwrap() as i32, 1);
         assert_eq!(op.execute(ripv.iter().map(|i| **i as f64)).unwrap() as i32, 1);
-        assert_eq!(op.execute(rfov.iter().map(|i| **i as f64)).unwrap(), 1.5);
-        assert_eq!(op.execute(rfpv.iter().map(|i| **i as f64)).unwrap(), 1.5);
+        assert_eq!(op.execute(rfov.iter().map(|i| **i)).unwrap(), 1.5);
+        assert_eq!(op.execute(rfpv.iter().map(|i| **i)).unwrap(), 1.5);
     }
 
     #[test]
@@ -251,8 +251,8 @@ mod tests {
         
         assert_eq!(op.execute(iov.iter().map(|i| *i as f64)).unwrap() as i32, 9);
         assert_eq!(op.execute(ipv.iter().map(|i| *i as f64)).unwrap() as i32, 9);
-        assert_eq!(op.execute(fov.iter().map(|i| *i as f64)).unwrap(), 5.1);
-        assert_eq!(op.execute(fpv.iter().map(|i| *i as f64)).unwrap(), 5.1);
+        assert_eq!(op.execute(fov.iter().copied()).unwrap(), 5.1);
+        assert_eq!(op.execute(fpv.iter().copied()).unwrap(), 5.1);
 
         // mess with ordering
         let riov: Vec<&i32> = iov.iter().rev().collect();
@@ -262,8 +262,8 @@ mod tests {
 
         assert_eq!(op.execute(riov.iter().map(|i| **i as f64)).unwrap() as i32, 9);
         assert_eq!(op.execute(ripv.iter().map(|i| **i as f64)).unwrap() as i32, 9);
-        assert_eq!(op.execute(rfov.iter().map(|i| **i as f64)).unwrap(), 5.1);
-        assert_eq!(op.execute(rfpv.iter().map(|i| **i as f64)).unwrap(), 5.1);
+        assert_eq!(op.execute(rfov.iter().map(|i| **i)).unwrap(), 5.1);
+        assert_eq!(op.execute(rfpv.iter().map(|i| **i)).unwrap(), 5.1);
     }
 
     #[test]
@@ -0,0 +1,252 @@
+use std::ops::Not;
+use std::sync::Arc;
+
+use ahash::{AHashMap, AHashSet};
+use bimap::BiMap;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use nohash_hasher::{IntMap, IntSet};
+use serde_json::Value;
+
+use crate::objects::ocel::{Ocel, OcelEvent, OcelObject};
+use crate::objects::ocel::attributes::AttributeValue;
+
+/// Predicate on the attributes of an event.
+pub type VmapPredicate = Arc<dyn Fn(&AHashMap<String, Value>) -> bool + Send + Sync>;
+
+/// Predicate on one attribute of an event in the type of the schema of the log.
+pub type AttributePredicate = Arc<dyn Fn(&AttributeValue) -> bool + Send + Sync>;
+
+/// Condition on the events of a log, combined with `and`, `or` and `!`.
+#[derive(Clone)]
+pub enum OcelFilter {
+    /// Events of one of the activities.
+    Activities(AHashSet<String>),
+    /// Events linked to an object of one of the types.
+    ObjectTypes(AHashSet<String>),
+    /// Events in `[from, to)`.
+    TimeWindow(DateTime<Utc>, DateTime<Utc>),
+    Vmap(VmapPredicate),
+    /// Events with the attribute, see `Ocel::event_attribute`, for which the predicate holds.
+    Attribute(String, AttributePredicate),
+    And(Vec<OcelFilter>),
+    Or(Vec<OcelFilter>),
+    Not(Box<OcelFilter>)
+}
+
+impl OcelFilter {
+    pub fn activities<'a>(activities: impl IntoIterator<Item = &'a str>) -> Self {
+        OcelFilter::Activities(activities.into_iter().map(|act| act.to_owned()).collect())
+    }
+
+    pub fn object_types<'a>(object_types: impl IntoIterator<Item = &'a str>) -> Self {
+        OcelFilter::ObjectTypes(object_types.into_iter().map(|ot| ot.to_owned()).collect())
+    }
+
+    pub fn time_window(from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        OcelFilter::TimeWindow(from, to)
+    }
+
+    pub fn vmap(predicate: impl Fn(&AHashMap<String, Value>) -> bool + Send + Sync + 'static) -> Self {
+        OcelFilter::Vmap(Arc::new(predicate))
+    }
+
+    pub fn attribute(key: &str, predicate: impl Fn(&AttributeValue) -> bool + Send + Sync + 'static) -> Self {
+        OcelFilter::Attribute(key.to_owned(), Arc::new(predicate))
+    }
+
+    pub fn and(self, other: OcelFilter) -> Self {
+        match self {
+            OcelFilter::And(mut filters) => {
+                filters.push(other);
+                OcelFilter::And(filters)
+            },
+            _ => OcelFilter::And(vec![self, other])
+        }
+    }
+
+    pub fn or(self, other: OcelFilter) -> Self {
+        match self {
+            OcelFilter::Or(mut filters) => {
+                filters.push(other);
+                OcelFilter::Or(filters)
+            },
+            _ => OcelFilter::Or(vec![self, other])
+        }
+    }
+
+    pub fn matches(&self, log: &Ocel, event: &OcelEvent) -> bool {
+        match self {
+            OcelFilter::Activities(activities) => activities.contains(log.activity(event)),
+            OcelFilter::ObjectTypes(object_types) => event.omap.iter().any(|oid| log.objects.get(oid).is_some_and(|obj| object_types.contains(log.object_type(obj)))),
+            OcelFilter::TimeWindow(from, to) => from <= &event.timestamp && &event.timestamp < to,
+            OcelFilter::Vmap(predicate) => predicate(&event.vmap),
+            OcelFilter::Attribute(key, predicate) => log.typed_event_value(event, key).is_some_and(|value| predicate(&value)),
+            OcelFilter::And(filters) => filters.iter().all(|filter| filter.matches(log, event)),
+            OcelFilter::Or(filters) => filters.iter().any(|filter| filter.matches(log, event)),
+            OcelFilter::Not(filter) => !filter.matches(log, event)
+        }
+    }
+}
+
+impl Not for OcelFilter {
+    type Output = OcelFilter;
+
+    fn not(self) -> Self::Output {
+        match self {
+            OcelFilter::Not(filter) => *filter,
+            _ => OcelFilter::Not(Box::new(self))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FilterOptions {
+    /// Only keep objects of these types, events without any of them are removed as well.
+    pub object_types: Option<AHashSet<String>>,
+    /// Renumber events in time order and objects in order of their ids, starting at 0.
+    pub compact_ids: bool
+}
+
+/// Sub-log of the events that match the filter. Objects are only kept while they have events.
+pub fn filter_ocel(log: &Ocel, filter: &OcelFilter, options: &FilterOptions) -> Ocel {
+    let mut events: IntSet<usize> = log.events.iter().filter(|(_, ev)| filter.matches(log, ev)).map(|(eid, _)| *eid).collect();
+    let mut objects: IntSet<usize> = events.iter().flat_map(|eid| log.events[eid].omap.iter().copied()).filter(|oid| log.objects.contains_key(oid)).collect();
+
+    if let Some(object_types) = &options.object_types {
+        objects.retain(|oid| object_types.contains(log.object_type(&log.objects[oid])));
+        events.retain(|eid| log.events[eid].omap.iter().any(|oid| objects.contains(oid)));
+    }
+
+    let mut filtered = sublog(log, &events, &objects);
+    if options.compact_ids {
+        compact_ids(&mut filtered);
+    }
+    filtered
+}
+
+/// Log of the given events and objects with the same ids. Links to other events and objects are
+/// removed, `activities` is recomputed and the rest of the log is copied.
+pub fn sublog(log: &Ocel, events: &IntSet<usize>, objects: &IntSet<usize>) -> Ocel {
+    let events: IntMap<usize, OcelEvent> = events.iter().filter_map(|eid| {
+        let mut ev = log.events.get(eid)?.clone();
+        ev.omap.retain(|oid| objects.contains(oid));
+        ev.qualifiers.retain(|oid, _| objects.contains(oid));
+        Some((*eid, ev))
+    }).collect();
+    let objects: IntMap<usize, OcelObject> = objects.iter().filter_map(|oid| {
+        let mut obj = log.objects.get(oid)?.clone();
+        obj.events.retain(|eid| events.get(eid).is_some_and(|ev| ev.omap.contains(oid)));
+        Some((*oid, obj))
+    }).collect();
+
+    let o2o = log.o2o.iter()
+                     .filter(|(oid, _)| objects.contains_key(oid))
+                     .map(|(oid, links)| (*oid, links.iter().filter(|link| objects.contains_key(&link.target)).cloned().collect::<Vec<_>>()))
+                     .filter(|(_, links)| !links.is_empty())
+                     .collect();
+
+    Ocel {
+        global_log: log.global_log.clone(),
+        global_event: log.global_event.clone(),
+        global_object: log.global_object.clone(),
+        event_map: log.event_map.iter().filter(|(_, eid)| events.contains_key(eid)).map(|(name, eid)| (name.to_owned(), *eid)).collect(),
+        object_map: log.object_map.iter().filter(|(_, oid)| objects.contains_key(oid)).map(|(name, oid)| (name.to_owned(), *oid)).collect(),
+        activity_table: log.activity_table.clone(),
+        object_type_table: log.object_type_table.clone(),
+        schema: log.schema.clone(),
+        o2o,
+        events,
+        objects,
+        ..Default::default()
+    }
+}
+
+/// Renumber events in time order and objects in order of their previous ids.
+pub fn compact_ids(log: &mut Ocel) {
+    log.reindex_chronologically();
+
+    let renumbering: IntMap<usize, usize> = log.objects.keys().sorted().enumerate().map(|(new_id, oid)| (*oid, new_id)).collect();
+    log.objects = log.objects.drain().map(|(oid, obj)| (renumbering[&oid], obj)).collect();
+    log.object_map = log.object_map.iter().map(|(name, oid)| (name.to_owned(), renumbering[oid])).collect::<BiMap<String, usize>>();
+    for ev in log.events.values_mut() {
+        ev.omap = ev.omap.iter().filter_map(|oid| renumbering.get(oid).copied()).collect();
+        ev.qualifiers = ev.qualifiers.drain().filter_map(|(oid, qualifiers)| Some((*renumbering.get(&oid)?, qualifiers))).collect();
+    }
+    log.o2o = log.o2o.drain().filter_map(|(oid, mut links)| {
+        links.retain(|link| renumbering.contains_key(&link.target));
+        links.iter_mut().for_each(|link| link.target = renumbering[&link.target]);
+        Some((*renumbering.get(&oid)?, links))
+    }).collect();
+    log.invalidate_index();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::objects::ocel::builder::LogBuilder;
+    use crate::objects::ocel::importer::import_ocel;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    #[test]
+    fn test_filter_activities() {
+        let filter = OcelFilter::activities(["place order", "receive payment"]);
+        let filtered = filter_ocel(&OCEL, &filter, &FilterOptions::default());
+        assert_eq!(filtered.events.len(), 6);
+        assert_eq!(filtered.activities(), vec!["place order", "receive payment"]);
+        for obj in filtered.objects.values() {
+            assert!(!obj.events.is_empty());
+            assert!(obj.events.iter().all(|eid| filtered.events.contains_key(eid)));
+        }
+        filtered.build().expect("the filtered log is consistent");
+    }
+
+    #[test]
+    fn test_filter_combinators() {
+        let first = OCEL.events.values().map(|ev| ev.timestamp).min().unwrap();
+        let window = OcelFilter::time_window(first, first + Duration::days(3));
+        let not_picked = !OcelFilter::activities(["pick item"]);
+        let filter = window.clone().and(not_picked.clone()).or(OcelFilter::vmap(|vmap| vmap.contains_key("prepaid-amount")));
+
+        let filtered = filter_ocel(&OCEL, &filter, &FilterOptions::default());
+        for (eid, ev) in &OCEL.events {
+            let kept = (window.matches(&OCEL, ev) && not_picked.matches(&OCEL, ev)) || ev.vmap.contains_key("prepaid-amount");
+            assert_eq!(filtered.events.contains_key(eid), kept);
+        }
+        assert!(matches!(!not_picked, OcelFilter::Activities(_)));
+        filtered.build().expect("the filtered log is consistent");
+    }
+
+    #[test]
+    fn test_filter_object_types() {
+        let options = FilterOptions { object_types: Some(AHashSet::from(["order".to_string()])), compact_ids: false };
+        let filtered = filter_ocel(&OCEL, &OcelFilter::And(vec![]), &options);
+        assert!(filtered.objects.values().all(|obj| filtered.object_type(obj) == "order"));
+        assert!(filtered.events.values().all(|ev| !ev.omap.is_empty()));
+        let order_events: IntSet<usize> = OCEL.objects.values().filter(|obj| OCEL.object_type(obj) == "order").flat_map(|obj| obj.events.iter().copied()).collect();
+        assert_eq!(filtered.events.len(), order_events.len());
+        assert!(filtered.o2o.values().flatten().all(|link| filtered.objects.contains_key(&link.target)));
+        filtered.build().expect("the filtered log is consistent");
+    }
+
+    #[test]
+    fn test_compact_ids() {
+        let filter = OcelFilter::object_types(["package"]);
+        let options = FilterOptions { compact_ids: true, ..Default::default() };
+        let filtered = filter_ocel(&OCEL, &filter, &options);
+        assert_eq!(filtered.events.keys().copied().sorted().collect::<Vec<usize>>(), (0..filtered.events.len()).collect::<Vec<usize>>());
+        assert_eq!(filtered.objects.keys().copied().sorted().collect::<Vec<usize>>(), (0..filtered.objects.len()).collect::<Vec<usize>>());
+        for (name, eid) in filtered.event_map.iter() {
+            let original = &OCEL.events[OCEL.event_map.get_by_left(name).unwrap()];
+            let objects: AHashSet<&String> = filtered.events[eid].omap.iter().map(|oid| filtered.object_map.get_by_right(oid).unwrap()).collect();
+            assert_eq!(objects, original.omap.iter().map(|oid| OCEL.object_map.get_by_right(oid).unwrap()).collect());
+        }
+        filtered.build().expect("the filtered log is consistent");
+    }
+}
@@ -0,0 +1,138 @@
+use itertools::Itertools;
+use serde_json::Value;
+
+use crate::objects::ocel::Ocel;
+use crate::objects::xes::{XesLog, XesTrace, XesEvent, CONCEPT_NAME, IDENTITY_ID, TIME_TIMESTAMP};
+
+/// How to treat an event that is linked to several objects of the flattened type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlatteningPolicy {
+    /// Copy the event into the trace of every linked object.
+    #[default]
+    Duplicate,
+    /// Only keep the event in the trace of the linked object with the smallest id, which is the first of its traces.
+    KeepFirst
+}
+
+/// Flatten the log to one trace per object of `object_type`. Traces carry the object id as
+/// `concept:name` and the `ovmap`, events carry activity, timestamp, event id and the `vmap`.
+pub fn flatten_ocel(log: &Ocel, object_type: &str, policy: FlatteningPolicy) -> XesLog {
+    let mut xes = XesLog::default();
+    xes.attributes.insert(CONCEPT_NAME.to_string(), Value::String(object_type.to_owned()));
+
+    let Some(type_id) = log.object_type_id(object_type) else {
+        return xes;
+    };
+
+    for oid in log.objects.keys().sorted() {
+        let obj = &log.objects[oid];
+        if obj.obj_type != type_id {
+            continue;
+        }
+
+        let mut trace = XesTrace::default();
+        trace.attributes.insert(CONCEPT_NAME.to_string(), Value::String(log.object_map.get_by_right(oid).expect("object map and objects are filled together").to_owned()));
+        for (key, value) in obj.ovmap.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            trace.attributes.entry(key.to_owned()).or_insert(value.to_owned());
+        }
+
+        // links to objects that are not part of the log are ignored
+        let trace_events = obj.events.iter()
+                                     .filter_map(|eid| log.events.get(eid).map(|ev| (eid, ev)))
+                                     .filter(|(_, ev)| {
+                                         policy == FlatteningPolicy::Duplicate ||
+                                         ev.omap.iter().filter(|o| log.objects.get(o).is_some_and(|other| other.obj_type == type_id)).min() == Some(oid)
+                                     })
+                                     .sorted_by_key(|(eid, ev)| (ev.timestamp, **eid));
+
+        for (eid, ev) in trace_events {
+            let mut event = XesEvent::default();
+            event.attributes.insert(CONCEPT_NAME.to_string(), Value::String(log.activity(ev).to_owned()));
+            event.attributes.insert(TIME_TIMESTAMP.to_string(), Value::String(ev.timestamp.to_rfc3339()));
+            event.attributes.insert(IDENTITY_ID.to_string(), Value::String(log.event_map.get_by_right(eid).expect("event map and events are filled together").to_owned()));
+            for (key, value) in ev.vmap.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+                event.attributes.entry(key.to_owned()).or_insert(value.to_owned());
+            }
+            trace.events.push(event);
+        }
+
+        xes.traces.push(trace);
+    }
+
+    xes
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ahash::AHashSet;
+
+    use super::*;
+    use crate::objects::ocel::importer::import_ocel;
+    use crate::objects::xes::exporter::export_xes_to_writer;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    #[test]
+    fn test_flatten_duplicate() {
+        let xes = flatten_ocel(&OCEL, "item", FlatteningPolicy::Duplicate);
+        let items: Vec<&usize> = OCEL.objects.iter().filter(|(_, obj)| OCEL.object_type(obj) == "item").map(|(oid, _)| oid).collect();
+        assert_eq!(xes.traces.len(), items.len());
+        assert_eq!(xes.traces.iter().map(|t| t.events.len()).sum::<usize>(), items.iter().map(|oid| OCEL.objects[oid].events.len()).sum::<usize>());
+
+        for trace in &xes.traces {
+            let timestamps: Vec<&str> = trace.events.iter().map(|ev| ev.attributes[TIME_TIMESTAMP].as_str().unwrap()).collect();
+            let mut sorted = timestamps.clone();
+            sorted.sort();
+            assert_eq!(timestamps, sorted);
+        }
+    }
+
+    #[test]
+    fn test_flatten_keep_first() {
+        let xes = flatten_ocel(&OCEL, "item", FlatteningPolicy::KeepFirst);
+        let mut seen: AHashSet<&str> = AHashSet::new();
+        for ev in xes.traces.iter().flat_map(|t| t.events.iter()) {
+            assert!(seen.insert(ev.attributes[IDENTITY_ID].as_str().unwrap()));
+        }
+
+        let item_events: AHashSet<&usize> = OCEL.objects.values().filter(|obj| OCEL.object_type(obj) == "item").flat_map(|obj| obj.events.iter()).collect();
+        assert_eq!(seen.len(), item_events.len());
+    }
+
+    #[test]
+    fn test_flatten_keep_first_dangling_link() {
+        let mut log = OCEL.clone();
+        let eid = *log.objects.values().find(|obj| log.object_type(obj) == "item").unwrap().events.first().unwrap();
+        log.events_mut().get_mut(&eid).unwrap().omap.insert(usize::MAX);
+        let xes = flatten_ocel(&log, "item", FlatteningPolicy::KeepFirst);
+        let expected = flatten_ocel(&OCEL, "item", FlatteningPolicy::KeepFirst);
+        assert_eq!(xes.traces.iter().map(|t| t.events.len()).collect::<Vec<usize>>(), expected.traces.iter().map(|t| t.events.len()).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_flatten_attributes() {
+        let xes = flatten_ocel(&OCEL, "order", FlatteningPolicy::Duplicate);
+        for trace in &xes.traces {
+            let oid = OCEL.object_map.get_by_left(trace.attributes[CONCEPT_NAME].as_str().unwrap()).unwrap();
+            for (key, value) in &OCEL.objects[oid].ovmap {
+                assert_eq!(&trace.attributes[key], value);
+            }
+            for event in &trace.events {
+                let ev = &OCEL.events[OCEL.event_map.get_by_left(event.attributes[IDENTITY_ID].as_str().unwrap()).unwrap()];
+                assert_eq!(event.attributes[CONCEPT_NAME], Value::String(OCEL.activity(ev).to_owned()));
+                for (key, value) in &ev.vmap {
+                    assert_eq!(&event.attributes[key], value);
+                }
+            }
+        }
+
+        let mut buf: Vec<u8> = vec![];
+        export_xes_to_writer(&xes, &mut buf).expect("could not write the xes log");
+        let s = String::from_utf8(buf).unwrap();
+        assert_eq!(s.matches("<trace>").count(), xes.traces.len());
+        assert!(s.contains(r#"<date key="time:timestamp""#));
+    }
+}
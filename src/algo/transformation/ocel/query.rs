@@ -0,0 +1,544 @@
+//! Queries over the events of a log, for example
+//! `events where activity in ("pay", "ship") and omap has type "item" and vmap.price > 100 between 2023-01-01 and 2023-02-01`.
+//!
+//! ```text
+//! query     := "events" ["where" expr] ["between" date "and" date]
+//! expr      := and_expr {"or" and_expr}
+//! and_expr  := unary {"and" unary}
+//! unary     := "not" unary | "(" expr ")" | condition
+//! condition := "activity" ("in" "(" string {"," string} ")" | "=" string | "!=" string)
+//!            | "omap" "has" "type" string
+//!            | "vmap" "." key comparison (number | string | "true" | "false")
+//!            | "timestamp" comparison date
+//!            | "between" date "and" date
+//! ```
+//! Keywords are case-insensitive, dates are RFC 3339 timestamps or days like `2023-01-01` in UTC.
+//! `between` includes its start and excludes its end. Attributes are compared in the type the schema of the log
+//! gives them, numbers also match strings that hold a number. Comparisons with a missing attribute are false.
+use std::ops::Range;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use polars::prelude::DataFrame;
+
+use crate::objects::ocel::Ocel;
+use crate::objects::ocel::attributes::AttributeValue;
+use crate::algo::transformation::ocel::filtering::{filter_ocel, FilterOptions, OcelFilter};
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge
+}
+
+impl Comparison {
+    fn holds<T: PartialOrd>(&self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Ne => left != right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Number(f64),
+    Date(DateTime<Utc>),
+    Compare(Comparison),
+    LParen,
+    RParen,
+    Comma,
+    Dot,
+    End
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(w) => format!("'{}'", w),
+            Token::Str(s) => format!("\"{}\"", s),
+            Token::Number(n) => n.to_string(),
+            Token::Date(d) => d.to_rfc3339(),
+            Token::Compare(_) => "a comparison".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+            Token::Dot => "'.'".to_string(),
+            Token::End => "the end of the query".to_string()
+        }
+    }
+}
+
+fn query_error(message: String, span: Range<usize>) -> Error {
+    Error::Query { message, span }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == ':'
+}
+
+fn tokenize(query: &str) -> Result<Vec<(Token, Range<usize>)>, Error> {
+    let mut tokens = vec![];
+    let mut chars = query.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let single = match c {
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            ',' => Some(Token::Comma),
+            '.' => Some(Token::Dot),
+            _ => None
+        };
+        if let Some(token) = single {
+            chars.next();
+            tokens.push((token, start..start + 1));
+            continue;
+        }
+
+        if c == '"' {
+            chars.next();
+            let mut s = String::new();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        end = Some(i + 1);
+                        break;
+                    },
+                    '\\' => {
+                        if let Some((_, escaped)) = chars.next() {
+                            s.push(escaped);
+                        }
+                    },
+                    _ => s.push(c)
+                }
+            }
+            let end = end.ok_or_else(|| query_error("The string is not closed.".to_string(), start..query.len()))?;
+            tokens.push((Token::Str(s), start..end));
+            continue;
+        }
+
+        if "=!<>".contains(c) {
+            chars.next();
+            let followed_by_eq = chars.peek().is_some_and(|(_, next)| *next == '=');
+            let (comparison, len) = match (c, followed_by_eq) {
+                ('=', true) => (Comparison::Eq, 2),
+                ('=', false) => (Comparison::Eq, 1),
+                ('!', true) => (Comparison::Ne, 2),
+                ('<', true) => (Comparison::Le, 2),
+                ('<', false) => (Comparison::Lt, 1),
+                ('>', true) => (Comparison::Ge, 2),
+                ('>', false) => (Comparison::Gt, 1),
+                _ => return Err(query_error("'!' has to be followed by '='.".to_string(), start..start + 1))
+            };
+            if len == 2 {
+                chars.next();
+            }
+            tokens.push((Token::Compare(comparison), start..start + len));
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '-' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(is_word_char(c) || c == '.' || c == '+') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let text = &query[start..end];
+            let token = if text.len() >= 10 && text.as_bytes()[4] == b'-' {
+                Token::Date(parse_date(text).ok_or_else(|| query_error(format!("{} is not a date.", text), start..end))?)
+            } else {
+                Token::Number(text.parse::<f64>().map_err(|_| query_error(format!("{} is not a number.", text), start..end))?)
+            };
+            tokens.push((token, start..end));
+            continue;
+        }
+
+        if is_word_char(c) {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !is_word_char(c) {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((Token::Word(query[start..end].to_owned()), start..end));
+            continue;
+        }
+
+        return Err(query_error(format!("Unexpected character '{}'.", c), start..start + c.len_utf8()));
+    }
+
+    tokens.push((Token::End, query.len()..query.len()));
+    Ok(tokens)
+}
+
+fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(naive.and_utc());
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|naive| naive.and_utc())
+}
+
+struct Parser {
+    tokens: Vec<(Token, Range<usize>)>,
+    pos: usize
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn span(&self) -> Range<usize> {
+        self.tokens[self.pos].1.clone()
+    }
+
+    fn next(&mut self) -> (Token, Range<usize>) {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        query_error(format!("Expected {}, found {}.", expected, self.peek().describe()), self.span())
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if !self.at_keyword(keyword) {
+            return Err(self.unexpected(&format!("'{}'", keyword)));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), Error> {
+        if self.peek() != &token {
+            return Err(self.unexpected(&token.describe()));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        match self.peek().clone() {
+            Token::Str(s) => {
+                self.next();
+                Ok(s)
+            },
+            _ => Err(self.unexpected("a string"))
+        }
+    }
+
+    fn date(&mut self) -> Result<DateTime<Utc>, Error> {
+        match self.peek().clone() {
+            Token::Date(d) => {
+                self.next();
+                Ok(d)
+            },
+            Token::Str(s) => {
+                let span = self.span();
+                self.next();
+                parse_date(&s).ok_or_else(|| query_error(format!("{} is not a date.", s), span))
+            },
+            _ => Err(self.unexpected("a date"))
+        }
+    }
+
+    fn comparison(&mut self) -> Result<(Comparison, Range<usize>), Error> {
+        match self.peek().clone() {
+            Token::Compare(comparison) => Ok((comparison, self.next().1)),
+            _ => Err(self.unexpected("a comparison"))
+        }
+    }
+
+    fn query(&mut self) -> Result<OcelFilter, Error> {
+        self.keyword("events")?;
+        let mut filter = OcelFilter::And(vec![]);
+        if self.at_keyword("where") {
+            self.next();
+            filter = self.expr()?;
+        }
+        if self.at_keyword("between") {
+            filter = filter.and(self.between()?);
+        }
+        if self.peek() != &Token::End {
+            return Err(self.unexpected("'and', 'or', 'between' or the end of the query"));
+        }
+        Ok(filter)
+    }
+
+    fn expr(&mut self) -> Result<OcelFilter, Error> {
+        let mut filter = self.and_expr()?;
+        while self.at_keyword("or") {
+            self.next();
+            filter = filter.or(self.and_expr()?);
+        }
+        Ok(filter)
+    }
+
+    fn and_expr(&mut self) -> Result<OcelFilter, Error> {
+        let mut filter = self.unary()?;
+        while self.at_keyword("and") {
+            self.next();
+            filter = filter.and(self.unary()?);
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<OcelFilter, Error> {
+        if self.at_keyword("not") {
+            self.next();
+            return Ok(!self.unary()?);
+        }
+        if self.peek() == &Token::LParen {
+            self.next();
+            let filter = self.expr()?;
+            self.expect(Token::RParen)?;
+            return Ok(filter);
+        }
+        self.condition()
+    }
+
+    fn between(&mut self) -> Result<OcelFilter, Error> {
+        self.keyword("between")?;
+        let from = self.date()?;
+        self.keyword("and")?;
+        let to = self.date()?;
+        Ok(OcelFilter::time_window(from, to))
+    }
+
+    fn condition(&mut self) -> Result<OcelFilter, Error> {
+        if self.at_keyword("between") {
+            return self.between();
+        }
+        if self.at_keyword("activity") {
+            self.next();
+            if self.at_keyword("in") {
+                self.next();
+                self.expect(Token::LParen)?;
+                let mut activities = vec![self.string()?];
+                while self.peek() == &Token::Comma {
+                    self.next();
+                    activities.push(self.string()?);
+                }
+                self.expect(Token::RParen)?;
+                return Ok(OcelFilter::activities(activities.iter().map(|act| act.as_str())));
+            }
+            return match self.comparison()? {
+                (Comparison::Eq, _) => Ok(OcelFilter::activities([self.string()?.as_str()])),
+                (Comparison::Ne, _) => Ok(!OcelFilter::activities([self.string()?.as_str()])),
+                (_, span) => Err(query_error("Activities can only be compared with '=' and '!='.".to_string(), span))
+            };
+        }
+        if self.at_keyword("omap") {
+            self.next();
+            self.keyword("has")?;
+            self.keyword("type")?;
+            return Ok(OcelFilter::object_types([self.string()?.as_str()]));
+        }
+        if self.at_keyword("vmap") {
+            self.next();
+            self.expect(Token::Dot)?;
+            let key = match self.next() {
+                (Token::Word(key), _) | (Token::Str(key), _) => key,
+                (token, span) => return Err(query_error(format!("Expected an attribute name, found {}.", token.describe()), span))
+            };
+            let (comparison, comparison_span) = self.comparison()?;
+            return vmap_filter(key, comparison, comparison_span, self.next());
+        }
+        if self.at_keyword("timestamp") {
+            self.next();
+            let (comparison, _) = self.comparison()?;
+            let time = self.date()?;
+            let next = time + Duration::nanoseconds(1);
+            let (min, max) = (DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC);
+            return Ok(match comparison {
+                Comparison::Eq => OcelFilter::time_window(time, next),
+                Comparison::Ne => !OcelFilter::time_window(time, next),
+                Comparison::Lt => OcelFilter::time_window(min, time),
+                Comparison::Le => OcelFilter::time_window(min, next),
+                Comparison::Gt => OcelFilter::time_window(next, max),
+                Comparison::Ge => OcelFilter::time_window(time, max)
+            });
+        }
+        Err(self.unexpected("'activity', 'omap', 'vmap', 'timestamp', 'between', 'not' or '('"))
+    }
+}
+
+fn vmap_filter(key: String, comparison: Comparison, comparison_span: Range<usize>, literal: (Token, Range<usize>)) -> Result<OcelFilter, Error> {
+    Ok(match literal {
+        (Token::Number(n), _) => OcelFilter::attribute(&key, move |value| match value {
+            AttributeValue::String(s) => s.trim().parse::<f64>().is_ok_and(|v| comparison.holds(v, n)),
+            value => value.as_f64().is_some_and(|v| comparison.holds(v, n))
+        }),
+        (Token::Str(s), _) => {
+            let date = parse_date(&s);
+            OcelFilter::attribute(&key, move |value| match value {
+                AttributeValue::String(v) => comparison.holds(v.as_str(), s.as_str()),
+                AttributeValue::Timestamp(v) => date.is_some_and(|d| comparison.holds(*v, d)),
+                _ => false
+            })
+        },
+        (Token::Word(w), span) if w.eq_ignore_ascii_case("true") || w.eq_ignore_ascii_case("false") => {
+            if !matches!(comparison, Comparison::Eq | Comparison::Ne) {
+                return Err(query_error("Booleans can only be compared with '=' and '!='.".to_string(), comparison_span.start..span.end));
+            }
+            let b = w.eq_ignore_ascii_case("true");
+            OcelFilter::attribute(&key, move |value| matches!(value, AttributeValue::Bool(v) if comparison.holds(*v, b)))
+        },
+        (Token::Date(d), _) => OcelFilter::attribute(&key, move |value| match value {
+            AttributeValue::Timestamp(v) => comparison.holds(*v, d),
+            AttributeValue::String(v) => parse_date(v).is_some_and(|v| comparison.holds(v, d)),
+            _ => false
+        }),
+        (token, span) => return Err(query_error(format!("Expected a number, string, date or boolean, found {}.", token.describe()), span))
+    })
+}
+
+/// Parse a query into a filter, see the module documentation for the syntax.
+pub fn parse_query(query: &str) -> Result<OcelFilter, Error> {
+    let mut parser = Parser { tokens: tokenize(query)?, pos: 0 };
+    parser.query()
+}
+
+/// Sub-log of the events that match the query, see `filter_ocel`.
+pub fn query_ocel(log: &Ocel, query: &str) -> Result<Ocel, Error> {
+    Ok(filter_ocel(log, &parse_query(query)?, &FilterOptions::default()))
+}
+
+/// Events that match the query as a table in the shape of `Ocel::to_dataframes`.
+pub fn query_dataframe(log: &Ocel, query: &str) -> Result<DataFrame, Error> {
+    Ok(query_ocel(log, query)?.to_dataframes()?.events)
+}
+
+/// The query with the part an error points at underlined, other errors are only formatted.
+pub fn describe_query_error(query: &str, error: &Error) -> String {
+    match error {
+        Error::Query { message, span } => {
+            let width = query[span.clone()].chars().count().max(1);
+            format!("{}\n{}{}\n{}", query, " ".repeat(query[..span.start].chars().count()), "^".repeat(width), message)
+        },
+        _ => error.to_string()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use serde_json::json;
+
+    use super::*;
+    use crate::objects::ocel::attributes::AttributeType;
+    use crate::objects::ocel::builder::{BuilderObject, LogBuilder};
+    use crate::objects::ocel::importer::import_ocel;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    fn error_span(query: &str) -> Range<usize> {
+        match parse_query(query) {
+            Err(Error::Query { span, .. }) => span,
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("{} should not parse", query)
+        }
+    }
+
+    #[test]
+    fn test_query_activities_and_types() {
+        let sublog = query_ocel(&OCEL, r#"events where activity in ("place order", "pick item") and omap has type "package""#).unwrap();
+        assert!(sublog.events.is_empty());
+
+        let sublog = query_ocel(&OCEL, r#"events where activity in ("place order", "pick item") and omap has type "order""#).unwrap();
+        assert_eq!(sublog.events.len(), 9);
+        assert_eq!(sublog.activities(), vec!["place order", "pick item"]);
+
+        let sublog = query_ocel(&OCEL, r#"EVENTS WHERE NOT (activity = "place order" OR activity != "pick item")"#).unwrap();
+        assert!(sublog.events.values().all(|ev| sublog.activity(ev) == "pick item"));
+        assert_eq!(sublog.events.len(), 6);
+    }
+
+    #[test]
+    fn test_query_vmap_and_time() {
+        let query = r#"events where vmap.prepaid-amount > 100 between 2000-01-01 and 2100-01-01T00:00:00Z"#;
+        let sublog = query_ocel(&OCEL, query).unwrap();
+        let expected = OCEL.events.values().filter(|ev| ev.vmap.get("prepaid-amount").and_then(|v| v.as_f64()).is_some_and(|v| v > 100.0)).count();
+        assert!(expected > 0);
+        assert_eq!(sublog.events.len(), expected);
+
+        let first = OCEL.events.values().map(|ev| ev.timestamp).min().unwrap();
+        let sublog = query_ocel(&OCEL, &format!("events where timestamp <= \"{}\"", first.to_rfc3339())).unwrap();
+        assert_eq!(sublog.events.len(), OCEL.events.values().filter(|ev| ev.timestamp == first).count());
+        assert_eq!(query_ocel(&OCEL, "events").unwrap().events.len(), OCEL.events.len());
+    }
+
+    #[test]
+    fn test_query_typed_attributes() {
+        let mut log = Ocel::default();
+        let time = parse_date("2023-01-01").unwrap();
+        for (name, amount) in [("e1", json!("7")), ("e2", json!(3)), ("e3", json!("9.5")), ("e4", json!("n/a"))] {
+            log.add_event(name, time, "pay", vec![BuilderObject("order", "o1")], Some([("amount".to_string(), amount)].into_iter().collect())).unwrap();
+        }
+        let matched = |log: &Ocel, query: &str| query_ocel(log, query).unwrap().event_map.left_values().sorted().cloned().collect_vec();
+        assert_eq!(matched(&log, "events where vmap.amount > 5"), vec!["e1", "e3"]);
+        assert_eq!(matched(&log, r#"events where vmap.amount = "n/a""#), vec!["e4"]);
+
+        // a numeric type in the schema reads the strings as numbers
+        let pay = log.activity_id("pay").unwrap();
+        log.schema.activities.get_mut(&pay).unwrap().insert("amount".to_string(), AttributeType::Float);
+        assert_eq!(log.event_attribute(log.event_map.get_by_left("e1").unwrap(), "amount"), Some(AttributeValue::Float(7.0)));
+        assert_eq!(matched(&log, "events where vmap.amount > 5"), vec!["e1", "e3"]);
+        assert_eq!(matched(&log, "events where vmap.amount <= 5"), vec!["e2"]);
+    }
+
+    #[test]
+    fn test_query_dataframe() {
+        let df = query_dataframe(&OCEL, r#"events where activity = "receive payment""#).unwrap();
+        assert_eq!(df.height(), 3);
+    }
+
+    #[test]
+    fn test_query_errors() {
+        assert_eq!(error_span(r#"objects where activity = "a""#), 0..7);
+        assert_eq!(error_span(r#"events where activity < "a""#), 22..23);
+        assert_eq!(error_span(r#"events where activity in ("a" "b")"#), 30..33);
+        assert_eq!(error_span(r#"events where vmap.price > true"#), 24..30);
+        assert_eq!(error_span(r#"events where activity = "a"#), 24..26);
+        assert_eq!(error_span(r#"events where timestamp > 2023-13-01"#), 25..35);
+        assert_eq!(error_span(r#"events where activity = "a" ship"#), 28..32);
+        assert_eq!(error_span(r#"events where"#), 12..12);
+
+        let query = r#"events where omap has kind "item""#;
+        let error = parse_query(query).err().unwrap();
+        assert_eq!(describe_query_error(query, &error), format!("{}\n{}^^^^\nExpected 'type', found 'kind'.", query, " ".repeat(22)));
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use nohash_hasher::IntSet;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use crate::Error;
+use crate::objects::ocel::Ocel;
+use crate::algo::transformation::ocel::filtering::sublog;
+use crate::algo::transformation::ocel::executions::{extract_process_executions, ExecutionStrategy};
+
+/// Where objects with events before and after the cut of a temporal split end up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpanPolicy {
+    /// The object is part of both logs with the events on either side of the cut.
+    #[default]
+    Split,
+    /// The object and all its events are part of the training log only.
+    Train,
+    /// The object and all its events are left out of both logs.
+    Drop
+}
+
+/// Sample the executions of `n` objects of the leading type, the objects that share an event with them
+/// and all events of these objects. Fewer objects are sampled if the log has fewer, the same seed gives
+/// the same sample.
+pub fn sample_objects(log: &Ocel, leading_type: &str, n: usize, seed: u64) -> Ocel {
+    let strategy = ExecutionStrategy::LeadingType { object_type: leading_type.to_owned(), hops: 1 };
+    let executions = extract_process_executions(log, &strategy);
+
+    let (mut events, mut objects): (IntSet<usize>, IntSet<usize>) = Default::default();
+    for execution in executions.choose_multiple(&mut ChaCha8Rng::seed_from_u64(seed), n) {
+        events.extend(&execution.events);
+        objects.extend(&execution.objects);
+    }
+    sublog(log, &events, &objects)
+}
+
+/// Split the log into the events before `cut` for training and the events from `cut` on for testing.
+pub fn temporal_split(log: &Ocel, cut: &DateTime<Utc>, policy: SpanPolicy) -> (Ocel, Ocel) {
+    // links to events that are not part of the log are skipped
+    let before = |eid: &usize| log.events.get(eid).map(|ev| ev.timestamp < *cut);
+    let spanning_events: IntSet<usize> = log.objects.values()
+                                            .filter(|obj| obj.events.iter().any(|eid| before(eid) == Some(true)) && obj.events.iter().any(|eid| before(eid) == Some(false)))
+                                            .flat_map(|obj| obj.events.iter().filter(|eid| log.events.contains_key(eid)).copied())
+                                            .collect();
+
+    let (mut train, mut test): (IntSet<usize>, IntSet<usize>) = log.events.keys().copied().partition(|eid| before(eid) == Some(true));
+    match policy {
+        SpanPolicy::Split => {},
+        SpanPolicy::Train => {
+            test.retain(|eid| !spanning_events.contains(eid));
+            train.extend(&spanning_events);
+        },
+        SpanPolicy::Drop => {
+            train.retain(|eid| !spanning_events.contains(eid));
+            test.retain(|eid| !spanning_events.contains(eid));
+        }
+    }
+
+    // events of spanning objects are either all in one log or in none, so only split objects end up in both
+    let objects_of = |events: &IntSet<usize>| -> IntSet<usize> {
+        events.iter().filter_map(|eid| log.events.get(eid)).flat_map(|ev| ev.omap.iter().copied()).collect()
+    };
+    let (train_objects, test_objects) = (objects_of(&train), objects_of(&test));
+    (sublog(log, &train, &train_objects), sublog(log, &test, &test_objects))
+}
+
+/// Split the process executions, the connected components of objects that share events, into `k` folds.
+/// Every fold is the test log of one pair of training and test log. The same seed gives the same folds.
+/// Objects without events are not part of any fold.
+pub fn k_fold_split(log: &Ocel, k: usize, seed: u64) -> Result<Vec<(Ocel, Ocel)>, Error> {
+    if k == 0 {
+        return Err(Error::Schema("A k-fold split needs at least one fold.".to_string()));
+    }
+    let mut executions = extract_process_executions(log, &ExecutionStrategy::ConnectedComponents);
+    executions.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+
+    let mut folds: Vec<(IntSet<usize>, IntSet<usize>)> = vec![Default::default(); k];
+    for (n, execution) in executions.into_iter().enumerate() {
+        let fold = &mut folds[n % k];
+        fold.0.extend(execution.events);
+        fold.1.extend(execution.objects);
+    }
+
+    Ok((0..k).map(|test_fold| {
+        let mut train: (IntSet<usize>, IntSet<usize>) = Default::default();
+        for (_, (events, objects)) in folds.iter().enumerate().filter(|(n, _)| *n != test_fold) {
+            train.0.extend(events);
+            train.1.extend(objects);
+        }
+        let test = &folds[test_fold];
+        (sublog(log, &train.0, &train.1), sublog(log, &test.0, &test.1))
+    }).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::objects::ocel::builder::LogBuilder;
+    use crate::objects::ocel::importer::import_ocel;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    #[test]
+    fn test_sample_objects() {
+        let sample = sample_objects(&OCEL, "order", 2, 42);
+        assert_eq!(sample.objects_of_type("order").len(), 2);
+        for obj in sample.objects.values() {
+            assert!(obj.events.iter().all(|eid| sample.events.contains_key(eid)));
+        }
+        let again = sample_objects(&OCEL, "order", 2, 42);
+        assert_eq!(sample.events.keys().sorted().collect_vec(), again.events.keys().sorted().collect_vec());
+        assert_eq!(sample.objects.keys().sorted().collect_vec(), again.objects.keys().sorted().collect_vec());
+        assert_eq!(sample_objects(&OCEL, "order", 10, 1).objects_of_type("order").len(), 3);
+        for (oid, obj) in &sample.objects {
+            assert_eq!(obj.events.len(), OCEL.objects[oid].events.len());
+        }
+        sample.build().expect("the sample is consistent");
+    }
+
+    #[test]
+    fn test_temporal_split() {
+        let times: Vec<DateTime<Utc>> = OCEL.events.values().map(|ev| ev.timestamp).sorted().collect();
+        let cut = times[times.len() / 2];
+
+        let (train, test) = temporal_split(&OCEL, &cut, SpanPolicy::Split);
+        assert_eq!(train.events.len() + test.events.len(), OCEL.events.len());
+        assert!(train.events.values().all(|ev| ev.timestamp < cut));
+        assert!(test.events.values().all(|ev| ev.timestamp >= cut));
+
+        let (train, test) = temporal_split(&OCEL, &cut, SpanPolicy::Train);
+        assert_eq!(train.events.len() + test.events.len(), OCEL.events.len());
+        assert!(test.objects.keys().all(|oid| !train.objects.contains_key(oid) || OCEL.objects[oid].events.iter().all(|eid| OCEL.events[eid].timestamp >= cut)));
+        assert!(train.objects.iter().all(|(oid, obj)| obj.events.len() == OCEL.objects[oid].events.len() || test.objects.contains_key(oid)));
+
+        let (train, test) = temporal_split(&OCEL, &cut, SpanPolicy::Drop);
+        assert!(train.objects.keys().all(|oid| !test.objects.contains_key(oid)));
+        assert!(train.events.len() + test.events.len() < OCEL.events.len());
+        for log in [train, test] {
+            log.build().expect("the split is consistent");
+        }
+    }
+
+    #[test]
+    fn test_temporal_split_dangling_events() {
+        let times: Vec<DateTime<Utc>> = OCEL.events.values().map(|ev| ev.timestamp).sorted().collect();
+        let cut = times[times.len() / 2];
+        let mut log = OCEL.clone();
+        let missing = log.events.keys().max().unwrap() + 1;
+        log.objects_mut().values_mut().for_each(|obj| obj.events.push(missing));
+
+        for policy in [SpanPolicy::Split, SpanPolicy::Train, SpanPolicy::Drop] {
+            let (train, test) = temporal_split(&log, &cut, policy);
+            let (expected_train, expected_test) = temporal_split(&OCEL, &cut, policy);
+            assert_eq!(train.events.keys().sorted().collect_vec(), expected_train.events.keys().sorted().collect_vec());
+            assert_eq!(test.events.keys().sorted().collect_vec(), expected_test.events.keys().sorted().collect_vec());
+        }
+    }
+
+    #[test]
+    fn test_k_fold_split() {
+        let folds = k_fold_split(&OCEL, 2, 7).unwrap();
+        assert_eq!(folds.len(), 2);
+        let mut tested: IntSet<usize> = IntSet::default();
+        for (train, test) in &folds {
+            assert_eq!(train.events.len() + test.events.len(), OCEL.events.len());
+            assert!(test.events.keys().all(|eid| !train.events.contains_key(eid) && tested.insert(*eid)));
+            assert!(test.objects.keys().all(|oid| !train.objects.contains_key(oid)));
+        }
+        assert_eq!(tested.len(), OCEL.events.len());
+
+        let again = k_fold_split(&OCEL, 2, 7).unwrap();
+        assert_eq!(folds[0].1.events.keys().sorted().collect_vec(), again[0].1.events.keys().sorted().collect_vec());
+        for (train, test) in folds {
+            train.build().expect("the training log is consistent");
+            test.build().expect("the test log is consistent");
+        }
+        assert!(matches!(k_fold_split(&OCEL, 0, 7), Err(Error::Schema(_))));
+    }
+}
@@ -1,10 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
+use nohash_hasher::IntSet;
 use serde_json::{Value, json};
 use strum::{EnumString, IntoStaticStr, Display};
 
-use crate::objects::{ocel::Ocel, ocdg::Relations};
+use crate::objects::{ocel::{Ocel, interner::ObjectTypeId}, ocdg::Relations};
 
 
 
@@ -23,21 +24,19 @@ pub enum EventSituations {
 
 impl EventSituations {
     pub fn execute(&self, log: &Ocel, params: &EventSituationParameters, eid: &usize) -> Option<Value> {
-        let event = &log.events[&eid];
+        let event = &log.events[eid];
         match self {
             EventSituations::EventChoice => {
                 if let Some(req_activities) = &params.activities {
-                    if req_activities.contains(event.activity.as_str()) {
-                        return Some(json!(event.activity));
+                    if req_activities.contains(log.activity(event)) {
+                        return Some(json!(log.activity(event)));
                     }
                 }
             },
             EventSituations::EventAttribute => {
                 if let Some(req_property) = &params.property {
-                    if let Some(val) = event.vmap.get(&req_property.to_string()) {
-                        if !val.is_null() {
-                            return Some(val.to_owned());
-                        }
+                    if let Some(val) = log.event_attribute(eid, req_property) {
+                        return Some(val.to_json());
                     }
                 }
             },
@@ -50,11 +49,10 @@ impl EventSituations {
             },
             EventSituations::EventWait => {
                 let mut oldest_time: &DateTime<Utc> = &DateTime::<Utc>::MAX_UTC;
-                for oid in event.omap.iter() {
-                    let obj = &log.objects[&oid];
-                    if *obj.events.first().expect("cannot fail") != *eid {
-                        let prev_event: &usize = &obj.events[obj.events.iter().position(|&eid2| eid2 == *eid).unwrap() - 1];
-                        let prev_timestamp: &DateTime<Utc> = &log.events[prev_event].timestamp;
+                for (oid, obj) in event.omap.iter().filter_map(|oid| Some((oid, log.objects.get(oid)?))) {
+                    let pos = log.lifecycle_position(*eid, *oid);
+                    if let Some(prev_event) = pos.and_then(|pos| pos.checked_sub(1)).and_then(|prev| log.events.get(&obj.events[prev])) {
+                        let prev_timestamp: &DateTime<Utc> = &prev_event.timestamp;
                         if prev_timestamp < oldest_time {
                             oldest_time = prev_timestamp;
                         }
@@ -62,16 +60,15 @@ impl EventSituations {
                 }
 
                 if oldest_time != &DateTime::<Utc>::MAX_UTC {
-                    return Some(json!(log.events[&eid].timestamp.timestamp_millis() - oldest_time.timestamp_millis()));
+                    return Some(json!(log.events[eid].timestamp.timestamp_millis() - oldest_time.timestamp_millis()));
                 }
             },
             EventSituations::EventDuration => {
                 let mut youngest_time: &DateTime<Utc> = &DateTime::<Utc>::MIN_UTC;
-                for oid in event.omap.iter() {
-                    let obj = &log.objects[&oid];
-                    if *obj.events.last().expect("cannot fail") != *eid {
-                        let prev_event: &usize = &obj.events[obj.events.iter().position(|&eid2| eid2 == *eid).unwrap() + 1];
-                        let next_timestamp: &DateTime<Utc> = &log.events[prev_event].timestamp;
+                for (oid, obj) in event.omap.iter().filter_map(|oid| Some((oid, log.objects.get(oid)?))) {
+                    let pos = log.lifecycle_position(*eid, *oid);
+                    if let Some(next_event) = pos.and_then(|pos| obj.events.get(pos + 1)).and_then(|next| log.events.get(next)) {
+                        let next_timestamp: &DateTime<Utc> = &next_event.timestamp;
                         if next_timestamp > youngest_time {
                             youngest_time = next_timestamp;
                         }
@@ -79,17 +76,18 @@ impl EventSituations {
                 }
 
                 if youngest_time != &DateTime::<Utc>::MIN_UTC {
-                    return Some(json!(youngest_time.timestamp_millis() - log.events[&eid].timestamp.timestamp_millis()));
+                    return Some(json!(youngest_time.timestamp_millis() - log.events[eid].timestamp.timestamp_millis()));
                 }
             },
             EventSituations::EventObjectChoice => {
                 if let Some(acts) = &params.activities { 
-                    if acts.contains(event.activity.as_str()) {
+                    if acts.contains(log.activity(event)) {
                         if let Some(otypes) = &params.object_types {
+                            let otypes: IntSet<ObjectTypeId> = otypes.iter().filter_map(|ot| log.object_type_id(ot)).collect();
                             let mut oid_fit: Vec<&usize> = vec![];
                             for oid in event.omap.iter() {
-                                if otypes.contains(&log.objects[&oid].obj_type.as_str()) {
-                                    oid_fit.push(&oid);
+                                if otypes.contains(&log.objects[oid].obj_type) {
+                                    oid_fit.push(oid);
                                 }
                             }
                             if !oid_fit.is_empty() {
@@ -101,10 +99,10 @@ impl EventSituations {
             },
             EventSituations::EventMissingObjectType => {
                 if let Some(otypes) = &params.object_types {
-                    let omap_otypes: HashSet<&str> = HashSet::from_iter(event.omap.iter().map(|o| log.objects[o].obj_type.as_str()));
+                    let omap_otypes: IntSet<ObjectTypeId> = IntSet::from_iter(event.omap.iter().map(|o| log.objects[o].obj_type));
                     let mut ot_fit = vec![];
                     for ot in otypes {
-                        if !omap_otypes.contains(ot) {
+                        if !log.object_type_id(ot).is_some_and(|ot| omap_otypes.contains(&ot)) {
                             ot_fit.push(ot);
                         }
                     }
@@ -137,7 +135,7 @@ pub struct EventSituationConfig<'a> {
 
 pub fn collect_event_targets(log: &Ocel, situation: EventSituations, params: EventSituationParameters) -> Vec<(usize, Value)> {
     log.events.keys()
-              .map(|eid| (*eid, situation.execute(&log, &params, eid)))
+              .map(|eid| (*eid, situation.execute(log, &params, eid)))
               .filter(|(_, val)| val.is_some())
               .map(|(eid, val)| (eid, val.unwrap()))
               .collect()
@@ -157,10 +155,8 @@ mod tests {
         let log = get_test_data();
         let eid = 0;
         let situation = EventSituations::EventChoice;
-        let mut params_good = EventSituationParameters::default();
-        params_good.activities = Some(HashSet::from(["A"]));
-        let mut params_bad = EventSituationParameters::default();
-        params_bad.activities = Some(HashSet::from(["B"]));
+        let params_good = EventSituationParameters { activities: Some(HashSet::from(["A"])), ..Default::default() };
+        let params_bad = EventSituationParameters { activities: Some(HashSet::from(["B"])), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params_good, &eid).unwrap(), json!("A"));
         assert_eq!(situation.execute(&log, &params_bad, &eid), None);
@@ -171,10 +167,8 @@ mod tests {
         let log = get_test_data();
         let eid = 0;
         let situation = EventSituations::EventAttribute;
-        let mut params_good = EventSituationParameters::default();
-        params_good.property = Some("prepaid-amount");
-        let mut params_bad = EventSituationParameters::default();
-        params_bad.property = Some("not-known-key");
+        let params_good = EventSituationParameters { property: Some("prepaid-amount"), ..Default::default() };
+        let params_bad = EventSituationParameters { property: Some("not-known-key"), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params_good, &eid).unwrap(), json!(1000.0));
         assert_eq!(situation.execute(&log, &params_bad, &eid), None);
@@ -185,10 +179,8 @@ mod tests {
         let log = get_test_data();
         let eid = 1;
         let situation = EventSituations::EventAttributeUnknown;
-        let mut params_good = EventSituationParameters::default();
-        params_good.property = Some("errors");
-        let mut params_bad = EventSituationParameters::default();
-        params_bad.property = Some("not-known-key");
+        let params_good = EventSituationParameters { property: Some("errors"), ..Default::default() };
+        let params_bad = EventSituationParameters { property: Some("not-known-key"), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params_good, &eid).unwrap(), json!(null));
         assert_eq!(situation.execute(&log, &params_bad, &eid), None);
@@ -224,9 +216,7 @@ mod tests {
         let log = get_test_data();
         let eid = 0;
         let situation = EventSituations::EventObjectChoice;
-        let mut params = EventSituationParameters::default();
-        params.activities = Some(HashSet::from(["A"]));
-        params.object_types = Some(HashSet::from(["order"]));
+        let mut params = EventSituationParameters { activities: Some(HashSet::from(["A"])), object_types: Some(HashSet::from(["order"])), ..Default::default() };
 
         let o1oid: &usize = log.object_map.get_by_left(&"o1".to_string()).unwrap();
 
@@ -241,8 +231,7 @@ mod tests {
         let log = get_test_data();
         let eid = 0;
         let situation = EventSituations::EventMissingObjectType;
-        let mut params = EventSituationParameters::default();
-        params.object_types = Some(HashSet::from(["worker"]));
+        let mut params = EventSituationParameters { object_types: Some(HashSet::from(["worker"])), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params, &eid).unwrap(), json!(vec!["worker"]));
 
@@ -253,8 +242,7 @@ mod tests {
     #[test]
     fn test_collect_event_targets() {
         let log = get_test_data();
-        let mut params = EventSituationParameters::default();
-        params.activities = Some(HashSet::from(["A"]));
+        let params = EventSituationParameters { activities: Some(HashSet::from(["A"])), ..Default::default() };
         let situation = EventSituations::EventChoice;
 
         let test_execution = collect_event_targets(&log, situation, params);
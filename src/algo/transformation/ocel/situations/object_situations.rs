@@ -1,8 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
+use nohash_hasher::IntSet;
 use serde_json::{Value, json};
 use strum::{EnumString, IntoStaticStr, Display};
-use crate::objects::ocel::Ocel;
+use crate::objects::ocel::{Ocel, interner::ActivityId};
 
 lazy_static::lazy_static!{
 static ref NULLVALUES: Vec<Value> = vec![json!("NaN"), json!("NA"), json!("na"), json!("n/a"), json!(null)];
@@ -19,20 +20,26 @@ pub enum ObjectSituations {
 
 
 impl ObjectSituations {
+    /// `None` if the situation does not hold for the object, objects that are not part of the log
+    /// and `ObjectMissingReachableObjectType`, which is not supported yet, never hold.
     pub fn execute(&self, log: &Ocel, params: &ObjectSituationParameters, oid: &usize) -> Option<Value> {
-        let obj = &log.objects[&oid];
+        let obj = log.objects.get(oid)?;
         match self {
             ObjectSituations::ObjectAttribute => {
                 if let Some(req_property) = &params.property {
-                    if obj.ovmap.contains_key(&req_property.to_string()) {
-                        return Some(obj.ovmap[&req_property.to_string()].to_owned());
+                    let (val, typed) = match params.as_of_event.and_then(|eid| log.events.get(&eid)) {
+                        Some(event) => (obj.value_at(req_property, &event.timestamp), log.object_attribute_at(oid, req_property, &event.timestamp)),
+                        None => (obj.ovmap.get(*req_property), log.object_attribute(oid, req_property))
+                    };
+                    if let Some(val) = val {
+                        return Some(typed.map(|val| val.to_json()).unwrap_or(val.to_owned()));
                     }
                 }
             },
             ObjectSituations::ObjectAttributeUnknown => {
                 if let Some(req_property) = &params.property {
                     if let Some(val) = obj.ovmap.get(&req_property.to_string()) {
-                        if val.is_null() || NULLVALUES.contains(&val) {
+                        if val.is_null() || NULLVALUES.contains(val) {
                             return Some(val.to_owned());
                         }
                     }
@@ -40,10 +47,10 @@ impl ObjectSituations {
             },
             ObjectSituations::ObjectMissingActivity => {
                 if let Some(activities) = &params.activities {
-                    let oe_activities: HashSet<&str> = HashSet::from_iter(obj.events.iter().map(|eid| log.events[eid].activity.as_str()));
+                    let oe_activities: IntSet<ActivityId> = IntSet::from_iter(obj.events.iter().filter_map(|eid| log.events.get(eid)).map(|ev| ev.activity));
                     let mut remaining: Vec<&str> = vec![];
                     for ac in activities {
-                        if !oe_activities.contains(ac) {
+                        if !log.activity_id(ac).is_some_and(|ac| oe_activities.contains(&ac)) {
                             remaining.push(ac);
                         }
                     }
@@ -54,15 +61,14 @@ impl ObjectSituations {
             },
             ObjectSituations::ObjectLifetime => {
                 if let Some(activities) = &params.activities {
-                    let final_eid = obj.events.last().expect("this cannot fail");
-                    if activities.contains(log.events[&final_eid].activity.as_str()) {
-                        let first_ev = log.events[obj.events.first().expect("this cannot fail")].timestamp;
-                        let last_ev = log.events[final_eid].timestamp;
-                        return Some(json!(last_ev.timestamp_millis() - first_ev.timestamp_millis()));
+                    let first_ev = log.events.get(obj.events.first()?)?;
+                    let last_ev = log.events.get(obj.events.last()?)?;
+                    if activities.contains(log.activity(last_ev)) {
+                        return Some(json!(last_ev.timestamp.timestamp_millis() - first_ev.timestamp.timestamp_millis()));
                     }
                 } 
             },
-            ObjectSituations::ObjectMissingReachableObjectType => {todo!()}
+            ObjectSituations::ObjectMissingReachableObjectType => {}
         }
         None
     }
@@ -72,7 +78,9 @@ impl ObjectSituations {
 pub struct ObjectSituationParameters<'a> {
     pub activities: Option<HashSet<&'a str>>,
     pub property: Option<&'a str>,
-    pub object_types: Option<HashSet<&'a str>>
+    pub object_types: Option<HashSet<&'a str>>,
+    /// Event whose time `ObjectAttribute` evaluates the attribute at, its initial value is used without one.
+    pub as_of_event: Option<usize>
 }
 
 
@@ -83,7 +91,7 @@ pub struct ObjectSituationConfig<'a> {
 
 pub fn collect_object_targets(log: &Ocel, situation: ObjectSituations, params: ObjectSituationParameters) -> Vec<(usize, Value)> {
     log.objects.keys()
-              .map(|oid| (*oid, situation.execute(&log, &params, oid)))
+              .map(|oid| (*oid, situation.execute(log, &params, oid)))
               .filter(|(_, val)| val.is_some())
               .map(|(oid, val)| (oid, val.unwrap()))
               .collect()
@@ -105,24 +113,35 @@ mod tests {
         let log = get_test_data();
         let oid = log.object_map.get_by_left(&"o1".to_string()).unwrap().to_owned();
         let situation = ObjectSituations::ObjectAttribute;
-        let mut params_good = ObjectSituationParameters::default();
-        params_good.property = Some("total");
-        let mut params_bad = ObjectSituationParameters::default();
-        params_bad.property = Some("notaproperty");
+        let params_good = ObjectSituationParameters { property: Some("total"), ..Default::default() };
+        let params_bad = ObjectSituationParameters { property: Some("notaproperty"), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params_good, &oid).unwrap(), json!(2999.99));
         assert_eq!(situation.execute(&log, &params_bad, &oid), None);
     }
 
+    #[test]
+    fn test_object_attribute_as_of_event() {
+        let mut log = get_test_data();
+        let oid = log.object_map.get_by_left("i1").unwrap().to_owned();
+        let (first, last) = (log.objects[&oid].events[0], *log.objects[&oid].events.last().unwrap());
+        let changed_at = log.events[&last].timestamp;
+        log.objects_mut().get_mut(&oid).unwrap().history.insert("price".to_string(), vec![(changed_at, json!(1500.0))]);
+
+        let situation = ObjectSituations::ObjectAttribute;
+        let before = ObjectSituationParameters { property: Some("price"), as_of_event: Some(first), ..Default::default() };
+        let after = ObjectSituationParameters { property: Some("price"), as_of_event: Some(last), ..Default::default() };
+        assert_eq!(situation.execute(&log, &before, &oid).unwrap(), json!(2000.0));
+        assert_eq!(situation.execute(&log, &after, &oid).unwrap(), json!(1500.0));
+    }
+
     #[test]
     fn test_object_attribute_unknown_validation() {
         let log = get_test_data();
         let oid = log.object_map.get_by_left(&"i1".to_string()).unwrap().to_owned();
         let situation = ObjectSituations::ObjectAttributeUnknown;
-        let mut params_good = ObjectSituationParameters::default();
-        params_good.property = Some("name");
-        let mut params_bad = ObjectSituationParameters::default();
-        params_bad.property = Some("price");
+        let params_good = ObjectSituationParameters { property: Some("name"), ..Default::default() };
+        let params_bad = ObjectSituationParameters { property: Some("price"), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params_good, &oid).unwrap(), json!(null));
         assert_eq!(situation.execute(&log, &params_bad, &oid), None);
@@ -134,10 +153,8 @@ mod tests {
         let log = get_test_data();
         let oid = log.object_map.get_by_left(&"o1".to_string()).unwrap().to_owned();
         let situation = ObjectSituations::ObjectMissingActivity;
-        let mut params_good = ObjectSituationParameters::default();
-        params_good.activities = Some(HashSet::from(["B"]));
-        let mut params_bad = ObjectSituationParameters::default();
-        params_bad.activities = Some(HashSet::from(["A"]));
+        let params_good = ObjectSituationParameters { activities: Some(HashSet::from(["B"])), ..Default::default() };
+        let params_bad = ObjectSituationParameters { activities: Some(HashSet::from(["A"])), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params_good, &oid).unwrap(), json!(vec!["B"]));
         assert_eq!(situation.execute(&log, &params_bad, &oid), None);
@@ -149,19 +166,29 @@ mod tests {
         let oid_good = log.object_map.get_by_left(&"i1".to_string()).unwrap().to_owned();
         let oid_bad = log.object_map.get_by_left(&"o1".to_string()).unwrap().to_owned();
         let situation = ObjectSituations::ObjectLifetime;
-        let mut params = ObjectSituationParameters::default();
-        params.activities = Some(HashSet::from(["B"]));
+        let params = ObjectSituationParameters { activities: Some(HashSet::from(["B"])), ..Default::default() };
 
         assert_eq!(situation.execute(&log, &params, &oid_good).unwrap(), json!(3600000));
         assert_eq!(situation.execute(&log, &params, &oid_bad), None);
     } 
     
+    #[test]
+    fn test_object_lifetime_without_events() {
+        let mut log = get_test_data();
+        let oid = *log.object_map.get_by_left("i1").unwrap();
+        log.objects_mut().get_mut(&oid).unwrap().events.clear();
+        let params = ObjectSituationParameters { activities: Some(HashSet::from(["B"])), ..Default::default() };
+
+        assert_eq!(ObjectSituations::ObjectLifetime.execute(&log, &params, &oid), None);
+        assert!(collect_object_targets(&log, ObjectSituations::ObjectLifetime, params).iter().all(|(target, _)| *target != oid));
+        assert!(collect_object_targets(&log, ObjectSituations::ObjectMissingReachableObjectType, ObjectSituationParameters::default()).is_empty());
+    }
+
     #[test]
     fn test_collect_object_targets() {
         let log = get_test_data();
         let oid = log.object_map.get_by_left(&"o1".to_string()).unwrap().to_owned();
-        let mut params = ObjectSituationParameters::default();
-        params.activities = Some(HashSet::from(["B"]));
+        let params = ObjectSituationParameters { activities: Some(HashSet::from(["B"])), ..Default::default() };
         let situation = ObjectSituations::ObjectMissingActivity;
 
         let test_execution = collect_object_targets(&log, situation, params);
@@ -1,9 +1,8 @@
-use std::iter::FromIterator;
-
-use nohash_hasher::{IntMap, IntSet};
+use nohash_hasher::IntSet;
 use strum::EnumString;
 
-use crate::objects::ocel::{Ocel, OcelEvent, OcelObject};
+use crate::objects::ocel::Ocel;
+use crate::algo::transformation::ocel::filtering::sublog;
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, EnumString)]
 pub enum ExtractionPlan {
@@ -50,11 +49,7 @@ pub fn generate_event_situation_sublog(log: &Ocel, extraction_plan: ExtractionPl
         }
     }
 
-    let events: IntMap<usize, OcelEvent> = IntMap::from_iter(event_set.into_iter().map(|ev| (ev, log.events[&ev].clone())));
-    let objects: IntMap<usize, OcelObject> = IntMap::from_iter(object_set.into_iter().map(|obj| (obj, log.objects[&obj].clone())));
-
-    Some(Ocel {activities: log.activities.clone(), event_map: log.event_map.clone(), object_map: log.object_map.clone(), global_log: log.global_log.clone(), global_event: log.global_event.clone(), global_object: log.global_object.clone(), events, objects })
-
+    Some(sublog(log, &event_set, &object_set))
 }
 
 #[cfg(test)]
@@ -69,7 +64,7 @@ mod tests {
     #[test]
     fn test_event_situation_sublog_prefix_root() {
         let test_eid = OCEL.event_map.get_by_left("e36").expect("test log was altered");
-        let sublog = generate_event_situation_sublog(&OCEL, ExtractionPlan::PrefixRoot, &test_eid);
+        let sublog = generate_event_situation_sublog(&OCEL, ExtractionPlan::PrefixRoot, test_eid);
         
         assert!(sublog.is_some());
         if let Some(sublog_unwrapped) = sublog {
@@ -82,7 +77,7 @@ mod tests {
     #[test]
     fn test_event_situation_sublog_root_suffix() {
         let test_eid = OCEL.event_map.get_by_left("e5").expect("test log was altered");
-        let sublog = generate_event_situation_sublog(&OCEL, ExtractionPlan::RootSuffix, &test_eid);
+        let sublog = generate_event_situation_sublog(&OCEL, ExtractionPlan::RootSuffix, test_eid);
         
         assert!(sublog.is_some());
         if let Some(sublog_unwrapped) = sublog {
@@ -95,7 +90,7 @@ mod tests {
     #[test]
     fn test_event_situation_sublog_all_events() {
         let test_eid = OCEL.event_map.get_by_left("e5").expect("test log was altered");
-        let sublog = generate_event_situation_sublog(&OCEL, ExtractionPlan::AllEvents, &test_eid);
+        let sublog = generate_event_situation_sublog(&OCEL, ExtractionPlan::AllEvents, test_eid);
         
         assert!(sublog.is_some());
         if let Some(sublog_unwrapped) = sublog {
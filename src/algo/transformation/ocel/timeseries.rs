@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use ahash::AHashSet;
+use itertools::Itertools;
 use nohash_hasher::IntMap;
 use serde_json::Value;
 use strum::{EnumString, IntoStaticStr, Display};
@@ -20,13 +21,13 @@ impl BinMethod {
     pub fn execute(&self, log: &Ocel, timediff: Option<i64>) -> Vec<i64> {
         match self {
             BinMethod::EqualTime => {
-                return auto_timediff_binning(log);
+                auto_timediff_binning(log)
             },
             BinMethod::EqualTimeDiff => {
                 if let Some(td) = timediff {
                     return manual_timediff_binning(log, td);
                 }
-                return vec![];
+                vec![]
             }
         }
     }
@@ -70,28 +71,42 @@ pub struct TimeSeriesConfig<'a> {
 }
 
 
+/// Timestamps of the events in order of their ids, which is the order `generate_time_series` reads them in.
+fn event_millis(log: &Ocel) -> Vec<i64> {
+    log.events.keys().sorted().map(|eid| log.events[eid].timestamp.timestamp_millis()).collect()
+}
+
 pub fn auto_timediff_binning(log: &Ocel) -> Vec<i64> {
-    let bin_series: Vec<i64> = log.events.iter().map(|(_, values)| values.timestamp.timestamp_millis()).collect();
-    let time_width: i64 = (bin_series.last().expect("") - bin_series.first().expect("")) / bin_series.len() as i64;
+    let bin_series: Vec<i64> = event_millis(log);
+    let (Some(first), Some(last)) = (bin_series.first(), bin_series.last()) else {
+        return vec![];
+    };
+    let time_width: i64 = (last - first) / bin_series.len() as i64;
 
     time_bins(bin_series, time_width)
 }
 
 pub fn manual_timediff_binning(log: &Ocel, timediff: i64) -> Vec<i64> {
-    let bin_series: Vec<i64> = log.events.iter().map(|(_, values)| values.timestamp.timestamp_millis()).collect();
+    let bin_series: Vec<i64> = event_millis(log);
     
     time_bins(bin_series, timediff)
 }
 
 
+/// Bin of every time of the series, widths below a millisecond are treated as one millisecond.
 pub fn time_bins(series: Vec<i64>, timediff: i64) -> Vec<i64> {
-    let first_time: i64 = series.first().expect("").to_owned();
+    let Some(first_time) = series.first().copied() else {
+        return vec![];
+    };
+    let timediff = timediff.max(1);
     series.iter().map(|millis| (millis - first_time) / timediff).collect()
 }
 
 pub fn remove_bin_gaps(bin_list: Vec<i64>) -> Vec<i64> {
     let mut curr_num: i64 = 0;
-    let mut prev_bin = bin_list.first().unwrap().to_owned();
+    let Some(mut prev_bin) = bin_list.first().copied() else {
+        return vec![];
+    };
     bin_list.iter().map(|bin_id| { if bin_id != &prev_bin {curr_num += 1}; prev_bin = *bin_id; curr_num}).collect()
 }
 
@@ -100,18 +115,16 @@ pub fn generate_time_series<T>(log: &Ocel, binning: Vec<i64>, ts_type: TimeSerie
         where T: Num + ToPrimitive + PartialOrd + Clone + FromPrimitive {
         let binned_series: Vec<i64> = binning;
         
-        if binned_series.len() == 0 {
+        let Some(mut curr_id) = binned_series.first() else {
             return vec![];
-        }
-
-        let mut curr_id: &i64 = binned_series.first().unwrap();
+        };
         let mut final_series: Vec<f64> = vec![];
         let mut head: i64 = 0;
         let mut tail: i64 = 0;
 
         for bin in binned_series.iter() {
             if bin != curr_id {
-                match execute_time_series(&log, head, tail, &ts_type) {
+                match execute_time_series(log, head, tail, &ts_type) {
                     Some(res) => final_series.push(res),
                     None => final_series.push(0.0)
                 }
@@ -122,7 +135,7 @@ pub fn generate_time_series<T>(log: &Ocel, binning: Vec<i64>, ts_type: TimeSerie
         }
 
         // insert the remining elements in the series
-        match execute_time_series(&log, head, tail, &ts_type) {
+        match execute_time_series(log, head, tail, &ts_type) {
             Some(res) => final_series.push(res),
             None => final_series.push(0.0)
         }
@@ -131,21 +144,21 @@ pub fn generate_time_series<T>(log: &Ocel, binning: Vec<i64>, ts_type: TimeSerie
         final_series
 }
 
-fn execute_time_series<'a, T>(log: &'a Ocel, head: i64, tail: i64, ts_type: &TimeSeries) -> Option<T>
+fn execute_time_series<T>(log: &Ocel, head: i64, tail: i64, ts_type: &TimeSeries) -> Option<T>
         where T: Num + ToPrimitive + PartialOrd + Clone + FromPrimitive {
             let series = get_series(&log.events, head, tail - 1);
             ts_type.execute(log, series)
 }
 
 
-fn get_series<'a>(events: &'a IntMap<usize, OcelEvent>, head: i64, tail: i64) -> Vec<&'a OcelEvent> {
-    (head..=tail).into_iter().map(|x| events.get(&(x as usize)).unwrap()).collect()
+fn get_series(events: &IntMap<usize, OcelEvent>, head: i64, tail: i64) -> Vec<&OcelEvent> {
+    (head..=tail).filter_map(|x| events.get(&(x as usize))).collect()
 }
 
 
 pub fn series_differences_absolute<T>(series: Vec<T>) -> Vec<T>
         where T: Num + ToPrimitive + PartialOrd + Clone + Copy + FromPrimitive {
-            let mut diff_series = vec![T::from_f64(0.0).unwrap(); series.len() - 1];
+            let mut diff_series = vec![T::zero(); series.len().saturating_sub(1)];
             for (i, item) in diff_series.iter_mut().enumerate() {
                 *item = series[i+1] - series[i];
             }
@@ -154,9 +167,12 @@ pub fn series_differences_absolute<T>(series: Vec<T>) -> Vec<T>
 
 pub fn series_differences_relative<T>(series: Vec<T>) -> Vec<f64>
         where T: Num + ToPrimitive + PartialOrd + Clone + Copy + FromPrimitive {
-            let mut diff_series: Vec<f64> = vec![0.0; series.len() - 1];
+            let mut diff_series: Vec<f64> = vec![0.0; series.len().saturating_sub(1)];
             for (i, item) in diff_series.iter_mut().enumerate() {
-                *item = T::to_f64(&(series[i+1] - series[i])).unwrap() / T::to_f64(&series[i]).unwrap();
+                *item = match (T::to_f64(&(series[i+1] - series[i])), T::to_f64(&series[i])) {
+                    (Some(diff), Some(base)) => diff / base,
+                    _ => f64::NAN
+                };
             }
             diff_series
 }
@@ -225,6 +241,16 @@ mod tests {
         assert_eq!(vec![0, 0, 0, 0, 1], time_bins(millis, 10000));
     }
 
+    #[test]
+    fn test_empty_series() {
+        let log = Ocel::default();
+        assert!(auto_timediff_binning(&log).is_empty());
+        assert!(manual_timediff_binning(&log, 0).is_empty());
+        assert!(remove_bin_gaps(vec![]).is_empty());
+        assert!(series_differences_absolute(Vec::<i64>::new()).is_empty());
+        assert_eq!(vec![0, 0], time_bins(vec![5, 5], 0));
+    }
+
     #[test]
     fn test_remove_bin_gaps() {
         let millis: Vec<i64> = vec![0, 1000, 2000, 3000, 10000];
@@ -264,19 +290,19 @@ mod tests {
     #[test]
     fn test_time_series_activity_count() {
         let log = get_test_data();
-        assert_eq!(3, TimeSeries::ActivityCount.execute::<i64>(&log, log.events.values().into_iter().collect()).unwrap());
+        assert_eq!(3, TimeSeries::ActivityCount.execute::<i64>(&log, log.events.values().collect()).unwrap());
     }
 
     #[test]
     fn test_time_series_object_count() {
         let log = get_test_data();
-        assert_eq!(6, TimeSeries::ObjectCount.execute::<i64>(&log, log.events.values().into_iter().collect()).unwrap());
+        assert_eq!(6, TimeSeries::ObjectCount.execute::<i64>(&log, log.events.values().collect()).unwrap());
     }
 
     #[test]
     fn test_time_series_unique_object_count() {
         let log = get_test_data();
-        assert_eq!(4, TimeSeries::UniqueObjectCount.execute::<i64>(&log, log.events.values().into_iter().collect()).unwrap());
+        assert_eq!(4, TimeSeries::UniqueObjectCount.execute::<i64>(&log, log.events.values().collect()).unwrap());
     }
 
 
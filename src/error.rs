@@ -0,0 +1,119 @@
+use std::fmt;
+use std::ops::Range;
+
+use polars::prelude::PolarsError;
+
+/// Error returned by every fallible function of the crate.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file or stream failed.
+    Io(std::io::Error),
+    /// The input is not well-formed json, xml, csv, sqlite or ron, or a value in it does not parse.
+    Parse(String),
+    /// The input is well-formed, but misses something its format or the requested mapping needs.
+    Schema(String),
+    /// Ids are repeated or refer to events and objects that do not exist.
+    ReferentialIntegrity(String),
+    /// The relation cannot be used for the requested computation.
+    UnsupportedRelation(String),
+    /// The format is not known or does not support the requested source or target.
+    UnsupportedFormat(String),
+    /// A query does not parse, `span` holds the byte range of the query that is at fault.
+    Query { message: String, span: Range<usize> }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "IO error: {}", e),
+            Error::Parse(msg) => write!(f, "Parse error: {}", msg),
+            Error::Schema(msg) => write!(f, "Schema error: {}", msg),
+            Error::ReferentialIntegrity(msg) => write!(f, "Referential integrity error: {}", msg),
+            Error::UnsupportedRelation(msg) => write!(f, "Unsupported relation: {}", msg),
+            Error::UnsupportedFormat(msg) => write!(f, "Unsupported format: {}", msg),
+            Error::Query { message, span } => write!(f, "Query error at {}..{}: {}", span.start, span.end, message)
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        match e.classify() {
+            serde_json::error::Category::Io => Error::Io(e.into()),
+            _ => Error::Parse(e.to_string())
+        }
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(e: quick_xml::Error) -> Self {
+        match e {
+            quick_xml::Error::Io(io) => Error::Io(std::io::Error::new(io.kind(), io.to_string())),
+            _ => Error::Parse(e.to_string())
+        }
+    }
+}
+
+impl From<quick_xml::DeError> for Error {
+    fn from(e: quick_xml::DeError) -> Self {
+        match e {
+            quick_xml::DeError::InvalidXml(xml) => xml.into(),
+            _ => Error::Parse(e.to_string())
+        }
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for Error {
+    fn from(e: quick_xml::events::attributes::AttrError) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<PolarsError> for Error {
+    fn from(e: PolarsError) -> Self {
+        match e {
+            PolarsError::Io(io) => Error::Io(io),
+            PolarsError::ColumnNotFound(_) | PolarsError::SchemaFieldNotFound(_) | PolarsError::SchemaMismatch(_) | PolarsError::ShapeMismatch(_) => Error::Schema(e.to_string()),
+            _ => Error::Parse(e.to_string())
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for Error {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
@@ -1,2 +1,5 @@
 pub mod objects;
 pub mod algo;
+pub mod error;
+
+pub use error::Error;
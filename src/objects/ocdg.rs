@@ -1,10 +1,11 @@
 pub(crate) mod variants;
 pub mod importer;
 pub mod exporter;
+pub mod format;
 pub mod decomposition;
 pub(crate) mod generation;
 
-use std::{collections::hash_map::Entry, vec, fmt, error::Error};
+use std::{collections::hash_map::Entry, vec, fmt};
 use ahash::AHashSet;
 use bimap::BiMap;
 use petgraph::{graph::{NodeIndex, EdgeIndex}, stable_graph::StableDiGraph};
@@ -14,19 +15,9 @@ use num_enum::{TryFromPrimitive, IntoPrimitive};
 use strum::{EnumIter, EnumString};
 
 use super::ocel::Ocel;
+use crate::Error;
 
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct RelationError;
-
-impl fmt::Display for RelationError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Invalid Relation input in vector")
-    }
-}
-
-impl Error for RelationError {}
-
 pub trait OcdgRelations {
     fn is_timeconscious(&self) -> bool;
     fn is_directed(&self) -> bool;
@@ -48,29 +39,22 @@ pub enum Relations {
     MINION = 9,
     PEELER = 10,
     ENGAGES = 11,
-    ASCENDANTS = 12
+    ASCENDANTS = 12,
+    /// Explicit links between objects of the log, see `Ocel::o2o`.
+    LINKED = 13
 }
 
 impl OcdgRelations for Relations {
     fn is_timeconscious(&self) -> bool {
-        match self {
-            Relations::DESCENDANTS | Relations::INHERITANCE | Relations::SPLIT | Relations::CONSUMES => {true},
-            _ => {false}
-        }
+        matches!(self, Relations::DESCENDANTS | Relations::INHERITANCE | Relations::SPLIT | Relations::CONSUMES)
     }
 
     fn is_directed(&self) -> bool {
-        match self {
-            Relations::DESCENDANTS | Relations::INHERITANCE | Relations::SPLIT | Relations::CONSUMES | Relations::MINION | Relations::ASCENDANTS => {true},
-            _ => {false}
-        }
+        matches!(self, Relations::DESCENDANTS | Relations::INHERITANCE | Relations::SPLIT | Relations::CONSUMES | Relations::MINION | Relations::ASCENDANTS | Relations::LINKED)
     }
 
     fn is_multiproof(&self) -> bool {
-        match self {
-            Relations::COLIFE | Relations::PEELER | Relations::ENGAGES => {true},
-            _ => {false}
-        }
+        matches!(self, Relations::COLIFE | Relations::PEELER | Relations::ENGAGES)
     }
 }
 
@@ -84,6 +68,7 @@ impl Relations {
     fn relation_type(&self) -> u8 {
         match self {
             Relations::SPLIT => 1,
+            Relations::LINKED => 3,
             _ => {2}
             
         }
@@ -103,156 +88,144 @@ impl Relations {
             Relations::MINION => 9,
             Relations::PEELER => 10,
             Relations:: ENGAGES => 11,
-            Relations::ASCENDANTS => 12
+            Relations::ASCENDANTS => 12,
+            Relations::LINKED => 13
         }
     }
 
     
     fn execute_whole(&self, log: &Ocel, ocdg: &Ocdg, neighs: &IntMap<usize, IntSet<usize>>, oid1: usize) -> Vec<(usize, usize, EventAdd, Relations)> {
         let mut to_add: Vec<(usize, usize, EventAdd, Relations)> = Vec::new();
-        let src_oe = &log.objects.get(&oid1).unwrap().events;
-        let src_type = &ocdg.node_attributes.get(&oid1).unwrap().node_type;
-            match self {
-                Relations::SPLIT => {
-                    let mut conforming_oid: IntSet<usize> = IntSet::default();
-                    let src_e = src_oe.last().unwrap();
-                    for oid2 in neighs.get(&oid1).unwrap() {
-                        let neigh_oe = &log.objects.get(&oid2).unwrap().events;
-                        let neigh_type = &ocdg.node_attributes.get(&oid2).unwrap().node_type;
-                        if src_type == neigh_type && src_e == neigh_oe.first().unwrap() {
-                            conforming_oid.insert(*oid2);
-                        }
-                        
-                    }
-                    if conforming_oid.len() > 1 {
-                        for oid2 in &conforming_oid {
-                            to_add.push((oid1, *oid2, EventAdd::SINGLE(*src_e), Relations::SPLIT));
+        // objects without events or outside of the graph have no relations
+        let (Some(src_obj), Some(_), Some(src_neighs)) = (log.objects.get(&oid1), ocdg.node_attributes.get(&oid1), neighs.get(&oid1)) else {
+            return to_add;
+        };
+        let src_type = src_obj.obj_type;
+        if self == &Relations::SPLIT {
+            let mut conforming_oid: IntSet<usize> = IntSet::default();
+            let Some(src_e) = src_obj.events.last() else {
+                return to_add;
+            };
+            for oid2 in src_neighs {
+                let neigh_first = log.objects.get(oid2).and_then(|obj| obj.events.first());
+                let neigh_type = log.objects.get(oid2).filter(|_| ocdg.node_attributes.contains_key(oid2)).map(|obj| obj.obj_type);
+                if Some(src_type) == neigh_type && Some(src_e) == neigh_first {
+                    conforming_oid.insert(*oid2);
+                }
+                
+            }
+            if conforming_oid.len() > 1 {
+                for oid2 in &conforming_oid {
+                    to_add.push((oid1, *oid2, EventAdd::SINGLE(*src_e), Relations::SPLIT));
 
-                        }
-                    }
-                },
-                _ => {},
+                }
             }
-            to_add
         }
-
+        to_add
+    }
 
     fn execute(&self, log: &Ocel, ocdg: &Ocdg, oid1: usize, oid2: usize) -> Vec<(usize, usize, EventAdd, Relations)> {
         let mut to_add: Vec<(usize, usize, EventAdd, Relations)> = Vec::new();
-        let src_oe = &log.objects.get(&oid1).unwrap().events;
-        let tar_oe = &log.objects.get(&oid2).unwrap().events;
-        let src_type = &ocdg.node_attributes.get(&oid1).unwrap().node_type;
-        let tar_type = &ocdg.node_attributes.get(&oid2).unwrap().node_type;
+        // objects without events or outside of the graph have no relations
+        let (Some(src_obj), Some(tar_obj), Some(_), Some(_)) = (log.objects.get(&oid1), log.objects.get(&oid2), ocdg.node_attributes.get(&oid1), ocdg.node_attributes.get(&oid2)) else {
+            return to_add;
+        };
+        let (src_oe, tar_oe) = (&src_obj.events, &tar_obj.events);
+        let (Some(src_first), Some(src_last), Some(tar_first), Some(tar_last)) = (src_oe.first(), src_oe.last(), tar_oe.first(), tar_oe.last()) else {
+            return to_add;
+        };
+        let (src_type, tar_type) = (src_obj.obj_type, tar_obj.obj_type);
         
         match self {
-            Relations::INTERACTS => {
-                if oid1 < oid2 {
-                    let e_set = intersection_count_sorted_vec(src_oe, tar_oe);
-                    to_add.push((oid1, oid2, EventAdd::MULTI(e_set.to_owned()), Relations::INTERACTS));
-                    to_add.push((oid2, oid1, EventAdd::MULTI(e_set), Relations::INTERACTS));
-                }
+            Relations::INTERACTS if oid1 < oid2 => {
+                let e_set = intersection_count_sorted_vec(src_oe, tar_oe);
+                to_add.push((oid1, oid2, EventAdd::MULTI(e_set.to_owned()), Relations::INTERACTS));
+                to_add.push((oid2, oid1, EventAdd::MULTI(e_set), Relations::INTERACTS));
             },
-            Relations::DESCENDANTS => {
-                if (src_oe[0] < tar_oe[0]) && src_oe.contains(&tar_oe[0]) {
-                    to_add.push((oid1, oid2, EventAdd::SINGLE(tar_oe[0]), Relations::DESCENDANTS));
-                }
+            Relations::DESCENDANTS if (src_first < tar_first) && src_oe.contains(tar_first) => {
+                to_add.push((oid1, oid2, EventAdd::SINGLE(*tar_first), Relations::DESCENDANTS));
             },
-            Relations::ASCENDANTS => {
-                if (src_oe[0] < tar_oe[0]) && src_oe.contains(&tar_oe[0]) {
-                    to_add.push((oid2, oid1, EventAdd::SINGLE(tar_oe[0]), Relations::ASCENDANTS));
-                }
+            Relations::ASCENDANTS if (src_first < tar_first) && src_oe.contains(tar_first) => {
+                to_add.push((oid2, oid1, EventAdd::SINGLE(*tar_first), Relations::ASCENDANTS));
             },
-            Relations::COLIFE => { // one time
-                if oid1 < oid2 && src_oe == tar_oe {
-                    let e_set: IntSet<usize> = IntSet::from_iter(src_oe.to_owned());
-                    to_add.push((oid1, oid2, EventAdd::MULTI(e_set.to_owned()), Relations::COLIFE));
-                    to_add.push((oid2, oid1, EventAdd::MULTI(e_set), Relations::COLIFE));
-                }
+            Relations::COLIFE if oid1 < oid2 && src_oe == tar_oe => { // one time
+                let e_set: IntSet<usize> = IntSet::from_iter(src_oe.to_owned());
+                to_add.push((oid1, oid2, EventAdd::MULTI(e_set.to_owned()), Relations::COLIFE));
+                to_add.push((oid2, oid1, EventAdd::MULTI(e_set), Relations::COLIFE));
             },
-            Relations::COBIRTH => { // one time
-                if oid1 < oid2 {
-                    let src_e = src_oe.first().unwrap();
-                    if src_e == tar_oe.first().unwrap() {
-                        to_add.push((oid1, oid2, EventAdd::SINGLE(*src_e), Relations::COBIRTH));
-                        to_add.push((oid2, oid1, EventAdd::SINGLE(*src_e), Relations::COBIRTH));
-                    }
+            Relations::COBIRTH if oid1 < oid2 => { // one time
+                let src_e = src_first;
+                if src_e == tar_first {
+                    to_add.push((oid1, oid2, EventAdd::SINGLE(*src_e), Relations::COBIRTH));
+                    to_add.push((oid2, oid1, EventAdd::SINGLE(*src_e), Relations::COBIRTH));
                 }
             },
-            Relations::CODEATH => { // one time
-                if oid1 < oid2 {
-                    let src_e = src_oe.last().unwrap();
-                    if src_e == tar_oe.last().unwrap() {
-                        to_add.push((oid1, oid2, EventAdd::SINGLE(*src_e), Relations::CODEATH));
-                        to_add.push((oid2, oid1, EventAdd::SINGLE(*src_e), Relations::CODEATH));
-                    }
+            Relations::CODEATH if oid1 < oid2 => { // one time
+                let src_e = src_last;
+                if src_e == tar_last {
+                    to_add.push((oid1, oid2, EventAdd::SINGLE(*src_e), Relations::CODEATH));
+                    to_add.push((oid2, oid1, EventAdd::SINGLE(*src_e), Relations::CODEATH));
                 }
             },
             Relations::INHERITANCE => {
-                let src_e = src_oe.last().unwrap();
+                let src_e = src_last;
                 if src_type == tar_type &&
-                   src_e == tar_oe.first().unwrap() {
+                   src_e == tar_first {
                     to_add.push((oid1, oid2, EventAdd::SINGLE(*src_e), Relations::INHERITANCE));
                 }
             },
             Relations::CONSUMES => {
-                let src_e = src_oe.last().unwrap();
+                let src_e = src_last;
                 if src_type != tar_type &&
-                   src_e == tar_oe.first().unwrap() {
+                   src_e == tar_first {
                     to_add.push((oid1, oid2, EventAdd::SINGLE(*src_e), Relations::CONSUMES));
                 }
             },
             Relations::MERGE => {
-                let src_e = src_oe.last().unwrap();
+                let src_e = src_last;
                 if src_type == tar_type && 
-                   src_oe.last().unwrap() != tar_oe.last().unwrap() {
+                   src_last != tar_last {
                     to_add.push((oid1, oid2, EventAdd::SINGLE(*src_e), Relations::MERGE));
                 }
             },
-            Relations::MINION => {
-                   if src_oe.len() > tar_oe.len() {
-                       let src_set: AHashSet<&usize> = AHashSet::from_iter(src_oe);
-                       for ev in tar_oe {
-                            if !src_set.contains(ev) {
-                                return to_add;
-                            }
-                       }
-                       to_add.push((oid1, oid2, EventAdd::MULTI(IntSet::<usize>::from_iter(tar_oe.iter().cloned())), Relations::MINION));
-                   }
-            },
-            Relations::PEELER => {
-                if oid1 < oid2  {
-                    let shorter_oe = if src_oe.len() > tar_oe.len() {tar_oe} else {src_oe};
-                    let mut shared_events: IntSet<usize> = IntSet::default();
-                    let mut failed: bool = false;
-                    for event in shorter_oe.iter() {
-                        let omap = &log.events.get(&*event).unwrap().omap;    
-                        if omap.len() > 2 && omap.contains(&oid1) && omap.contains(&oid2){ 
-                            failed = true;
-                            break; 
-                        } else {
-                            shared_events.insert(*event);
-                        }
+            Relations::MINION if src_oe.len() > tar_oe.len() => {
+                let src_set: AHashSet<&usize> = AHashSet::from_iter(src_oe);
+                for ev in tar_oe {
+                    if !src_set.contains(ev) {
+                        return to_add;
                     }
-                    if !failed {
-                        to_add.push((oid1, oid2, EventAdd::MULTI(shared_events.to_owned()), Relations::PEELER));
-                        to_add.push((oid2, oid1, EventAdd::MULTI(shared_events), Relations::PEELER));
+                }
+                to_add.push((oid1, oid2, EventAdd::MULTI(IntSet::<usize>::from_iter(tar_oe.iter().cloned())), Relations::MINION));
+            },
+            Relations::PEELER if oid1 < oid2 => {
+                let shorter_oe = if src_oe.len() > tar_oe.len() {tar_oe} else {src_oe};
+                let mut shared_events: IntSet<usize> = IntSet::default();
+                let mut failed: bool = false;
+                for event in shorter_oe.iter() {
+                    let omap = &log.events[event].omap;    
+                    if omap.len() > 2 && omap.contains(&oid1) && omap.contains(&oid2){ 
+                        failed = true;
+                        break; 
+                    } else {
+                        shared_events.insert(*event);
                     }
                 }
+                if !failed {
+                    to_add.push((oid1, oid2, EventAdd::MULTI(shared_events.to_owned()), Relations::PEELER));
+                    to_add.push((oid2, oid1, EventAdd::MULTI(shared_events), Relations::PEELER));
+                }
             },
-            Relations::ENGAGES => {
-                if oid1 < oid2 {
-                    let src_oe_set: IntSet<_> = IntSet::<usize>::from_iter(src_oe.clone());
-                    let tar_oe_set: IntSet<_> = IntSet::<usize>::from_iter(tar_oe.clone());
-                    if !tar_oe_set.contains(src_oe.first().unwrap()) &&
-                       !tar_oe_set.contains(src_oe.last().unwrap()) &&
-                       !src_oe_set.contains(tar_oe.first().unwrap()) &&
-                       !src_oe_set.contains(tar_oe.last().unwrap()) {
-                            let shared_events: IntSet<usize> = src_oe_set.intersection(&tar_oe_set).map(|i| *i).collect();
-                            to_add.push((oid1, oid2, EventAdd::MULTI(shared_events.to_owned()), Relations::ENGAGES));
-                            to_add.push((oid2, oid1, EventAdd::MULTI(shared_events), Relations::ENGAGES));
-                       }
+            Relations::ENGAGES if oid1 < oid2 => {
+                let src_oe_set: IntSet<_> = IntSet::<usize>::from_iter(src_oe.clone());
+                let tar_oe_set: IntSet<_> = IntSet::<usize>::from_iter(tar_oe.clone());
+                if !tar_oe_set.contains(src_first) &&
+                   !tar_oe_set.contains(src_last) &&
+                   !src_oe_set.contains(tar_first) &&
+                   !src_oe_set.contains(tar_last) {
+                    let shared_events: IntSet<usize> = src_oe_set.intersection(&tar_oe_set).copied().collect();
+                    to_add.push((oid1, oid2, EventAdd::MULTI(shared_events.to_owned()), Relations::ENGAGES));
+                    to_add.push((oid2, oid1, EventAdd::MULTI(shared_events), Relations::ENGAGES));
                 }
-
             },
             _ => {}
         }
@@ -296,7 +269,7 @@ impl Ocdg {
 
     fn apply_new_edges(&mut self, edge: (usize, usize), eids: EventAdd, rel: Relations) {
             self.iedges.entry(edge.0).or_default().entry(edge.1).or_insert_with(|| self.net.add_edge(self.inodes[&edge.0], self.inodes[&edge.1], 0));
-            match self.irels.entry(edge.0).or_default().entry(edge.1).or_default().entry(rel.relation_index().into()) {
+            match self.irels.entry(edge.0).or_default().entry(edge.1).or_default().entry(rel.relation_index()) {
                 Entry::Vacant(e) => {
                     if let EventAdd::MULTI(multi) = eids {
                         e.insert(multi);
@@ -320,32 +293,37 @@ impl Ocdg {
 
 }
 
-pub fn generate_ocdg(log: &Ocel, relations: &Vec<Relations>) -> Ocdg {
+/// Graph of the objects that have events, with an edge per relation that holds between two objects.
+/// Objects or events without a name in the maps of the log are a `ReferentialIntegrity` error.
+pub fn generate_ocdg(log: &Ocel, relations: &[Relations]) -> Result<Ocdg, Error> {
     let mut ocdg: Ocdg = Ocdg::default();
     let rel_inst: Vec<_> = relations.iter().filter(|r| r.relation_type() == 2).collect();
     let rel_whole: Vec<_> = relations.iter().filter(|r| r.relation_type() == 1).collect();
-    let mut neighbours: IntMap<usize, IntSet<usize>> = IntMap::default();
+    let neighbours = object_neighbours(log);
 
-    for (eid, data) in &log.events {
-        for oid in &data.omap {
+    for data in log.events.values() {
+        for oid in data.omap.iter().filter(|oid| log.objects.contains_key(oid)) {
             if !ocdg.node_attributes.contains_key(oid) {
                 let new_node = ocdg.net.add_node(*oid);
-                ocdg.object_map.insert(log.object_map.get_by_right(&oid).expect("This cannot occur").to_owned(), *oid);
+                let name = log.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)))?;
+                ocdg.object_map.insert(name.to_owned(), *oid);
                 ocdg.init_object_key(*oid);
                 ocdg.inodes.entry(*oid).or_insert(new_node);
                 let curr_obj = &log.objects[oid];
-                ocdg.node_attributes.entry(*oid).or_default().node_type = curr_obj.obj_type.to_owned();
+                ocdg.node_attributes.entry(*oid).or_default().node_type = log.object_type(curr_obj).to_owned();
 
             }
-            neighbours.entry(*oid).or_default().extend(&log.events.get(&eid).unwrap().omap);
         }
 
     }
 
-    let new_edges: Vec<(usize, usize, EventAdd, Relations)> = ocdg.inodes.par_iter()
-                           .map(|(oid, _)| whole_instance_edges(&log, &ocdg, oid, &neighbours, &rel_whole, &rel_inst))
+    let mut new_edges: Vec<(usize, usize, EventAdd, Relations)> = ocdg.inodes.par_iter()
+                           .map(|(oid, _)| whole_instance_edges(log, &ocdg, oid, &neighbours, &rel_whole, &rel_inst))
                            .flatten()
                            .collect();
+    if relations.contains(&Relations::LINKED) {
+        new_edges.extend(linked_edges(log, &ocdg));
+    }
 
     let mut ev_added: AHashSet<usize> = AHashSet::new();
     for edge in new_edges {
@@ -366,22 +344,34 @@ pub fn generate_ocdg(log: &Ocel, relations: &Vec<Relations>) -> Ocdg {
     
     // add event mappings
     for ev in ev_added {
-        ocdg.event_map.insert(log.event_map.get_by_right(&ev).expect("This cannot fail ever").to_owned(), ev);
+        let name = log.event_map.get_by_right(&ev).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is missing from the event map.", ev)))?;
+        ocdg.event_map.insert(name.to_owned(), ev);
     }
-    ocdg
+    Ok(ocdg)
 }
 
+/// Objects that share an event with each object, including the object itself.
+/// Objects without events or missing from the log have no entry.
+pub(crate) fn object_neighbours(log: &Ocel) -> IntMap<usize, IntSet<usize>> {
+    let mut neighbours: IntMap<usize, IntSet<usize>> = IntMap::default();
+    for data in log.events.values() {
+        for oid in data.omap.iter().filter(|oid| log.objects.contains_key(oid)) {
+            neighbours.entry(*oid).or_default().extend(data.omap.iter().filter(|oid| log.objects.contains_key(oid)));
+        }
+    }
+    neighbours
+}
 
 fn whole_instance_edges(log: &Ocel, ocdg:&Ocdg, oid1: &usize, neighs: &IntMap<usize, IntSet<usize>>, rel_whole: &Vec<&Relations>, rel_inst: &Vec<&Relations>) -> Vec<(usize, usize, EventAdd, Relations)> {
         // println!("{:?} reporting in!", &oid1);
         let mut oid_edges: Vec<(usize, usize, EventAdd, Relations)> = vec![];
         for rel in rel_whole {
-            oid_edges.extend(rel.execute_whole(&log, &ocdg, neighs, *oid1));
+            oid_edges.extend(rel.execute_whole(log, ocdg, neighs, *oid1));
         }
-        for oid2 in neighs.get(oid1).unwrap() {
+        for oid2 in neighs.get(oid1).into_iter().flatten() {
             if oid1 != oid2 {
                 for rel in rel_inst {
-                    oid_edges.extend(rel.execute(&log, &ocdg, *oid1, *oid2));
+                    oid_edges.extend(rel.execute(log, ocdg, *oid1, *oid2));
                 }
             }
 
@@ -390,6 +380,15 @@ fn whole_instance_edges(log: &Ocel, ocdg:&Ocdg, oid1: &usize, neighs: &IntMap<us
 
 }
 
+/// Edges of the links between objects, links are not part of any event.
+fn linked_edges(log: &Ocel, ocdg: &Ocdg) -> Vec<(usize, usize, EventAdd, Relations)> {
+    log.o2o.iter()
+           .flat_map(|(src, links)| links.iter().map(move |link| (*src, link.target)))
+           .filter(|(src, tar)| src != tar && ocdg.inodes.contains_key(src) && ocdg.inodes.contains_key(tar))
+           .map(|(src, tar)| (src, tar, EventAdd::MULTI(IntSet::default()), Relations::LINKED))
+           .collect()
+}
+
 fn intersection_count_sorted_vec(a: &[usize], b: &[usize]) -> IntSet<usize> {
     let mut intersected: IntSet<usize> = IntSet::default();
     let mut b_iter = b.iter();
@@ -408,3 +407,69 @@ fn intersection_count_sorted_vec(a: &[usize], b: &[usize]) -> IntSet<usize> {
     }
     intersected
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, BTreeSet};
+    use strum::IntoEnumIterator;
+    use crate::objects::ocel::exporter::generate_ocel_external_repr;
+    use crate::objects::ocel::importer::{import_ocel, import_ocel_from_str};
+
+    /// Relations of the graph by object and event names, so that graphs of differently numbered logs can be compared.
+    fn named_relations(ocdg: &Ocdg, log: &Ocel) -> BTreeMap<(String, String, u8), BTreeSet<String>> {
+        let oname = |oid: &usize| log.object_map.get_by_right(oid).unwrap().to_owned();
+        ocdg.irels.iter().flat_map(|(src, targets)| targets.iter().flat_map(move |(tar, rels)| rels.iter().map(move |(rel, eids)| (src, tar, rel, eids))))
+                  .map(|(src, tar, rel, eids)| ((oname(src), oname(tar), *rel), eids.iter().map(|eid| log.event_map.get_by_right(eid).unwrap().to_owned()).collect()))
+                  .collect()
+    }
+
+    #[test]
+    fn test_inconsistent_log() {
+        let mut log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        let oid = *log.object_map.get_by_left("o1").unwrap();
+        log.objects_mut().get_mut(&oid).unwrap().events.clear();
+        log.events_mut().values_mut().next().unwrap().omap.insert(usize::MAX);
+
+        let ocdg = generate_ocdg(&log, &[Relations::INTERACTS, Relations::DESCENDANTS, Relations::SPLIT, Relations::ENGAGES]).unwrap();
+        assert!(!ocdg.irels.contains_key(&oid));
+        assert!(!ocdg.node_attributes.contains_key(&usize::MAX));
+
+        log.object_map.remove_by_left("i1");
+        assert!(matches!(generate_ocdg(&log, &[Relations::INTERACTS]), Err(Error::ReferentialIntegrity(_))));
+    }
+
+    #[test]
+    fn test_linked_objects() {
+        let log = import_ocel("logs/ocel2-test.json").expect("What did you do to the file?");
+        let (o1, i1) = (*log.object_map.get_by_left("o1").unwrap(), *log.object_map.get_by_left("i1").unwrap());
+
+        let ocdg = generate_ocdg(&log, &[Relations::LINKED]).unwrap();
+        assert_eq!(ocdg.irels[&o1][&i1].get(&Relations::LINKED.relation_index()), Some(&IntSet::default()));
+        assert!(!ocdg.irels.contains_key(&i1));
+
+        let ocdg = generate_ocdg(&log, &[Relations::INTERACTS]).unwrap();
+        assert!(!ocdg.irels[&o1][&i1].contains_key(&Relations::LINKED.relation_index()));
+    }
+
+    #[test]
+    fn test_shuffled_log() {
+        let log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        let mut shuffled = generate_ocel_external_repr(&log).unwrap();
+        shuffled.events.reverse();
+        shuffled.events.move_index(0, shuffled.events.len() / 2);
+        let shuffled_log = import_ocel_from_str(&serde_json::to_string(&shuffled).unwrap()).unwrap();
+
+        for (name, eid) in shuffled_log.event_map.iter() {
+            assert_eq!(log.event_map.get_by_left(name), Some(eid));
+        }
+        assert!(shuffled_log.objects.values().all(|obj| obj.events.windows(2).all(|w| w[0] < w[1])));
+
+        let relations: Vec<Relations> = Relations::iter().collect();
+        let ocdg = generate_ocdg(&log, &relations).unwrap();
+        let shuffled_ocdg = generate_ocdg(&shuffled_log, &relations).unwrap();
+        assert!(!ocdg.irels.is_empty());
+        assert_eq!(named_relations(&ocdg, &log), named_relations(&shuffled_ocdg, &shuffled_log));
+    }
+}
@@ -1,10 +1,11 @@
-pub(self) mod general_object_split;
+ mod general_object_split;
 
 use self::general_object_split::general_object_split_in_place;
 
 use super::Ocdg;
+use crate::Error;
 
 
-pub fn decompose_in_place(ocdg: Ocdg) -> Ocdg {
+pub fn decompose_in_place(ocdg: Ocdg) -> Result<Ocdg, Error> {
     general_object_split_in_place(ocdg)
 }
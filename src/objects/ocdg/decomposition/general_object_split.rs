@@ -1,32 +1,31 @@
 use crate::objects::ocdg::{Ocdg, Relations};
+use crate::Error;
 use ahash::{AHashSet, AHashMap};
 use nohash_hasher::IntSet;
 
 
 
-pub fn general_object_split_in_place(mut ocdg: Ocdg) -> Ocdg {
+pub fn general_object_split_in_place(mut ocdg: Ocdg) -> Result<Ocdg, Error> {
     let mut ot_set = AHashSet::<String>::default();
     let mut to_remove: Vec<(usize, usize)> = vec![];
     ocdg.node_attributes.iter().for_each(|(_, v)| {ot_set.insert(v.node_type.clone());});
     for (src, tar_map) in &ocdg.irels {
-        let mut ot_check = AHashMap::<String, IntSet<usize>>::default();
-        let mut edge_list = AHashMap::<String, Vec<usize>>::default();
+        // events and targets of the descendant edges per target type
+        let mut edge_list = AHashMap::<&str, (IntSet<usize>, Vec<usize>)>::default();
         for (tar, rel_map) in tar_map {
-            let tar_type = &ocdg.node_attributes.get(tar).expect("this cannot fail").node_type;
-            match rel_map.get(&Relations::DESCENDANTS.into()) {
-                Some(v) => {
-                    if !v.is_empty() {
-                        ot_check.entry(tar_type.to_string()).or_default().extend(v);
-                        edge_list.entry(tar_type.to_string()).or_default().push(*tar);
-                    }
-                },
-                None => {}
+            let tar_type = &ocdg.node_attributes.get(tar).ok_or_else(|| Error::ReferentialIntegrity(format!("Node {} has no attributes.", tar)))?.node_type;
+            if let Some(v) = rel_map.get(&Relations::DESCENDANTS.into()) {
+                if !v.is_empty() {
+                    let (events, targets) = edge_list.entry(tar_type).or_default();
+                    events.extend(v);
+                    targets.push(*tar);
+                }
             }
         }
 
         // check which edges need to be removed
-        for (ot, rel_edges) in &edge_list {
-            if ot_check.get(ot).expect("cannot fail").len() > 1 {
+        for (events, rel_edges) in edge_list.values() {
+            if events.len() > 1 {
                 rel_edges.iter()
                          .for_each(|edge_tar| {
                              to_remove.push((*src, *edge_tar));
@@ -37,14 +36,17 @@ pub fn general_object_split_in_place(mut ocdg: Ocdg) -> Ocdg {
 
     // remove the edges -> make changes as we are no longer in the borrow
     for (src, tar) in to_remove {
+        let edge = ocdg.iedges.get_mut(&src).and_then(|targets| targets.remove(&tar))
+                              .ok_or_else(|| Error::ReferentialIntegrity(format!("The edge from {} to {} is missing from the graph.", src, tar)))?;
+        ocdg.net.remove_edge(edge);
         ocdg.node_attributes.entry(src).or_default().src_cut.insert(tar);
         ocdg.node_attributes.entry(tar).or_default().tar_cut.insert(src);
-        ocdg.net.remove_edge(*ocdg.iedges.get(&src).expect("").get(&tar).expect(""));
-        ocdg.iedges.get_mut(&src).expect("src no longer in iedge").remove(&tar);
-        ocdg.irels.get_mut(&src).expect("src no longer in irel").remove(&tar);
+        if let Some(targets) = ocdg.irels.get_mut(&src) {
+            targets.remove(&tar);
+        }
     }
 
-    ocdg
+    Ok(ocdg)
 }
 
 
@@ -56,10 +58,10 @@ mod tests {
 
     #[test]
     fn test_decompose_general_object_split() {
-        let default: Ocdg = generate_ocdg(&import_ocel("logs/ocel-decomposition-test.jsonocel").expect("What did you do to the file?"), &vec![Relations::DESCENDANTS]);
+        let default: Ocdg = generate_ocdg(&import_ocel("logs/ocel-decomposition-test.jsonocel").expect("What did you do to the file?"), &[Relations::DESCENDANTS]).unwrap();
         assert_eq!(default.net.edge_count(), 4);
 
-        let decomposed: Ocdg = general_object_split_in_place(default);
+        let decomposed: Ocdg = general_object_split_in_place(default).unwrap();
         assert_eq!(decomposed.net.edge_count(), 2);
 
         let general_object = decomposed.object_map.get_by_left("i1").expect("cannot fail");
@@ -78,4 +80,12 @@ mod tests {
         assert_eq!(decomposed.node_attributes.get(nothing).unwrap().src_cut.len(), 0);
         assert_eq!(decomposed.node_attributes.get(nothing).unwrap().tar_cut.len(), 0);
     }
+
+    #[test]
+    fn test_missing_node_attributes() {
+        let mut default: Ocdg = generate_ocdg(&import_ocel("logs/ocel-decomposition-test.jsonocel").expect("What did you do to the file?"), &[Relations::DESCENDANTS]).unwrap();
+        let p1 = *default.object_map.get_by_left("p1").unwrap();
+        default.node_attributes.remove(&p1);
+        assert!(matches!(general_object_split_in_place(default), Err(Error::ReferentialIntegrity(_))));
+    }
 }
@@ -1,16 +1,33 @@
 pub(crate) mod variants;
 
-use std::error::Error;
+use crate::Error;
+use std::io::Write;
 
-use self::variants::gexf::{export_gexf_ocdg, ocdg_to_xml};
+use self::variants::gexf::{export_gexf_ocdg, export_gexf_ocdg_to_writer, ocdg_to_xml};
 
 use super::Ocdg;
+use super::format::{OcdgFormat, ocdg_format_handler};
 
 
-pub fn generate_ocdg_string(g: &Ocdg) -> Result<String, Box<dyn Error>> {
-    return ocdg_to_xml(g);
+pub fn generate_ocdg_string(g: &Ocdg) -> Result<String, Error> {
+    ocdg_to_xml(g)
 }
 
-pub fn export_ocdg(g: &Ocdg, file_path: &str) -> Result<bool, Box<dyn Error>> {
-    return export_gexf_ocdg(g, file_path);
+/// Export a graph in the format belonging to the extension of the path, gexf if it has none.
+pub fn export_ocdg(g: &Ocdg, file_path: &str) -> Result<bool, Error> {
+    export_ocdg_as(g, file_path, OcdgFormat::from_extension(file_path).unwrap_or(OcdgFormat::Gexf))
+}
+
+pub fn export_ocdg_as(g: &Ocdg, file_path: &str, format: OcdgFormat) -> Result<bool, Error> {
+    match format {
+        OcdgFormat::Gexf => export_gexf_ocdg(g, file_path),
+        OcdgFormat::Custom(name) => ocdg_format_handler(&name)?.export(g, file_path)
+    }
+}
+
+pub fn export_ocdg_to_writer<W: Write>(g: &Ocdg, mut writer: W, format: OcdgFormat) -> Result<bool, Error> {
+    match format {
+        OcdgFormat::Gexf => export_gexf_ocdg_to_writer(g, writer),
+        OcdgFormat::Custom(name) => ocdg_format_handler(&name)?.export_to_writer(g, &mut writer)
+    }
 }
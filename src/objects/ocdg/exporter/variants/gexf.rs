@@ -1,20 +1,23 @@
-use std::{fs::OpenOptions, io::{BufWriter, Write}, error::Error};
+use std::{fs::OpenOptions, io::{BufWriter, Write}};
+use crate::Error;
 use quick_xml::se::to_string;
 use strum::IntoEnumIterator;
 
 use crate::objects::ocdg::{variants::gexf::{Gexf, NodeGexf, AttValuesGexf, AttValueGexf, EdgeGexf, AttributesGexf, AttributeGexf}, Ocdg, Relations};
 
 
-pub(crate) fn ocdg_to_gexf(g: &Ocdg) -> Result<Gexf, Box<dyn Error>> {
+pub(crate) fn ocdg_to_gexf(g: &Ocdg) -> Result<Gexf, Error> {
     let mut gexf_repr: Gexf = Gexf::new();
+    let oname = |oid: &usize| g.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)));
+    let ename = |eid: &usize| g.event_map.get_by_right(eid).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is missing from the event map.", eid)));
 
         // object attr
-        let mut node_attrs: Vec<AttributeGexf> = vec![];
-        node_attrs.push(AttributeGexf { id: 0.to_string(), title: "type".to_string(), attr_type: "string".to_string()});
-
-        // these should be liststrings but gephi does not like exporting them...
-        node_attrs.push(AttributeGexf { id: 1.to_string(), title: "src_cut".to_string(), attr_type: "string".to_string()});
-        node_attrs.push(AttributeGexf { id: 2.to_string(), title: "tar_cut".to_string(), attr_type: "string".to_string()});
+        let node_attrs: Vec<AttributeGexf> = vec![
+            AttributeGexf { id: 0.to_string(), title: "type".to_string(), attr_type: "string".to_string()},
+            // these should be liststrings but gephi does not like exporting them...
+            AttributeGexf { id: 1.to_string(), title: "src_cut".to_string(), attr_type: "string".to_string()},
+            AttributeGexf { id: 2.to_string(), title: "tar_cut".to_string(), attr_type: "string".to_string()}
+        ];
         gexf_repr.graph.attributes.push(AttributesGexf { class: "node".to_string(), attributes: node_attrs });
 
         // edge attr
@@ -32,19 +35,19 @@ pub(crate) fn ocdg_to_gexf(g: &Ocdg) -> Result<Gexf, Box<dyn Error>> {
         for (oid, data) in &g.node_attributes {
             let mut attrvalues: Vec<AttValueGexf> = vec![];
             attrvalues.push(AttValueGexf { attr: 0.to_string(), value: data.node_type.to_owned() });
-            let src_cut_str: Vec<&String> = data.src_cut.iter().map(|ob| g.object_map.get_by_right(ob).expect("cannot fail")).collect();
-            let tar_cut_str: Vec<&String> = data.tar_cut.iter().map(|ob| g.object_map.get_by_right(ob).expect("cannot fail")).collect();
+            let src_cut_str: Vec<&String> = data.src_cut.iter().map(oname).collect::<Result<_, Error>>()?;
+            let tar_cut_str: Vec<&String> = data.tar_cut.iter().map(oname).collect::<Result<_, Error>>()?;
             attrvalues.push(AttValueGexf { attr: 1.to_string(), value: format!("{:?}", src_cut_str).replace("\"", "'") });
             attrvalues.push(AttValueGexf { attr: 2.to_string(), value: format!("{:?}", tar_cut_str).replace("\"", "'") });
 
-            gexf_repr.graph.nodes.nodes.push(NodeGexf {id: oid.to_string(), label: g.object_map.get_by_right(oid).expect("This can't fail").to_owned(), attvalues: AttValuesGexf {attvalues: attrvalues}});
+            gexf_repr.graph.nodes.nodes.push(NodeGexf {id: oid.to_string(), label: oname(oid)?.to_owned(), attvalues: AttValuesGexf {attvalues: attrvalues}});
         }
 
         for (src, edge_data) in &g.irels {
             for (tar, rels) in edge_data {
                 let mut attrvalues: Vec<AttValueGexf> = vec![];
                 for (r, events) in rels {
-                    let ev_s: Vec<String> = events.iter().map(|eid| g.event_map.get_by_right(eid).expect("This can't fail").to_owned()).collect();
+                    let ev_s: Vec<String> = events.iter().map(|eid| ename(eid).map(|name| name.to_owned())).collect::<Result<_, Error>>()?;
                     attrvalues.push(AttValueGexf { attr: r.to_string(), value: format!("{:?}", ev_s).replace("\"", "'") });
                 }
 
@@ -56,7 +59,7 @@ pub(crate) fn ocdg_to_gexf(g: &Ocdg) -> Result<Gexf, Box<dyn Error>> {
         Ok(gexf_repr)
 }
 
-pub(crate) fn ocdg_to_xml(g: &Ocdg) -> Result<String, Box<dyn Error>> {
+pub(crate) fn ocdg_to_xml(g: &Ocdg) -> Result<String, Error> {
     let gexf_repr: Gexf = ocdg_to_gexf(g)?;
     let mut ocdg_xml = r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string();
     ocdg_xml.push_str(&to_string(&gexf_repr)?);
@@ -65,13 +68,15 @@ pub(crate) fn ocdg_to_xml(g: &Ocdg) -> Result<String, Box<dyn Error>> {
 }
 
 
-pub(crate) fn export_gexf_ocdg(g: &Ocdg, file_path: &str) -> Result<bool, Box<dyn Error>> {
-    
-    let ocdg_xml: String = ocdg_to_xml(g)?;
+pub(crate) fn export_gexf_ocdg(g: &Ocdg, file_path: &str) -> Result<bool, Error> {
+    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path)?;
+    export_gexf_ocdg_to_writer(g, BufWriter::new(output_file))
+}
 
-    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path).unwrap();
-    let mut f = BufWriter::new(output_file);
-    f.write_all(ocdg_xml.as_bytes()).expect("Unable to write data");
+pub(crate) fn export_gexf_ocdg_to_writer<W: Write>(g: &Ocdg, mut writer: W) -> Result<bool, Error> {
+    let ocdg_xml: String = ocdg_to_xml(g)?;
+    writer.write_all(ocdg_xml.as_bytes())?;
+    writer.flush()?;
 
     Ok(true)
 }
@@ -0,0 +1,142 @@
+use crate::Error;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use crate::objects::ocel::Ocel;
+
+use super::Ocdg;
+
+/// Number of leading bytes looked at when sniffing the format of a file.
+const SNIFF_LENGTH: usize = 4096;
+
+lazy_static::lazy_static!{
+    static ref OCDG_FORMATS: RwLock<Vec<Arc<dyn OcdgFormatHandler>>> = RwLock::new(vec![]);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcdgFormat {
+    Gexf,
+    /// A format registered with `register_ocdg_format`, referenced by its name.
+    Custom(String)
+}
+
+/// Import and export of a graph format that is not part of the crate.
+pub trait OcdgFormatHandler: Send + Sync {
+    fn name(&self) -> &str;
+    /// File extensions without the leading dot.
+    fn extensions(&self) -> Vec<&str>;
+    /// Whether the first bytes of a file belong to this format.
+    fn sniff(&self, _head: &[u8]) -> bool {
+        false
+    }
+    fn import(&self, file_path: &str) -> Result<Ocdg, Error>;
+    /// Import a graph whose object and event ids are taken from an existing log.
+    fn import_link_ocel(&self, _file_path: &str, _log: &Ocel) -> Result<Ocdg, Error> {
+        Err(Error::UnsupportedFormat(format!("The ocdg format {} cannot be linked to an ocel.", self.name())))
+    }
+    fn export(&self, g: &Ocdg, file_path: &str) -> Result<bool, Error>;
+    fn import_from_reader(&self, _reader: &mut dyn Read) -> Result<Ocdg, Error> {
+        Err(Error::UnsupportedFormat(format!("The ocdg format {} can only be imported from a file.", self.name())))
+    }
+    fn import_link_ocel_from_reader(&self, _reader: &mut dyn Read, _log: &Ocel) -> Result<Ocdg, Error> {
+        Err(Error::UnsupportedFormat(format!("The ocdg format {} cannot be linked to an ocel.", self.name())))
+    }
+    fn export_to_writer(&self, _g: &Ocdg, _writer: &mut dyn Write) -> Result<bool, Error> {
+        Err(Error::UnsupportedFormat(format!("The ocdg format {} can only be exported to a file.", self.name())))
+    }
+}
+
+/// Register a custom format. A later registration with the same name replaces the earlier one.
+pub fn register_ocdg_format(handler: Arc<dyn OcdgFormatHandler>) {
+    let mut formats = OCDG_FORMATS.write().unwrap_or_else(|e| e.into_inner());
+    formats.retain(|f| f.name() != handler.name());
+    formats.push(handler);
+}
+
+pub(crate) fn ocdg_format_handler(name: &str) -> Result<Arc<dyn OcdgFormatHandler>, Error> {
+    let formats = OCDG_FORMATS.read().unwrap_or_else(|e| e.into_inner());
+    match formats.iter().find(|f| f.name() == name) {
+        Some(handler) => Ok(handler.clone()),
+        None => Err(Error::UnsupportedFormat(format!("The ocdg format {} has not been registered.", name)))
+    }
+}
+
+impl OcdgFormat {
+    /// Detect the format by file extension and fall back to the content of the file.
+    pub fn detect(file_path: &str) -> Result<OcdgFormat, Error> {
+        if let Some(format) = OcdgFormat::from_extension(file_path) {
+            return Ok(format);
+        }
+
+        let mut head: Vec<u8> = vec![];
+        File::open(file_path)?.take(SNIFF_LENGTH as u64).read_to_end(&mut head)?;
+        OcdgFormat::sniff(&head).ok_or_else(|| Error::UnsupportedFormat(format!("Could not detect the ocdg format of {}.", file_path)))
+    }
+
+    pub fn from_extension(file_path: &str) -> Option<OcdgFormat> {
+        let ext = Path::new(file_path).extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "gexf" => Some(OcdgFormat::Gexf),
+            _ => {
+                let formats = OCDG_FORMATS.read().unwrap_or_else(|e| e.into_inner());
+                formats.iter()
+                       .find(|f| f.extensions().iter().any(|f_ext| f_ext.eq_ignore_ascii_case(&ext)))
+                       .map(|f| OcdgFormat::Custom(f.name().to_string()))
+            }
+        }
+    }
+
+    pub fn sniff(head: &[u8]) -> Option<OcdgFormat> {
+        {
+            let formats = OCDG_FORMATS.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(f) = formats.iter().find(|f| f.sniff(head)) {
+                return Some(OcdgFormat::Custom(f.name().to_string()));
+            }
+        }
+
+        if String::from_utf8_lossy(head).contains("<gexf") {
+            Some(OcdgFormat::Gexf)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestFormat;
+
+    impl OcdgFormatHandler for TestFormat {
+        fn name(&self) -> &str {
+            "test-format"
+        }
+
+        fn extensions(&self) -> Vec<&str> {
+            vec!["testocdg"]
+        }
+
+        fn import(&self, _file_path: &str) -> Result<Ocdg, Error> {
+            Ok(Ocdg::default())
+        }
+
+        fn export(&self, _g: &Ocdg, _file_path: &str) -> Result<bool, Error> {
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(OcdgFormat::from_extension("graph.gexf"), Some(OcdgFormat::Gexf));
+        assert_eq!(OcdgFormat::sniff(b"<?xml version=\"1.0\"?><gexf xmlns=\"\">"), Some(OcdgFormat::Gexf));
+        assert_eq!(OcdgFormat::sniff(b"{}"), None);
+
+        register_ocdg_format(Arc::new(TestFormat));
+        assert_eq!(OcdgFormat::from_extension("graph.testocdg"), Some(OcdgFormat::Custom("test-format".to_string())));
+        assert!(ocdg_format_handler("test-format").unwrap().import_link_ocel("graph.testocdg", &Ocel::default()).is_err());
+    }
+}
@@ -1,16 +1,127 @@
 pub(crate) mod variants;
-use std::error::Error;
+use crate::Error;
+use std::io::Read;
 
 use crate::objects::ocel::Ocel;
 
-use self::variants::gexf::{import_gexf_ocdg, import_gexf_ocdg_link_ocel};
+use self::variants::gexf::{import_gexf_ocdg, import_gexf_ocdg_from_reader, import_gexf_ocdg_link_ocel, import_gexf_ocdg_link_ocel_from_reader};
 
 use super::Ocdg;
+use super::format::{OcdgFormat, ocdg_format_handler};
 
-pub fn import_ocdg(file_path: &str) -> Result<Ocdg, Box<dyn Error>> {
-    return import_gexf_ocdg(file_path);
+/// Import a graph in the format detected from its extension or content.
+pub fn import_ocdg(file_path: &str) -> Result<Ocdg, Error> {
+    import_ocdg_as(file_path, OcdgFormat::detect(file_path)?)
 }
 
-pub fn import_ocdg_link_ocel(file_path: &str, log: &Ocel) -> Result<Ocdg, Box<dyn Error>> {
-    return import_gexf_ocdg_link_ocel(file_path, log);
+pub fn import_ocdg_as(file_path: &str, format: OcdgFormat) -> Result<Ocdg, Error> {
+    match format {
+        OcdgFormat::Gexf => import_gexf_ocdg(file_path),
+        OcdgFormat::Custom(name) => ocdg_format_handler(&name)?.import(file_path)
+    }
+}
+
+pub fn import_ocdg_from_reader<R: Read>(mut reader: R, format: OcdgFormat) -> Result<Ocdg, Error> {
+    match format {
+        OcdgFormat::Gexf => import_gexf_ocdg_from_reader(reader),
+        OcdgFormat::Custom(name) => ocdg_format_handler(&name)?.import_from_reader(&mut reader)
+    }
+}
+
+/// Import a graph held in memory, the format is detected from its content.
+pub fn import_ocdg_from_slice(bytes: &[u8]) -> Result<Ocdg, Error> {
+    let format = OcdgFormat::sniff(bytes).ok_or_else(|| Error::UnsupportedFormat("Could not detect the ocdg format of the input.".to_string()))?;
+    import_ocdg_from_reader(bytes, format)
+}
+
+pub fn import_ocdg_from_str(s: &str) -> Result<Ocdg, Error> {
+    import_ocdg_from_slice(s.as_bytes())
+}
+
+pub fn import_ocdg_link_ocel(file_path: &str, log: &Ocel) -> Result<Ocdg, Error> {
+    import_ocdg_link_ocel_as(file_path, log, OcdgFormat::detect(file_path)?)
+}
+
+pub fn import_ocdg_link_ocel_as(file_path: &str, log: &Ocel, format: OcdgFormat) -> Result<Ocdg, Error> {
+    match format {
+        OcdgFormat::Gexf => import_gexf_ocdg_link_ocel(file_path, log),
+        OcdgFormat::Custom(name) => ocdg_format_handler(&name)?.import_link_ocel(file_path, log)
+    }
+}
+
+pub fn import_ocdg_link_ocel_from_reader<R: Read>(mut reader: R, log: &Ocel, format: OcdgFormat) -> Result<Ocdg, Error> {
+    match format {
+        OcdgFormat::Gexf => import_gexf_ocdg_link_ocel_from_reader(reader, log),
+        OcdgFormat::Custom(name) => ocdg_format_handler(&name)?.import_link_ocel_from_reader(&mut reader, log)
+    }
+}
+
+pub fn import_ocdg_link_ocel_from_slice(bytes: &[u8], log: &Ocel) -> Result<Ocdg, Error> {
+    let format = OcdgFormat::sniff(bytes).ok_or_else(|| Error::UnsupportedFormat("Could not detect the ocdg format of the input.".to_string()))?;
+    import_ocdg_link_ocel_from_reader(bytes, log, format)
+}
+
+pub fn import_ocdg_link_ocel_from_str(s: &str, log: &Ocel) -> Result<Ocdg, Error> {
+    import_ocdg_link_ocel_from_slice(s.as_bytes(), log)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ocdg::{generate_ocdg, Relations};
+    use crate::objects::ocdg::exporter::export_ocdg_to_writer;
+    use crate::objects::ocel::importer::import_ocel;
+
+    #[test]
+    fn test_in_memory_round_trip() {
+        let log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        let ocdg = generate_ocdg(&log, &[Relations::INTERACTS, Relations::DESCENDANTS]).unwrap();
+        let mut buf: Vec<u8> = vec![];
+        export_ocdg_to_writer(&ocdg, &mut buf, OcdgFormat::Gexf).expect("could not export the graph");
+
+        let reimported = import_ocdg_from_slice(&buf).expect("could not import the exported graph");
+        assert_eq!(ocdg.net.node_count(), reimported.net.node_count());
+        assert_eq!(ocdg.net.edge_count(), reimported.net.edge_count());
+
+        let linked = import_ocdg_link_ocel_from_slice(&buf, &log).expect("could not import the exported graph");
+        assert_eq!(ocdg.irels, linked.irels);
+    }
+
+    #[test]
+    fn test_export_unnamed_objects() {
+        let log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        let mut ocdg = generate_ocdg(&log, &[Relations::INTERACTS]).unwrap();
+        ocdg.event_map.clear();
+        assert!(matches!(export_ocdg_to_writer(&ocdg, vec![], OcdgFormat::Gexf), Err(Error::ReferentialIntegrity(_))));
+        ocdg.object_map.remove_by_left("i1");
+        assert!(matches!(export_ocdg_to_writer(&ocdg, vec![], OcdgFormat::Gexf), Err(Error::ReferentialIntegrity(_))));
+    }
+
+    #[test]
+    fn test_link_unknown_objects() {
+        let log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        let ocdg = generate_ocdg(&log, &[Relations::INTERACTS]).unwrap();
+        let mut buf: Vec<u8> = vec![];
+        export_ocdg_to_writer(&ocdg, &mut buf, OcdgFormat::Gexf).expect("could not export the graph");
+
+        let other_log = import_ocel("logs/min.jsonocel").expect("What did you do to the file?");
+        assert!(matches!(import_ocdg_link_ocel_from_slice(&buf, &other_log), Err(Error::ReferentialIntegrity(_))));
+
+        // nodes without the source and target cut attributes
+        let missing_cuts = r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">
+            <meta><creator>pmrs</creator><description>test</description></meta>
+            <graph defaultedgetype="directed">
+                <attributes class="node"></attributes>
+                <nodes>
+                    <node id="0" label="o1"><attvalues><attvalue for="0" value="order"/></attvalues></node>
+                    <node id="1" label="i1"><attvalues><attvalue for="0" value="item"/></attvalues></node>
+                </nodes>
+                <edges>
+                    <edge source="0" target="1"><attvalues><attvalue for="0" value="['e1']"/></attvalues></edge>
+                </edges>
+            </graph>
+        </gexf>"#;
+        assert!(matches!(import_ocdg_from_slice(missing_cuts.as_bytes()), Err(Error::Schema(_))));
+    }
 }
@@ -1,14 +1,23 @@
-use std::{error::Error, fs::File, io::Read, iter::FromIterator};
+use std::{fs::File, io::Read};
+use crate::Error;
 use nohash_hasher::{IntMap, IntSet};
 
 use quick_xml::de::from_str;
 
-use crate::objects::{ocdg::{variants::gexf::Gexf, Ocdg}, ocel::Ocel};
+use crate::objects::{ocdg::{variants::gexf::{Gexf, NodeGexf}, Ocdg}, ocel::Ocel};
 
-pub fn import_gexf_ocdg(file_path: &str) -> Result<Ocdg, Box<dyn Error>> {
+pub fn import_gexf_ocdg(file_path: &str) -> Result<Ocdg, Error> {
+   import_gexf_ocdg_from_reader(File::open(file_path)?)
+}
+
+pub fn import_gexf_ocdg_from_reader<R: Read>(mut reader: R) -> Result<Ocdg, Error> {
    let mut s = String::new();
-   File::open(file_path)?.read_to_string(&mut s)?;
-   let g: Gexf = from_str(&s)?;
+   reader.read_to_string(&mut s)?;
+   import_gexf_ocdg_from_str(&s)
+}
+
+pub fn import_gexf_ocdg_from_str(s: &str) -> Result<Ocdg, Error> {
+   let g: Gexf = from_str(s)?;
 
    let mut ocdg: Ocdg = Ocdg::default();
 
@@ -17,7 +26,7 @@ pub fn import_gexf_ocdg(file_path: &str) -> Result<Ocdg, Box<dyn Error>> {
        let new_node = ocdg.net.add_node(oid);
        ocdg.object_map.insert(obj.label.to_owned(), oid);
 
-       ocdg.node_attributes.entry(oid).or_default().node_type = obj.attvalues.attvalues[0].value.to_owned();
+       ocdg.node_attributes.entry(oid).or_default().node_type = node_attribute(obj, 0)?.to_owned();
 
        ocdg.inodes.entry(oid).or_insert(new_node);
    }
@@ -25,18 +34,23 @@ pub fn import_gexf_ocdg(file_path: &str) -> Result<Ocdg, Box<dyn Error>> {
    // add src_cuts and tar_cuts after object map is complete
    for obj in g.graph.nodes.nodes {
        let oid = obj.id.parse::<usize>()?;
-       let src_cut_prep = obj.attvalues.attvalues[1].value.replace("'", "\"");
+       let src_cut_prep = node_attribute(&obj, 1)?.replace("'", "\"");
        let src_cut_decode: Vec<&str> = ron::from_str(src_cut_prep.as_str())?;
-       ocdg.node_attributes.entry(oid).or_default().src_cut = IntSet::from_iter(src_cut_decode.iter().map(|s| ocdg.object_map.get_by_left(*s).unwrap().to_owned()));
-       let tar_cut_prep = obj.attvalues.attvalues[2].value.replace("'", "\"");
+       let src_cut = src_cut_decode.iter().map(|s| known_object(&ocdg, s)).collect::<Result<IntSet<usize>, Error>>()?;
+       ocdg.node_attributes.entry(oid).or_default().src_cut = src_cut;
+       let tar_cut_prep = node_attribute(&obj, 2)?.replace("'", "\"");
        let tar_cut_decode: Vec<&str> = ron::from_str(tar_cut_prep.as_str())?;
-       ocdg.node_attributes.entry(oid).or_default().tar_cut = IntSet::from_iter(tar_cut_decode.iter().map(|s| ocdg.object_map.get_by_left(*s).unwrap().to_owned()));
+       let tar_cut = tar_cut_decode.iter().map(|s| known_object(&ocdg, s)).collect::<Result<IntSet<usize>, Error>>()?;
+       ocdg.node_attributes.entry(oid).or_default().tar_cut = tar_cut;
    }
 
    let mut ev_id: usize = usize::MIN;
     for ev in g.graph.edges.edges {
        let src_o: usize = ev.source.parse::<usize>()?;
        let tar_o: usize = ev.target.parse::<usize>()?;
+       let (Some(src_node), Some(tar_node)) = (ocdg.inodes.get(&src_o).copied(), ocdg.inodes.get(&tar_o).copied()) else {
+           return Err(Error::ReferentialIntegrity(format!("The edge from {} to {} connects nodes that are not part of the graph.", ev.source, ev.target)));
+       };
 
        for rel in ev.attvalues.attvalues {
            let rel_value_decode = rel.value.replace("'", "\"");
@@ -51,14 +65,14 @@ pub fn import_gexf_ocdg(file_path: &str) -> Result<Ocdg, Box<dyn Error>> {
                             },
                             None => {
                                 ocdg.event_map.insert(eid.to_string(), ev_id);
-                                ev_id = ev_id + 1;
+                                ev_id += 1;
                                 ev_id - 1
                             }
                         }
                      }).collect());
        }
 
-       let new_edge = ocdg.net.add_edge(ocdg.inodes[&src_o], ocdg.inodes[&tar_o], 0);
+       let new_edge = ocdg.net.add_edge(src_node, tar_node, 0);
        ocdg.iedges.entry(src_o).or_default().entry(tar_o).or_insert(new_edge);
 
    }
@@ -68,36 +82,50 @@ pub fn import_gexf_ocdg(file_path: &str) -> Result<Ocdg, Box<dyn Error>> {
 
 
 
-pub fn import_gexf_ocdg_link_ocel(file_path: &str, log: &Ocel) -> Result<Ocdg, Box<dyn Error>> {
+pub fn import_gexf_ocdg_link_ocel(file_path: &str, log: &Ocel) -> Result<Ocdg, Error> {
+   import_gexf_ocdg_link_ocel_from_reader(File::open(file_path)?, log)
+}
+
+pub fn import_gexf_ocdg_link_ocel_from_reader<R: Read>(mut reader: R, log: &Ocel) -> Result<Ocdg, Error> {
    let mut s = String::new();
-   File::open(file_path)?.read_to_string(&mut s)?;
-   let g: Gexf = from_str(&s)?;
+   reader.read_to_string(&mut s)?;
+   import_gexf_ocdg_link_ocel_from_str(&s, log)
+}
+
+pub fn import_gexf_ocdg_link_ocel_from_str(s: &str, log: &Ocel) -> Result<Ocdg, Error> {
+   let g: Gexf = from_str(s)?;
 
    let mut ocdg: Ocdg = Ocdg::default();
 
-   let file_to_log: IntMap<usize, &usize> = IntMap::from_iter(g.graph.nodes.nodes.iter()
-                                                                                 .map(|node| (node.id.parse::<usize>().unwrap(), log.object_map.get_by_left(&node.label).unwrap())));
+   let file_to_log: IntMap<usize, &usize> = g.graph.nodes.nodes.iter()
+                                              .map(|node| Ok((node.id.parse::<usize>()?, log_object(log, &node.label)?)))
+                                              .collect::<Result<_, Error>>()?;
 
 
    for obj in g.graph.nodes.nodes {
-       let oid = log.object_map.get_by_left(&obj.label).unwrap();
+       let oid = log_object(log, &obj.label)?;
        let new_node = ocdg.net.add_node(*oid);
 
-       ocdg.node_attributes.entry(*oid).or_default().node_type = obj.attvalues.attvalues[0].value.to_owned();
+       ocdg.node_attributes.entry(*oid).or_default().node_type = node_attribute(&obj, 0)?.to_owned();
 
        ocdg.inodes.entry(*oid).or_insert(new_node);
    }
 
    for ev in g.graph.edges.edges {
-       let src_o: &usize = file_to_log[&ev.source.parse::<usize>()?];
-       let tar_o: &usize = file_to_log[&ev.target.parse::<usize>()?];
+       let (Some(src_o), Some(tar_o)) = (file_to_log.get(&ev.source.parse::<usize>()?).copied(), file_to_log.get(&ev.target.parse::<usize>()?).copied()) else {
+           return Err(Error::ReferentialIntegrity(format!("The edge from {} to {} connects nodes that are not part of the graph.", ev.source, ev.target)));
+       };
 
        for rel in ev.attvalues.attvalues {
-           let re: Vec<&str> = ron::from_str(&rel.value)?;
+           let rel_value_decode = rel.value.replace('\'', "\"");
+           let re: Vec<&str> = ron::from_str(rel_value_decode.as_str())?;
+           let events = re.iter()
+                          .map(|eid| log.event_map.get_by_left(*eid).copied().ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is not part of the log.", eid))))
+                          .collect::<Result<IntSet<usize>, Error>>()?;
            ocdg.irels.entry(*src_o).or_default()
                      .entry(*tar_o).or_default()
                      .entry(rel.attr.parse::<u8>()?)
-                     .or_insert(re.iter().map(|eid| log.event_map.get_by_left(*eid).unwrap().to_owned()).collect());
+                     .or_insert(events);
        }
 
        let new_edge = ocdg.net.add_edge(ocdg.inodes[src_o], ocdg.inodes[tar_o], 0);
@@ -107,3 +135,18 @@ pub fn import_gexf_ocdg_link_ocel(file_path: &str, log: &Ocel) -> Result<Ocdg, B
 
    Ok(ocdg)
 }
+
+/// Value of the attribute at `pos`, the node type, source and target cut in that order.
+fn node_attribute(node: &NodeGexf, pos: usize) -> Result<&str, Error> {
+   node.attvalues.attvalues.get(pos)
+                 .map(|att| att.value.as_str())
+                 .ok_or_else(|| Error::Schema(format!("Node {} has no attribute {}.", node.label, pos)))
+}
+
+fn known_object(ocdg: &Ocdg, label: &str) -> Result<usize, Error> {
+   ocdg.object_map.get_by_left(label).copied().ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not a node of the graph.", label)))
+}
+
+fn log_object<'a>(log: &'a Ocel, label: &str) -> Result<&'a usize, Error> {
+   log.object_map.get_by_left(label).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not part of the log.", label)))
+}
@@ -4,11 +4,14 @@ use serde::{Serialize, Deserialize};
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename="gexf")]
 pub struct Gexf {
+    #[serde(rename="@xmlns")]
     xmlns: String,
-    #[serde(alias="xmlns:xsi", rename(serialize="xmlns:xsi"))]
+    // namespaced attributes are optional when reading, quick-xml may only report their local name
+    #[serde(rename="@xmlns:xsi", alias="@xsi", default)]
     xmlnsxsi: String,
-    #[serde(alias="xsi:schemaLocation", rename(serialize="xsi:schemaLocation"))]
+    #[serde(rename="@xsi:schemaLocation", alias="@schemaLocation", default)]
     schemaloc: String,
+    #[serde(rename="@version")]
     version: String,
     pub meta: Meta,
     pub graph: GraphGexf
@@ -16,15 +19,14 @@ pub struct Gexf {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Meta {
-    #[serde(rename = "$unflatten=creator")]
     pub creator: String,
-    #[serde(rename = "$unflatten=description")]
     pub description: String
 }
 
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GraphGexf {
+    #[serde(rename="@defaultedgetype")]
     defaultedgetype: String,
     pub attributes: Vec<AttributesGexf>,
     pub nodes: NodesGexf,
@@ -33,6 +35,7 @@ pub struct GraphGexf {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AttributesGexf {
+    #[serde(rename="@class")]
     pub class: String,
     #[serde(rename="attribute", default)]
     pub attributes: Vec<AttributeGexf>
@@ -40,9 +43,11 @@ pub struct AttributesGexf {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AttributeGexf {
+    #[serde(rename="@id")]
     pub id: String,
+    #[serde(rename="@title")]
     pub title: String,
-    #[serde(rename="type")]
+    #[serde(rename="@type")]
     pub attr_type: String
 }
 
@@ -54,7 +59,9 @@ pub struct NodesGexf {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct NodeGexf {
+    #[serde(rename="@id")]
     pub id: String,
+    #[serde(rename="@label")]
     pub label: String,
     pub attvalues: AttValuesGexf
 }
@@ -67,8 +74,9 @@ pub struct AttValuesGexf {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AttValueGexf {
-    #[serde(alias="for", rename(serialize="for"))]
+    #[serde(rename="@for")]
     pub attr: String,
+    #[serde(rename="@value")]
     pub value: String
 
 }
@@ -82,7 +90,9 @@ pub struct EdgesGexf {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EdgeGexf {
-    pub source: String, 
+    #[serde(rename="@source")]
+    pub source: String,
+    #[serde(rename="@target")]
     pub target: String,
     pub attvalues: AttValuesGexf
 }
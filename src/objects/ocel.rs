@@ -2,15 +2,27 @@ pub mod importer;
 pub mod exporter;
 pub mod validator;
 pub mod builder;
+pub mod format;
+pub mod dataframe;
+pub mod attributes;
+pub mod interner;
+pub mod index;
+pub mod merge;
+pub(crate) mod variants;
 
 use bimap::BiMap;
-use serde::{Serialize, Deserialize, Deserializer};
+use serde::{Serialize, Deserialize};
 use nohash_hasher::{IntMap, IntSet};
 use serde_json::Value;
-use chrono::{DateTime, Utc, TimeZone};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
 use indexmap::IndexMap;
 use ahash::{AHashMap, AHashSet, RandomState};
 use std::cmp::Ordering;
+use crate::Error;
+use self::attributes::AttributeSchema;
+use self::interner::{ActivityId, Interner, ObjectTypeId};
+use self::index::{IndexCell, OcelIndex};
+use self::importer::{ImportOptions, ImportReport};
 
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -32,37 +44,92 @@ pub struct OcelObjectSerde{
     #[serde(alias = "ocel:type", rename(serialize = "ocel:type"))]
     pub obj_type: String,
     #[serde(alias = "ocel:ovmap", rename(serialize = "ocel:ovmap"))]
-    pub ovmap: AHashMap<String, Value>
+    pub ovmap: AHashMap<String, Value>,
+    /// Links to other objects, an extension of OCEL 1.0.
+    #[serde(alias = "ocel:o2o", rename(serialize = "ocel:o2o"), default, skip_serializing_if = "Vec::is_empty")]
+    pub o2o: Vec<OcelRelationshipSerde>
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct OcelEventSerde {
     #[serde(alias = "ocel:activity", rename(serialize = "ocel:activity"))]
     pub activity: String,
-    #[serde(alias = "ocel:timestamp", rename(serialize = "ocel:timestamp"), deserialize_with="timezone_default_utc")]
-    pub timestamp: DateTime<Utc>,
+    /// The timestamp as written, it is parsed with the import options of the log.
+    #[serde(alias = "ocel:timestamp", rename(serialize = "ocel:timestamp"))]
+    pub timestamp: String,
     #[serde(alias = "ocel:omap", rename(serialize = "ocel:omap"))]
     pub omap: AHashSet<String>,
     #[serde(alias = "ocel:vmap", rename(serialize = "ocel:vmap"))]
     pub vmap: AHashMap<String, Value>,
+    /// Qualified links to objects of the omap, an extension of OCEL 1.0.
+    #[serde(alias = "ocel:qualifiers", rename(serialize = "ocel:qualifiers"), default, skip_serializing_if = "Vec::is_empty")]
+    pub qualifiers: Vec<OcelRelationshipSerde>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OcelRelationshipSerde {
+    #[serde(alias = "ocel:oid", rename(serialize = "ocel:oid"))]
+    pub object_id: String,
+    #[serde(alias = "ocel:qualifier", rename(serialize = "ocel:qualifier"), default)]
+    pub qualifier: String
+}
+
+/// Formats of timestamps without an offset that are read without a custom format.
+pub(crate) const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// A timestamp in UTC and the offset it was written with, if it had one.
+pub(crate) type ParsedTimestamp = (DateTime<Utc>, Option<FixedOffset>);
+
+/// Reads timestamps with the import options and counts the values that were coerced.
+pub(crate) struct TimestampParser<'a> {
+    options: &'a ImportOptions,
+    pub(crate) report: ImportReport
 }
 
-fn timezone_default_utc<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
-    let s_input: Option<String> = Deserialize::deserialize(d)?;
-    let mut s: String = s_input.unwrap();
+impl<'a> TimestampParser<'a> {
+    pub(crate) fn new(options: &'a ImportOptions) -> Self {
+        TimestampParser { options, report: ImportReport::default() }
+    }
 
-    match DateTime::parse_from_rfc3339(s.as_str()) {
-        Ok(dt) => {Ok(DateTime::<Utc>::from(dt))},
-        Err(_e) => {
-            s.push('Z');
-            match DateTime::parse_from_rfc3339(s.as_str()) {
-                Ok(dt) => {Ok(DateTime::<Utc>::from(dt))},
-                Err(_e) => {Ok(DateTime::<Utc>::from(Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap()))}
+    /// RFC 3339 first, then the custom formats and the naive formats. Timestamps without an offset
+    /// are read in the default offset, the ones that do not parse become the Unix epoch unless the parser is strict.
+    pub(crate) fn parse(&mut self, s: &str) -> Result<ParsedTimestamp, Error> {
+        match self.read(s) {
+            Some((parsed, naive)) => {
+                if naive {
+                    self.report.naive_timestamps += 1;
+                }
+                Ok(parsed)
+            },
+            None if self.options.strict_timestamps => Err(Error::Parse(format!("Could not parse the timestamp {}.", s))),
+            None => {
+                self.report.invalid_timestamps += 1;
+                Ok((DateTime::<Utc>::default(), None))
             }
         }
     }
 
+    /// Whether `parse` reads the timestamp without falling back to the Unix epoch.
+    pub(crate) fn parses(&self, s: &str) -> bool {
+        self.read(s).is_some()
+    }
 
+    /// The timestamp and whether it was read in the default offset.
+    fn read(&self, s: &str) -> Option<(ParsedTimestamp, bool)> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return Some(((dt.with_timezone(&Utc), Some(*dt.offset())), false));
+        }
+        for format in &self.options.timestamp_formats {
+            if let Ok(dt) = DateTime::parse_from_str(s, format) {
+                return Some(((dt.with_timezone(&Utc), Some(*dt.offset())), false));
+            }
+        }
+        let naive = self.options.timestamp_formats.iter().map(|format| format.as_str())
+                                                  .chain(NAIVE_FORMATS)
+                                                  .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok());
+        let dt = naive.and_then(|naive| self.options.default_offset.from_local_datetime(&naive).single())?;
+        Some(((dt.with_timezone(&Utc), Some(self.options.default_offset)), true))
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -70,27 +137,234 @@ pub struct Ocel {
     pub global_log: AHashMap<String, Value>,
     pub global_event: AHashMap<String, Value>,
     pub global_object: AHashMap<String, Value>,
-    pub events: IntMap<usize, OcelEvent>,
-    pub objects: IntMap<usize, OcelObject>,
+    /// Read with `events` and edited with `events_mut` or the builder, so the index does not go stale.
+    pub(crate) events: IntMap<usize, OcelEvent>,
+    /// Read with `objects` and edited with `objects_mut` or the builder.
+    pub(crate) objects: IntMap<usize, OcelObject>,
     pub object_map: BiMap<String, usize>,
     pub event_map: BiMap<String, usize>,
-    pub activities: Vec<String>
+    /// Names of the activity ids of the events.
+    pub activity_table: Interner,
+    /// Names of the object type ids of the objects.
+    pub object_type_table: Interner,
+    /// Types of the attributes, see `Ocel::infer_schema`.
+    pub schema: AttributeSchema,
+    /// Links between objects by their source object.
+    pub o2o: IntMap<usize, Vec<ObjectLink>>,
+    /// Built on first use, see `Ocel::index`.
+    pub(crate) index: IndexCell
 }
 
 
+impl Ocel {
+    /// Renumber the events in time order, events with the same timestamp keep their previous order.
+    /// `event_map` follows the new ids and the events of every object are sorted by them.
+    /// The importers do this, it is only needed after editing `events` by hand.
+    pub fn reindex_chronologically(&mut self) {
+        let events = &self.events;
+        self.event_map.retain(|_, eid| events.contains_key(eid));
+
+        // pairs of old and new id, sorted by the old id
+        let mut renumbering: Vec<(usize, usize)> = self.events.keys().map(|eid| (*eid, 0)).collect();
+        renumbering.sort_unstable_by_key(|(eid, _)| (self.events[eid].timestamp, *eid));
+        for (new_id, ids) in renumbering.iter_mut().enumerate() {
+            ids.1 = new_id;
+        }
+        renumbering.sort_unstable();
+        let position = |renumbering: &[(usize, usize)], old_id: usize| renumbering.binary_search_by_key(&old_id, |(old, _)| *old).ok();
+
+        // events and their names are moved along the cycles of the renumbering, logs can be too large to copy
+        let mut moved = vec![false; renumbering.len()];
+        for start in 0..renumbering.len() {
+            if moved[start] {
+                continue;
+            }
+            let mut pos = start;
+            let old_id = renumbering[start].0;
+            // no other event takes the id of an event that keeps it
+            if renumbering[start].1 == old_id {
+                moved[start] = true;
+                continue;
+            }
+            let mut carried = self.events.remove(&old_id).map(|ev| (ev, self.event_map.remove_by_right(&old_id).map(|(name, _)| name)));
+            while let Some((event, name)) = carried {
+                moved[pos] = true;
+                let target = renumbering[pos].1;
+                let displaced_name = self.event_map.remove_by_right(&target).map(|(name, _)| name);
+                if let Some(name) = name {
+                    self.event_map.insert(name, target);
+                }
+                carried = self.events.insert(target, event).map(|ev| (ev, displaced_name));
+                if carried.is_some() {
+                    pos = position(&renumbering, target).expect("only old ids hold an event that was not moved");
+                }
+            }
+        }
+
+        let unchanged = renumbering.iter().all(|(old_id, new_id)| old_id == new_id);
+        for obj in self.objects.values_mut() {
+            obj.events.retain(|old_id| position(&renumbering, *old_id).is_some());
+            if !unchanged {
+                for eid in obj.events.iter_mut() {
+                    *eid = renumbering[position(&renumbering, *eid).expect("removed above")].1;
+                }
+            }
+            obj.events.sort_unstable();
+            obj.events.dedup();
+        }
+        self.invalidate_index();
+    }
+
+    /// Indexes of the log, they are built on first use and dropped by the builder, `events_mut`,
+    /// `objects_mut` and `clone`.
+    pub fn index(&self) -> &OcelIndex {
+        self.index.0.get_or_init(|| OcelIndex::new(self))
+    }
+
+    /// Position of an event in the events of an object, scanned for if the index is out of date.
+    pub fn lifecycle_position(&self, eid: usize, oid: usize) -> Option<usize> {
+        let obj = self.objects.get(&oid)?;
+        self.index().lifecycle_position(eid, oid)
+                    .filter(|pos| obj.events.get(*pos) == Some(&eid))
+                    .or_else(|| obj.events.iter().position(|eid2| *eid2 == eid))
+    }
+
+    pub fn events(&self) -> &IntMap<usize, OcelEvent> {
+        &self.events
+    }
+
+    pub fn objects(&self) -> &IntMap<usize, OcelObject> {
+        &self.objects
+    }
+
+    /// Events for editing by hand, the indexes are rebuilt on their next use.
+    pub fn events_mut(&mut self) -> &mut IntMap<usize, OcelEvent> {
+        self.invalidate_index();
+        &mut self.events
+    }
+
+    /// Objects for editing by hand, the indexes are rebuilt on their next use.
+    pub fn objects_mut(&mut self) -> &mut IntMap<usize, OcelObject> {
+        self.invalidate_index();
+        &mut self.objects
+    }
+
+    pub fn invalidate_index(&mut self) {
+        self.index = IndexCell::default();
+    }
+
+    /// Activities that have events in the order of their ids, which is the order of their first appearance in an imported log.
+    pub fn activities(&self) -> Vec<&str> {
+        self.index().activities().iter().map(|act| self.activity_table.name(*act)).collect()
+    }
+
+    /// Events of an activity sorted by id.
+    pub fn events_of_activity(&self, activity: &str) -> &[usize] {
+        self.activity_id(activity).map(|act| self.index().events_of_activity(act)).unwrap_or_default()
+    }
+
+    /// Objects of a type sorted by id.
+    pub fn objects_of_type(&self, obj_type: &str) -> &[usize] {
+        self.object_type_id(obj_type).map(|ot| self.index().objects_of_type(ot)).unwrap_or_default()
+    }
+
+    pub fn activity(&self, event: &OcelEvent) -> &str {
+        self.activity_table.name(event.activity)
+    }
+
+    pub fn object_type(&self, object: &OcelObject) -> &str {
+        self.object_type_table.name(object.obj_type)
+    }
+
+    pub fn activity_id(&self, activity: &str) -> Option<ActivityId> {
+        self.activity_table.id(activity)
+    }
+
+    pub fn object_type_id(&self, obj_type: &str) -> Option<ObjectTypeId> {
+        self.object_type_table.id(obj_type)
+    }
+
+    pub fn intern_activity(&mut self, activity: &str) -> ActivityId {
+        self.activity_table.intern(activity)
+    }
+
+    pub fn intern_object_type(&mut self, obj_type: &str) -> ObjectTypeId {
+        self.object_type_table.intern(obj_type)
+    }
+
+    /// Link two objects, links that are already part of the log are not added twice.
+    pub(crate) fn link_object(&mut self, source: usize, target: usize, qualifier: &str) {
+        let links = self.o2o.entry(source).or_default();
+        if !links.iter().any(|link| link.target == target && link.qualifier == qualifier) {
+            links.push(ObjectLink { target, qualifier: qualifier.to_owned() });
+        }
+    }
+}
+
+/// A qualified link from one object to another, the qualifier may be empty.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectLink {
+    pub target: usize,
+    pub qualifier: String
+}
+
+/// Timed values of object attributes per key, sorted by time.
+pub type AttributeHistory = AHashMap<String, Vec<(DateTime<Utc>, Value)>>;
+
 #[derive(Debug, Clone)]
 pub struct OcelObject{
-    pub obj_type: String,
+    /// Id of the object type, see `Ocel::object_type`.
+    pub obj_type: ObjectTypeId,
+    /// Initial values of the attributes.
     pub ovmap: AHashMap<String, Value>,
-    pub events: Vec<usize>
+    pub events: Vec<usize>,
+    /// Changes of the attributes after their initial value.
+    pub history: AttributeHistory
+}
+
+impl OcelObject {
+    /// Value of an attribute at a point in time, the initial value if it has not changed before.
+    pub fn value_at(&self, key: &str, timestamp: &DateTime<Utc>) -> Option<&Value> {
+        let changes = self.history.get(key).map(|changes| changes.as_slice()).unwrap_or_default();
+        match changes.partition_point(|(time, _)| time <= timestamp) {
+            0 => self.ovmap.get(key),
+            n => Some(&changes[n - 1].1)
+        }
+    }
 }
 
 #[derive(Debug, Eq, Clone)]
 pub struct OcelEvent {
-    pub activity: String,
+    /// Id of the activity, see `Ocel::activity`.
+    pub activity: ActivityId,
     pub timestamp: DateTime<Utc>,
     pub vmap: AHashMap<String, Value>,
-    pub omap: IntSet<usize>
+    pub omap: IntSet<usize>,
+    /// Offset the timestamp was written with in the imported log, `None` if it is not known.
+    pub offset: Option<FixedOffset>,
+    /// Qualifiers of the links to objects of `omap`, objects linked without a qualifier have no entry.
+    pub qualifiers: IntMap<usize, Vec<String>>
+}
+
+impl OcelEvent {
+    /// The timestamp in its original offset if it is known and should be kept, in UTC otherwise.
+    pub(crate) fn export_timestamp(&self, keep_offset: bool) -> DateTime<FixedOffset> {
+        match self.offset {
+            Some(offset) if keep_offset => self.timestamp.with_timezone(&offset),
+            _ => self.timestamp.fixed_offset()
+        }
+    }
+
+    /// Qualify the link to an object, an empty qualifier only links it.
+    pub(crate) fn qualify(&mut self, oid: usize, qualifier: &str) {
+        if qualifier.is_empty() {
+            return;
+        }
+        let qualifiers = self.qualifiers.entry(oid).or_default();
+        if !qualifiers.iter().any(|q| q == qualifier) {
+            qualifiers.push(qualifier.to_owned());
+        }
+    }
 }
 
 
@@ -0,0 +1,275 @@
+use ahash::{AHashMap, AHashSet};
+use chrono::{DateTime, SecondsFormat, Utc};
+use itertools::Itertools;
+use nohash_hasher::IntMap;
+use serde_json::{Value, json};
+
+use super::{Ocel, OcelEvent};
+use super::interner::{ActivityId, Interner, ObjectTypeId};
+
+/// An attribute of an event or object with its type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Timestamp(DateTime<Utc>),
+    List(Vec<AttributeValue>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AttributeType {
+    Int,
+    Float,
+    Bool,
+    String,
+    Timestamp,
+    List
+}
+
+impl AttributeValue {
+    /// Strings holding an RFC 3339 timestamp are timestamps, nested objects are kept as their json text.
+    /// `null` is a missing value and has no type.
+    pub fn from_json(value: &Value) -> Option<AttributeValue> {
+        match value {
+            Value::Null => None,
+            Value::Bool(b) => Some(AttributeValue::Bool(*b)),
+            Value::Number(n) => Some(n.as_i64().map(AttributeValue::Int).unwrap_or_else(|| AttributeValue::Float(n.as_f64().unwrap_or(f64::NAN)))),
+            Value::String(s) => Some(match DateTime::parse_from_rfc3339(s) {
+                Ok(dt) => AttributeValue::Timestamp(dt.with_timezone(&Utc)),
+                Err(_) => AttributeValue::String(s.to_owned())
+            }),
+            Value::Array(values) => Some(AttributeValue::List(values.iter().filter_map(AttributeValue::from_json).collect())),
+            Value::Object(_) => Some(AttributeValue::String(value.to_string()))
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            AttributeValue::Int(i) => json!(i),
+            AttributeValue::Float(f) => json!(f),
+            AttributeValue::Bool(b) => json!(b),
+            AttributeValue::String(s) => json!(s),
+            AttributeValue::Timestamp(dt) => json!(dt.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            AttributeValue::List(values) => Value::Array(values.iter().map(|v| v.to_json()).collect())
+        }
+    }
+
+    pub fn attribute_type(&self) -> AttributeType {
+        match self {
+            AttributeValue::Int(_) => AttributeType::Int,
+            AttributeValue::Float(_) => AttributeType::Float,
+            AttributeValue::Bool(_) => AttributeType::Bool,
+            AttributeValue::String(_) => AttributeType::String,
+            AttributeValue::Timestamp(_) => AttributeType::Timestamp,
+            AttributeValue::List(_) => AttributeType::List
+        }
+    }
+
+    /// Ints widen to floats, strings are read as numbers or booleans and every value can be written as a string,
+    /// other conversions fail.
+    pub fn convert(self, to: AttributeType) -> Option<AttributeValue> {
+        match (self, to) {
+            (value, to) if value.attribute_type() == to => Some(value),
+            (AttributeValue::Int(i), AttributeType::Float) => Some(AttributeValue::Float(i as f64)),
+            (AttributeValue::String(s), AttributeType::Int) => s.trim().parse().ok().map(AttributeValue::Int),
+            (AttributeValue::String(s), AttributeType::Float) => s.trim().parse().ok().map(AttributeValue::Float),
+            (AttributeValue::String(s), AttributeType::Bool) => s.trim().parse().ok().map(AttributeValue::Bool),
+            (AttributeValue::Timestamp(dt), AttributeType::String) => Some(AttributeValue::String(dt.to_rfc3339_opts(SecondsFormat::AutoSi, true))),
+            (value, AttributeType::String) => Some(AttributeValue::String(value.to_json().to_string())),
+            _ => None
+        }
+    }
+
+    /// Numeric value of ints and floats.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AttributeValue::Int(i) => Some(*i as f64),
+            AttributeValue::Float(f) => Some(*f),
+            _ => None
+        }
+    }
+}
+
+impl AttributeType {
+    /// Type that holds values of both types, ints and floats become floats and anything else strings.
+    fn unify(self, other: AttributeType) -> AttributeType {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (AttributeType::Int, AttributeType::Float) | (AttributeType::Float, AttributeType::Int) => AttributeType::Float,
+            _ => AttributeType::String
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, AttributeType::Int | AttributeType::Float)
+    }
+}
+
+/// Types of the `vmap` attributes per activity and of the `ovmap` attributes per object type,
+/// keyed by the ids of the interners of the log.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttributeSchema {
+    pub activities: IntMap<ActivityId, AHashMap<String, AttributeType>>,
+    pub object_types: IntMap<ObjectTypeId, AHashMap<String, AttributeType>>
+}
+
+impl AttributeSchema {
+    /// Widen the types of the activity so that they also hold the attributes of an added event.
+    pub(crate) fn observe_event(&mut self, activity: ActivityId, vmap: &AHashMap<String, Value>) {
+        observe(self.activities.entry(activity).or_default(), vmap);
+    }
+
+    pub(crate) fn observe_object(&mut self, obj_type: ObjectTypeId, ovmap: &AHashMap<String, Value>) {
+        observe(self.object_types.entry(obj_type).or_default(), ovmap);
+    }
+}
+
+fn observe(types: &mut AHashMap<String, AttributeType>, attributes: &AHashMap<String, Value>) {
+    for (key, value) in attributes {
+        if let Some(found) = AttributeValue::from_json(value).map(|v| v.attribute_type()) {
+            types.entry(key.to_owned()).and_modify(|t| *t = t.unify(found)).or_insert(found);
+        }
+    }
+}
+
+/// An attribute whose values have types that cannot be converted into each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixedAttribute {
+    /// Activity or object type the attribute belongs to.
+    pub owner: String,
+    pub key: String,
+    /// Types found in the log, in the order of `AttributeType`.
+    pub found: Vec<AttributeType>,
+    /// Type the values are read as.
+    pub inferred: AttributeType
+}
+
+/// Attributes whose values were not all of one type when the schema was inferred.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub mixed_activity_attributes: Vec<MixedAttribute>,
+    pub mixed_object_attributes: Vec<MixedAttribute>
+}
+
+impl ConversionReport {
+    pub fn is_empty(&self) -> bool {
+        self.mixed_activity_attributes.is_empty() && self.mixed_object_attributes.is_empty()
+    }
+}
+
+impl Ocel {
+    /// Infer the schema from all attribute values and store it in `schema`. The importers do this,
+    /// the builder widens the schema with the attributes it adds.
+    pub fn infer_schema(&mut self) -> ConversionReport {
+        let mut found: AHashMap<(u32, &str), AHashSet<AttributeType>> = AHashMap::new();
+        for ev in self.events.values() {
+            for (key, value) in &ev.vmap {
+                if let Some(v) = AttributeValue::from_json(value) {
+                    found.entry((ev.activity, key.as_str())).or_default().insert(v.attribute_type());
+                }
+            }
+        }
+        let (activities, mixed_activity_attributes) = schema_of(found, &self.activity_table);
+
+        let mut found: AHashMap<(u32, &str), AHashSet<AttributeType>> = AHashMap::new();
+        for obj in self.objects.values() {
+            let changes = obj.history.iter().flat_map(|(key, changes)| changes.iter().map(move |(_, value)| (key, value)));
+            for (key, value) in obj.ovmap.iter().chain(changes) {
+                if let Some(v) = AttributeValue::from_json(value) {
+                    found.entry((obj.obj_type, key.as_str())).or_default().insert(v.attribute_type());
+                }
+            }
+        }
+        let (object_types, mixed_object_attributes) = schema_of(found, &self.object_type_table);
+
+        self.schema = AttributeSchema { activities, object_types };
+        ConversionReport { mixed_activity_attributes, mixed_object_attributes }
+    }
+
+    /// Attribute of an event in the type of its activity, `None` if it is missing or `null`.
+    pub fn event_attribute(&self, eid: &usize, key: &str) -> Option<AttributeValue> {
+        self.typed_event_value(self.events.get(eid)?, key)
+    }
+
+    pub(crate) fn typed_event_value(&self, ev: &OcelEvent, key: &str) -> Option<AttributeValue> {
+        let value = AttributeValue::from_json(ev.vmap.get(key)?)?;
+        match self.schema.activities.get(&ev.activity).and_then(|types| types.get(key)) {
+            Some(t) => value.convert(*t),
+            None => Some(value)
+        }
+    }
+
+    /// Initial value of an object attribute in the type of its object type, `None` if it is missing or `null`.
+    pub fn object_attribute(&self, oid: &usize, key: &str) -> Option<AttributeValue> {
+        let obj = self.objects.get(oid)?;
+        self.typed_object_value(obj.obj_type, key, obj.ovmap.get(key)?)
+    }
+
+    /// Value of an object attribute at a point in time, see `OcelObject::value_at`.
+    pub fn object_attribute_at(&self, oid: &usize, key: &str, timestamp: &DateTime<Utc>) -> Option<AttributeValue> {
+        let obj = self.objects.get(oid)?;
+        self.typed_object_value(obj.obj_type, key, obj.value_at(key, timestamp)?)
+    }
+
+    fn typed_object_value(&self, obj_type: ObjectTypeId, key: &str, value: &Value) -> Option<AttributeValue> {
+        let value = AttributeValue::from_json(value)?;
+        match self.schema.object_types.get(&obj_type).and_then(|types| types.get(key)) {
+            Some(t) => value.convert(*t),
+            None => Some(value)
+        }
+    }
+}
+
+/// Schema of the types found per owner and attribute, attributes with more than one type that is not
+/// a mix of ints and floats are reported by the name of their owner, sorted by owner and key.
+fn schema_of(found: AHashMap<(u32, &str), AHashSet<AttributeType>>, owners: &Interner) -> (IntMap<u32, AHashMap<String, AttributeType>>, Vec<MixedAttribute>) {
+    let mut schema: IntMap<u32, AHashMap<String, AttributeType>> = IntMap::default();
+    let mut mixed: Vec<MixedAttribute> = vec![];
+    for ((owner, key), types) in found {
+        let inferred = types.iter().copied().reduce(AttributeType::unify).expect("only found types are recorded");
+        if types.len() > 1 && !types.iter().all(|t| t.is_numeric()) {
+            mixed.push(MixedAttribute { owner: owners.name(owner).to_owned(), key: key.to_owned(), found: types.into_iter().sorted().collect(), inferred });
+        }
+        schema.entry(owner).or_default().insert(key.to_owned(), inferred);
+    }
+    mixed.sort_by(|a, b| (&a.owner, &a.key).cmp(&(&b.owner, &b.key)));
+    (schema, mixed)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ocel::format::OcelFormat;
+    use crate::objects::ocel::importer::{import_ocel_from_reader_with_options, ImportOptions};
+
+    #[test]
+    fn test_schema_inference() {
+        let input = r#"{"ocel:global-log": {}, "ocel:global-event": {}, "ocel:global-object": {},
+            "ocel:objects": {"o1": {"ocel:type": "order", "ocel:ovmap": {"price": 10, "note": "urgent"}},
+                             "o2": {"ocel:type": "order", "ocel:ovmap": {"price": 10.5, "note": 3}}},
+            "ocel:events": {"e1": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T10:00:00Z", "ocel:omap": ["o1"], "ocel:vmap": {"due": "2022-01-02T10:00:00Z", "tags": ["x"], "paid": null}},
+                            "e2": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T11:00:00Z", "ocel:omap": ["o2"], "ocel:vmap": {"paid": true}}}}"#;
+        let (log, report) = import_ocel_from_reader_with_options(input.as_bytes(), OcelFormat::JsonOcel, &ImportOptions::default()).unwrap();
+        let report = report.attributes;
+
+        assert_eq!(log.schema.object_types[&log.object_type_id("order").unwrap()]["price"], AttributeType::Float);
+        assert_eq!(log.schema.activities[&log.activity_id("a").unwrap()]["due"], AttributeType::Timestamp);
+        assert_eq!(log.schema.activities[&log.activity_id("a").unwrap()]["tags"], AttributeType::List);
+        assert_eq!(log.schema.activities[&log.activity_id("a").unwrap()]["paid"], AttributeType::Bool);
+        assert!(report.mixed_activity_attributes.is_empty());
+        assert_eq!(report.mixed_object_attributes, vec![MixedAttribute { owner: "order".to_string(), key: "note".to_string(),
+                                                                         found: vec![AttributeType::Int, AttributeType::String], inferred: AttributeType::String }]);
+
+        let o1 = log.object_map.get_by_left("o1").unwrap();
+        let o2 = log.object_map.get_by_left("o2").unwrap();
+        let e1 = log.event_map.get_by_left("e1").unwrap();
+        assert_eq!(log.object_attribute(o1, "price"), Some(AttributeValue::Float(10.0)));
+        assert_eq!(log.object_attribute(o2, "note"), Some(AttributeValue::String("3".to_string())));
+        assert_eq!(log.event_attribute(e1, "paid"), None);
+        assert_eq!(log.event_attribute(e1, "tags"), Some(AttributeValue::List(vec![AttributeValue::String("x".to_string())])));
+        assert_eq!(log.event_attribute(e1, "due").unwrap().to_json(), json!("2022-01-02T10:00:00Z"));
+    }
+}
@@ -1,69 +1,223 @@
+use crate::Error;
+
 use ahash::AHashMap;
 use chrono::Utc;
 use chrono::DateTime;
-use nohash_hasher::IntSet;
+use nohash_hasher::{IntMap, IntSet};
 use serde_json::Value;
 use crate::objects::ocel::Ocel;
 
 use super::OcelEvent;
 use super::OcelObject;
+use super::AttributeHistory;
 
-struct BuilderObject<'a>(&'a str, &'a str);
+/// Object type and object id of an object referenced by an event.
+pub struct BuilderObject<'a>(pub &'a str, pub &'a str);
 
 
-trait LogBuilder {
-    fn add_event(&mut self, name: &str, time: DateTime<Utc>, activity: &str, obj: Vec<BuilderObject>, properties: Option<AHashMap<String, Value>>);
+/// Edit a log while keeping `event_map`, `object_map` and the
+/// time-sorted `events` lists of the objects consistent with the events.
+pub trait LogBuilder {
+    /// Add an object that is not referenced by any event yet.
+    fn add_object(&mut self, name: &str, obj_type: &str, properties: Option<AHashMap<String, Value>>) -> Result<usize, Error>;
+    /// Add an event, objects that are not part of the log yet are created without attributes.
+    fn add_event(&mut self, name: &str, time: DateTime<Utc>, activity: &str, obj: Vec<BuilderObject>, properties: Option<AHashMap<String, Value>>) -> Result<usize, Error>;
+    /// Add objects to the omap of an existing event.
+    fn attach_objects(&mut self, event_name: &str, obj: Vec<BuilderObject>) -> Result<(), Error>;
+    /// Qualify the link of an event to an object of its omap.
+    fn qualify_object(&mut self, event_name: &str, object_name: &str, qualifier: &str) -> Result<(), Error>;
+    /// Link an object to another one, independent of the events.
+    fn link_objects(&mut self, source_name: &str, target_name: &str, qualifier: &str) -> Result<(), Error>;
+    fn remove_event(&mut self, name: &str) -> Result<(), Error>;
+    /// Remove an object and all references to it, the events it was part of are kept.
+    fn remove_object(&mut self, name: &str) -> Result<(), Error>;
+    /// Check that all indexes of the log agree with each other and renumber the events in time order,
+    /// ids handed out while editing are not kept.
+    fn build(self) -> Result<Ocel, Error>;
 }
 
 
 impl LogBuilder for Ocel {
-    fn add_event(&mut self, event_name: &str, time: DateTime<Utc>, activity: &str, objs: Vec<BuilderObject>, properties: Option<AHashMap<String, Value>>) {
+    fn add_object(&mut self, object_name: &str, obj_type: &str, properties: Option<AHashMap<String, Value>>) -> Result<usize, Error> {
+        if self.object_map.contains_left(object_name) {
+            return Err(Error::ReferentialIntegrity(format!("Object id {} is already part of the log.", object_name)));
+        }
+        self.invalidate_index();
+        let new_id = free_id(self.objects.len(), |id| self.objects.contains_key(id));
+        let ovmap = properties.unwrap_or_default();
+        let obj_type = self.intern_object_type(obj_type);
+        self.schema.observe_object(obj_type, &ovmap);
+        self.objects.insert(new_id, OcelObject { obj_type, ovmap, events: vec![], history: AttributeHistory::new() });
+        self.object_map.insert(object_name.to_string(), new_id);
+        Ok(new_id)
+    }
+
+    fn add_event(&mut self, event_name: &str, time: DateTime<Utc>, activity: &str, objs: Vec<BuilderObject>, properties: Option<AHashMap<String, Value>>) -> Result<usize, Error> {
+        if self.event_map.contains_left(event_name) {
+            return Err(Error::ReferentialIntegrity(format!("Event id {} is already part of the log.", event_name)));
+        }
+        check_object_types(self, &objs)?;
+
+        self.invalidate_index();
+        // Generate event details
+        let new_ev_id = free_id(self.events.len(), |id| self.events.contains_key(id));
+        let ev_properties = properties.unwrap_or_default();
+
+        let activity = self.intern_activity(activity);
+        self.schema.observe_event(activity, &ev_properties);
+        let new_event = OcelEvent {activity, timestamp: time, omap: IntSet::default(), vmap: ev_properties, offset: None, qualifiers: IntMap::default()};
+        self.event_map.insert(event_name.to_string(), new_ev_id);
+        self.events.insert(new_ev_id, new_event);
+
+        self.attach_objects(event_name, objs)?;
+        Ok(new_ev_id)
+    }
+
+    fn attach_objects(&mut self, event_name: &str, objs: Vec<BuilderObject>) -> Result<(), Error> {
+        let ev_id = *self.event_map.get_by_left(event_name).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is not part of the log.", event_name)))?;
+        check_object_types(self, &objs)?;
+        self.invalidate_index();
+
         // create and gather objects
-        let omap_ids: IntSet<usize> = objs.iter().map(|bo| {
-            match self.object_map.get_by_left(bo.1) {
+        for bo in objs {
+            let oid = match self.object_map.get_by_left(bo.1) {
                 Some(id) => {id.to_owned()},
-                None => {
-                    let new_id = self.objects.len();
-                    let new_object: OcelObject = OcelObject { obj_type: bo.0.to_string(), ovmap: AHashMap::new(), events: vec![] };
-                    self.objects.insert(new_id, new_object);
-                    self.object_map.insert(bo.1.to_string(), new_id);
-                    new_id
+                None => self.add_object(bo.1, bo.0, None)?
+            };
+
+            let event = self.events.get_mut(&ev_id).expect("event map and events are kept together");
+            if event.omap.insert(oid) {
+                let key = (event.timestamp, ev_id);
+                let obj_events = &mut self.objects.get_mut(&oid).expect("object map and objects are kept together").events;
+                let pos = obj_events.partition_point(|eid| (self.events[eid].timestamp, *eid) < key);
+                obj_events.insert(pos, ev_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn qualify_object(&mut self, event_name: &str, object_name: &str, qualifier: &str) -> Result<(), Error> {
+        let ev_id = *self.event_map.get_by_left(event_name).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is not part of the log.", event_name)))?;
+        let oid = *self.object_map.get_by_left(object_name).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not part of the log.", object_name)))?;
+        let event = self.events.get_mut(&ev_id).expect("event map and events are kept together");
+        if !event.omap.contains(&oid) {
+            return Err(Error::ReferentialIntegrity(format!("Event {} does not reference object {}.", event_name, object_name)));
+        }
+        event.qualify(oid, qualifier);
+        Ok(())
+    }
+
+    fn link_objects(&mut self, source_name: &str, target_name: &str, qualifier: &str) -> Result<(), Error> {
+        let source = *self.object_map.get_by_left(source_name).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not part of the log.", source_name)))?;
+        let target = *self.object_map.get_by_left(target_name).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not part of the log.", target_name)))?;
+        self.link_object(source, target, qualifier);
+        Ok(())
+    }
+
+    fn remove_event(&mut self, name: &str) -> Result<(), Error> {
+        let (_, ev_id) = self.event_map.remove_by_left(name).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is not part of the log.", name)))?;
+        let event = self.events.remove(&ev_id).expect("event map and events are kept together");
+        self.invalidate_index();
+        for oid in &event.omap {
+            self.objects.get_mut(oid).expect("omap only references existing objects").events.retain(|eid| *eid != ev_id);
+        }
+        Ok(())
+    }
+
+    fn remove_object(&mut self, name: &str) -> Result<(), Error> {
+        let (_, oid) = self.object_map.remove_by_left(name).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is not part of the log.", name)))?;
+        let obj = self.objects.remove(&oid).expect("object map and objects are kept together");
+        self.invalidate_index();
+        for eid in &obj.events {
+            let event = self.events.get_mut(eid).expect("objects only reference existing events");
+            event.omap.remove(&oid);
+            event.qualifiers.remove(&oid);
+        }
+        self.o2o.remove(&oid);
+        for links in self.o2o.values_mut() {
+            links.retain(|link| link.target != oid);
+        }
+        self.o2o.retain(|_, links| !links.is_empty());
+        Ok(())
+    }
+
+    fn build(mut self) -> Result<Ocel, Error> {
+        if self.event_map.len() != self.events.len() || self.event_map.right_values().any(|eid| !self.events.contains_key(eid)) {
+            return Err(Error::ReferentialIntegrity("The event map does not match the events.".to_string()));
+        }
+        if self.object_map.len() != self.objects.len() || self.object_map.right_values().any(|oid| !self.objects.contains_key(oid)) {
+            return Err(Error::ReferentialIntegrity("The object map does not match the objects.".to_string()));
+        }
+
+        let mut object_events: AHashMap<usize, Vec<usize>> = AHashMap::new();
+        for (eid, event) in &self.events {
+            for oid in &event.omap {
+                if !self.objects.contains_key(oid) {
+                    return Err(Error::ReferentialIntegrity(format!("Event {} references an object that is not part of the log.", self.event_map.get_by_right(eid).expect("checked above"))));
                 }
+                object_events.entry(*oid).or_default().push(*eid);
+            }
+            if event.qualifiers.keys().any(|oid| !event.omap.contains(oid)) {
+                return Err(Error::ReferentialIntegrity(format!("Event {} qualifies a link to an object outside of its omap.", self.event_map.get_by_right(eid).expect("checked above"))));
+            }
+        }
+        for (oid, links) in &self.o2o {
+            if !self.objects.contains_key(oid) || links.iter().any(|link| !self.objects.contains_key(&link.target)) {
+                return Err(Error::ReferentialIntegrity("A link between objects references an object that is not part of the log.".to_string()));
             }
-        }).collect();
+        }
+        for (oid, obj) in &self.objects {
+            let name = self.object_map.get_by_right(oid).expect("checked above");
+            let mut expected = object_events.remove(oid).unwrap_or_default();
+            expected.sort_by_key(|eid| (self.events[eid].timestamp, *eid));
+            if obj.events != expected {
+                return Err(Error::ReferentialIntegrity(format!("The events of object {} do not match the events that reference it in time order.", name)));
+            }
+        }
 
-        // Generate event details
-        let new_ev_id = self.events.len();
-        
-        let ev_properties = match properties {
-            Some(prop) => prop,
-            None => AHashMap::default()
-        };
-
-        let new_event = OcelEvent {activity: activity.to_string(), timestamp: time, omap: omap_ids, vmap: ev_properties};
-        self.event_map.insert(event_name.to_string(), new_ev_id);
-        self.events.insert(new_ev_id, new_event);
+        self.reindex_chronologically();
+        Ok(self)
     }
 }
 
+/// Objects keep their type once they are part of the log.
+fn check_object_types(log: &Ocel, objs: &[BuilderObject]) -> Result<(), Error> {
+    for bo in objs {
+        if let Some(oid) = log.object_map.get_by_left(bo.1) {
+            let obj_type = log.object_type(&log.objects[oid]);
+            if obj_type != bo.0 {
+                return Err(Error::ReferentialIntegrity(format!("Object {} is of type {}, not {}.", bo.1, obj_type, bo.0)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lowest unused id that is not below `start`, the number of entries is free unless entries were removed.
+fn free_id(start: usize, taken: impl Fn(&usize) -> bool) -> usize {
+    (start..).find(|id| !taken(id)).expect("there is always an unused id")
+}
+
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use chrono::Duration;
+    use crate::objects::ocel::attributes::AttributeType;
 
     #[test]
     fn test_simple_event_add() {
         let mut log = Ocel::default();
 
-        log.add_event("e0", Utc::now(), "order items", vec![BuilderObject("order", "o1"), BuilderObject("item", "i1")], None);
+        log.add_event("e0", Utc::now(), "order items", vec![BuilderObject("order", "o1"), BuilderObject("item", "i1")], None).expect("the event is new");
 
         assert_eq!(log.events.len(), 1);
         assert_eq!(log.event_map.get_by_left("e0").expect("The event was not added to the event_map"), &0);
-        
+
         match log.events.get(&0) {
             Some(event) => {
-                assert_eq!(event.activity, "order items".to_string());
+                assert_eq!(log.activity(event), "order items");
                 assert_eq!(event.omap.len(), 2);
                 assert_eq!(event.vmap.len(), 0);
             },
@@ -73,13 +227,125 @@ mod tests {
         }
 
         // add another event but with already containing items
-        log.add_event("e1", Utc::now(), "receive items", vec![BuilderObject("order", "o1"), BuilderObject("item", "i1")], None);
+        log.add_event("e1", Utc::now(), "receive items", vec![BuilderObject("order", "o1"), BuilderObject("item", "i1")], None).expect("the event is new");
 
         assert_eq!(log.objects.len(), 2);
         assert_eq!(log.events.len(), 2);
         assert_eq!(log.object_map.len(), 2);
         assert_eq!(log.event_map.len(), 2);
+        assert_eq!(log.activities(), vec!["order items", "receive items"]);
+        assert_eq!(log.objects[&0].events, vec![0, 1]);
 
+        log.build().expect("the builder keeps the log consistent");
     }
 
+    #[test]
+    fn test_events_stay_sorted() {
+        let mut log = Ocel::default();
+        let start = Utc::now();
+        log.add_object("o1", "order", Some(AHashMap::from([("price".to_string(), Value::from(10))]))).unwrap();
+        log.add_event("e0", start + Duration::hours(2), "ship", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e1", start, "place", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e2", start + Duration::hours(1), "pay", vec![], None).unwrap();
+        log.attach_objects("e2", vec![BuilderObject("order", "o1"), BuilderObject("customer", "c1")]).unwrap();
+
+        let log = log.build().unwrap();
+        let o1 = &log.objects[log.object_map.get_by_left("o1").unwrap()];
+        assert_eq!(o1.events, vec![0, 1, 2]);
+        let names: Vec<&str> = o1.events.iter().map(|eid| log.event_map.get_by_right(eid).unwrap().as_str()).collect();
+        assert_eq!(names, vec!["e1", "e2", "e0"]);
+        assert_eq!(o1.ovmap["price"], Value::from(10));
+        assert_eq!(log.schema.object_types[&log.object_type_id("order").unwrap()]["price"], AttributeType::Int);
+    }
+
+    #[test]
+    fn test_reindex() {
+        let mut log = Ocel::default();
+        let start = Utc::now();
+        log.add_event("e0", start + Duration::hours(2), "ship", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e1", start, "place", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e2", start + Duration::hours(1), "pay", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e3", start, "place", vec![], None).unwrap();
+        log.remove_event("e2").unwrap();
+
+        log.reindex_chronologically();
+        let names: Vec<&str> = (0..3).map(|eid| log.event_map.get_by_right(&eid).unwrap().as_str()).collect();
+        assert_eq!(names, vec!["e1", "e3", "e0"]);
+        assert_eq!(log.objects[&0].events, vec![0, 2]);
+        assert_eq!(log.activity(&log.events[&2]), "ship");
+        log.build().unwrap();
+    }
+
+    #[test]
+    fn test_reindex_partly_ordered() {
+        let mut log = Ocel::default();
+        let start = Utc::now();
+        log.add_event("e0", start, "place", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e1", start + Duration::hours(2), "ship", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e2", start + Duration::hours(1), "pay", vec![BuilderObject("order", "o1")], None).unwrap();
+        log.add_event("e3", start + Duration::hours(3), "close", vec![], None).unwrap();
+
+        log.reindex_chronologically();
+        let names: Vec<&str> = (0..4).map(|eid| log.event_map.get_by_right(&eid).unwrap().as_str()).collect();
+        assert_eq!(names, vec!["e0", "e2", "e1", "e3"]);
+        assert_eq!(log.objects[&0].events, vec![0, 1, 2]);
+        assert_eq!(log.activity(&log.events[&3]), "close");
+        log.build().unwrap();
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut log = Ocel::default();
+        let start = Utc::now();
+        log.add_event("e0", start, "place", vec![BuilderObject("order", "o1"), BuilderObject("item", "i1")], None).unwrap();
+        log.add_event("e1", start + Duration::hours(1), "pick", vec![BuilderObject("item", "i1")], None).unwrap();
+        log.add_event("e2", start + Duration::hours(2), "ship", vec![BuilderObject("order", "o1"), BuilderObject("item", "i1")], None).unwrap();
+
+        log.remove_event("e1").unwrap();
+        assert_eq!(log.activities(), vec!["place", "ship"]);
+        assert_eq!(log.objects[log.object_map.get_by_left("i1").unwrap()].events, vec![0, 2]);
+
+        log.remove_object("o1").unwrap();
+        assert!(log.events.values().all(|ev| ev.omap.len() == 1));
+
+        // ids of removed events are not handed out again
+        assert_eq!(log.add_event("e3", start, "place", vec![], None).unwrap(), 3);
+        assert!(log.remove_event("e1").is_err());
+        log.build().unwrap();
+    }
+
+    #[test]
+    fn test_qualified_links() {
+        let mut log = Ocel::default();
+        log.add_event("e0", Utc::now(), "place", vec![BuilderObject("order", "o1"), BuilderObject("item", "i1"), BuilderObject("item", "i2")], None).unwrap();
+        log.qualify_object("e0", "o1", "order").unwrap();
+        log.qualify_object("e0", "i1", "item").unwrap();
+        log.link_objects("o1", "i1", "contains").unwrap();
+        log.link_objects("o1", "i2", "contains").unwrap();
+        log.link_objects("o1", "i2", "contains").unwrap();
+        assert!(log.qualify_object("e0", "o2", "order").is_err());
+
+        let (o1, i1) = (*log.object_map.get_by_left("o1").unwrap(), *log.object_map.get_by_left("i1").unwrap());
+        assert_eq!(log.events[&0].qualifiers[&o1], vec!["order"]);
+        assert_eq!(log.o2o[&o1].len(), 2);
+
+        log.remove_object("i1").unwrap();
+        assert!(!log.events[&0].qualifiers.contains_key(&i1));
+        assert_eq!(log.o2o[&o1].len(), 1);
+        log.build().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut log = Ocel::default();
+        log.add_event("e0", Utc::now(), "place", vec![BuilderObject("order", "o1")], None).unwrap();
+        assert!(log.add_event("e0", Utc::now(), "place", vec![], None).is_err());
+        assert!(log.add_object("o1", "order", None).is_err());
+        assert!(log.attach_objects("e0", vec![BuilderObject("item", "o1")]).is_err());
+        assert!(log.attach_objects("e1", vec![]).is_err());
+
+        // a log edited by hand is checked when it is built
+        log.objects_mut().get_mut(&0).unwrap().events.clear();
+        assert!(log.build().is_err());
+    }
 }
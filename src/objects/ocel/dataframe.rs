@@ -0,0 +1,336 @@
+use crate::Error;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use itertools::Itertools;
+use polars::prelude::{DataFrame, DataType, NamedFrom, Series, TimeUnit};
+use serde_json::Value;
+
+use super::{Ocel, ObjectLink, AttributeHistory, ParsedTimestamp, TimestampParser};
+use super::importer::{ImportOptions, ImportReport};
+use super::variants::{declare_event, declare_object, declared_event, fill_globals, relate_object, relate_objects};
+
+pub const EVENT_ID: &str = "ocel:eid";
+pub const ACTIVITY: &str = "ocel:activity";
+pub const TIMESTAMP: &str = "ocel:timestamp";
+pub const OBJECT_ID: &str = "ocel:oid";
+pub const OBJECT_TYPE: &str = "ocel:type";
+pub const QUALIFIER: &str = "ocel:qualifier";
+pub const TARGET_OBJECT_ID: &str = "ocel:target-oid";
+pub const ATTRIBUTE: &str = "ocel:attribute";
+pub const VALUE: &str = "ocel:value";
+
+/// The log as five tables. Events and objects have one column per `vmap`/`ovmap` key,
+/// the relations table holds one row per event, object in the `omap` and qualifier of the link,
+/// the o2o table one row per link between objects and the changes table one row per change in the `history` of an object.
+#[derive(Debug, Clone)]
+pub struct OcelDataFrames {
+    pub events: DataFrame,
+    pub objects: DataFrame,
+    pub relations: DataFrame,
+    pub o2o: DataFrame,
+    pub changes: DataFrame
+}
+
+impl Ocel {
+    /// Attribute columns are boolean, integer or float if all values of the key are,
+    /// other values are stored as strings, with lists and maps as their json text.
+    pub fn to_dataframes(&self) -> Result<OcelDataFrames, Error> {
+        let eids: Vec<usize> = self.events.keys().copied().sorted().collect();
+        let oids: Vec<usize> = self.objects.keys().copied().sorted().collect();
+
+        let mut event_columns = vec![Series::new(EVENT_ID, eids.iter().map(|eid| self.event_map.get_by_right(eid).map(|s| s.as_str())).collect::<Vec<Option<&str>>>()),
+                                     Series::new(ACTIVITY, eids.iter().map(|eid| self.activity(&self.events[eid])).collect::<Vec<&str>>()),
+                                     Series::new(TIMESTAMP, eids.iter().map(|eid| self.events[eid].timestamp.timestamp_micros()).collect::<Vec<i64>>())
+                                            .cast(&DataType::Datetime(TimeUnit::Microseconds, None))?];
+        let vmap_keys: Vec<&String> = eids.iter().flat_map(|eid| self.events[eid].vmap.keys()).unique().sorted().collect();
+        for key in vmap_keys {
+            event_columns.push(attribute_series(key, eids.iter().map(|eid| self.events[eid].vmap.get(key)).collect()));
+        }
+
+        let mut object_columns = vec![Series::new(OBJECT_ID, oids.iter().map(|oid| self.object_map.get_by_right(oid).map(|s| s.as_str())).collect::<Vec<Option<&str>>>()),
+                                      Series::new(OBJECT_TYPE, oids.iter().map(|oid| self.object_type(&self.objects[oid])).collect::<Vec<&str>>())];
+        let ovmap_keys: Vec<&String> = oids.iter().flat_map(|oid| self.objects[oid].ovmap.keys()).unique().sorted().collect();
+        for key in ovmap_keys {
+            object_columns.push(attribute_series(key, oids.iter().map(|oid| self.objects[oid].ovmap.get(key)).collect()));
+        }
+
+        // links without a qualifier have a null qualifier
+        let relations: Vec<(usize, usize, Option<&str>)> = eids.iter().flat_map(|eid| {
+            let ev = &self.events[eid];
+            ev.omap.iter().sorted().flat_map(move |oid| match ev.qualifiers.get(oid) {
+                Some(qualifiers) => qualifiers.iter().map(|q| (*eid, *oid, Some(q.as_str()))).collect::<Vec<_>>(),
+                None => vec![(*eid, *oid, None)]
+            })
+        }).collect();
+        let relation_columns = vec![Series::new(EVENT_ID, relations.iter().map(|(eid, _, _)| self.event_map.get_by_right(eid).map(|s| s.as_str())).collect::<Vec<Option<&str>>>()),
+                                    Series::new(OBJECT_ID, relations.iter().map(|(_, oid, _)| self.object_map.get_by_right(oid).map(|s| s.as_str())).collect::<Vec<Option<&str>>>()),
+                                    Series::new(QUALIFIER, relations.iter().map(|(_, _, q)| *q).collect::<Vec<Option<&str>>>())];
+
+        let links: Vec<(usize, &ObjectLink)> = oids.iter().flat_map(|oid| self.o2o.get(oid).into_iter().flatten().map(|link| (*oid, link))).collect();
+        let o2o_columns = vec![Series::new(OBJECT_ID, links.iter().map(|(oid, _)| self.object_map.get_by_right(oid).map(|s| s.as_str())).collect::<Vec<Option<&str>>>()),
+                               Series::new(TARGET_OBJECT_ID, links.iter().map(|(_, link)| self.object_map.get_by_right(&link.target).map(|s| s.as_str())).collect::<Vec<Option<&str>>>()),
+                               Series::new(QUALIFIER, links.iter().map(|(_, link)| link.qualifier.as_str()).collect::<Vec<&str>>())];
+
+        // the values of all attributes share a column, typed as the attribute columns are
+        let changes: Vec<(usize, &String, &DateTime<Utc>, &Value)> = oids.iter().flat_map(|oid| {
+            self.objects[oid].history.iter().sorted_by(|a, b| a.0.cmp(b.0)).flat_map(move |(key, changes)| changes.iter().map(move |(time, value)| (*oid, key, time, value)))
+        }).collect();
+        let change_columns = vec![Series::new(OBJECT_ID, changes.iter().map(|(oid, _, _, _)| self.object_map.get_by_right(oid).map(|s| s.as_str())).collect::<Vec<Option<&str>>>()),
+                                  Series::new(ATTRIBUTE, changes.iter().map(|(_, key, _, _)| key.as_str()).collect::<Vec<&str>>()),
+                                  Series::new(TIMESTAMP, changes.iter().map(|(_, _, time, _)| time.timestamp_micros()).collect::<Vec<i64>>())
+                                         .cast(&DataType::Datetime(TimeUnit::Microseconds, None))?,
+                                  attribute_series(VALUE, changes.iter().map(|(_, _, _, value)| Some(*value)).collect())];
+
+        Ok(OcelDataFrames { events: DataFrame::new(event_columns)?,
+                            objects: DataFrame::new(object_columns)?,
+                            relations: DataFrame::new(relation_columns)?,
+                            o2o: DataFrame::new(o2o_columns)?,
+                            changes: DataFrame::new(change_columns)? })
+    }
+
+    /// Rebuild a log from tables in the shape of `to_dataframes`. Events and objects are numbered
+    /// by row and null attribute values are left out. Timestamps may be datetime or string columns,
+    /// the qualifier column of the relations and o2o and changes tables without rows may be left out.
+    /// Changes stored as text are read back as json if the initial value of their attribute is not a string.
+    pub fn from_dataframes(frames: &OcelDataFrames) -> Result<Ocel, Error> {
+        Ok(Ocel::from_dataframes_with_options(frames, &ImportOptions::default())?.0)
+    }
+
+    /// `from_dataframes` with the given handling of the timestamps in string columns,
+    /// along with a report of the values that were coerced.
+    pub fn from_dataframes_with_options(frames: &OcelDataFrames, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+        let mut timestamp_parser = TimestampParser::new(options);
+        let mut log_internal = Ocel::default();
+        let mut object_types: Vec<String> = vec![];
+
+        let oids = string_column(&frames.objects, OBJECT_ID)?;
+        let obj_types = string_column(&frames.objects, OBJECT_TYPE)?;
+        let ovmap_columns = attribute_columns(&frames.objects, &[OBJECT_ID, OBJECT_TYPE])?;
+        for (row, (oid, obj_type)) in oids.into_iter().zip(obj_types).enumerate() {
+            let oid = oid.ok_or_else(|| Error::Schema(format!("Object {} has no id.", row)))?;
+            let obj_type = obj_type.ok_or_else(|| Error::Schema(format!("Object {} has no type.", oid)))?;
+            let ovmap = ovmap_columns.iter().filter_map(|(key, values)| values[row].to_owned().map(|value| (key.to_string(), value))).collect();
+            declare_object(&mut log_internal, &oid, &obj_type, ovmap, AttributeHistory::new())?;
+            if !object_types.contains(&obj_type) {
+                object_types.push(obj_type);
+            }
+        }
+
+        let eids = string_column(&frames.events, EVENT_ID)?;
+        let activities = string_column(&frames.events, ACTIVITY)?;
+        let timestamps = timestamp_column(&frames.events, TIMESTAMP, &mut timestamp_parser)?;
+        let vmap_columns = attribute_columns(&frames.events, &[EVENT_ID, ACTIVITY, TIMESTAMP])?;
+        for (row, ((eid, activity), timestamp)) in eids.into_iter().zip(activities).zip(timestamps).enumerate() {
+            let eid = eid.ok_or_else(|| Error::Schema(format!("Event {} has no id.", row)))?;
+            let activity = activity.ok_or_else(|| Error::Schema(format!("Event {} has no activity.", eid)))?;
+            let timestamp = timestamp.ok_or_else(|| Error::Schema(format!("Event {} has no timestamp.", eid)))?;
+            let vmap = vmap_columns.iter().filter_map(|(key, values)| values[row].to_owned().map(|value| (key.to_string(), value))).collect();
+            declare_event(&mut log_internal, &eid, &activity, timestamp, vmap)?;
+        }
+
+        let relation_eids = string_column(&frames.relations, EVENT_ID)?;
+        let relation_oids = string_column(&frames.relations, OBJECT_ID)?;
+        let relation_qualifiers = optional_string_column(&frames.relations, QUALIFIER)?;
+        for ((eid, oid), qualifier) in relation_eids.iter().zip(relation_oids.iter()).zip(relation_qualifiers) {
+            let (eid, oid) = (eid.as_deref().unwrap_or_default(), oid.as_deref().unwrap_or_default());
+            let eid_nh = declared_event(&log_internal, eid)?;
+            relate_object(&mut log_internal, eid_nh, oid, qualifier.as_deref().unwrap_or_default())?;
+        }
+
+        if frames.o2o.height() > 0 {
+            let sources = string_column(&frames.o2o, OBJECT_ID)?;
+            let targets = string_column(&frames.o2o, TARGET_OBJECT_ID)?;
+            let qualifiers = optional_string_column(&frames.o2o, QUALIFIER)?;
+            for ((source, target), qualifier) in sources.iter().zip(targets.iter()).zip(qualifiers) {
+                let (source, target) = (source.as_deref().unwrap_or_default(), target.as_deref().unwrap_or_default());
+                relate_objects(&mut log_internal, source, target, qualifier.as_deref().unwrap_or_default())?;
+            }
+        }
+
+        if frames.changes.height() > 0 {
+            let oids = string_column(&frames.changes, OBJECT_ID)?;
+            let keys = string_column(&frames.changes, ATTRIBUTE)?;
+            let times = timestamp_column(&frames.changes, TIMESTAMP, &mut timestamp_parser)?;
+            let values = value_column(&frames.changes, VALUE)?;
+            for (((oid, key), time), value) in oids.iter().zip(keys).zip(times).zip(values) {
+                let oid = oid.as_deref().unwrap_or_default();
+                let oid_nh = *log_internal.object_map.get_by_left(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("A change references the undeclared object {}.", oid)))?;
+                let key = key.ok_or_else(|| Error::Schema(format!("A change of object {} has no attribute.", oid)))?;
+                let (time, _) = time.ok_or_else(|| Error::Schema(format!("The change of {} of object {} has no timestamp.", key, oid)))?;
+                let obj = log_internal.objects.get_mut(&oid_nh).expect("object map and objects are filled together");
+                let value = match (value, obj.ovmap.get(&key)) {
+                    (Some(Value::String(text)), Some(initial)) if !initial.is_string() => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+                    (value, _) => value.unwrap_or(Value::Null)
+                };
+                obj.history.entry(key).or_default().push((time, value));
+            }
+            for obj in log_internal.objects.values_mut() {
+                obj.history.values_mut().for_each(|changes| changes.sort_by_key(|(time, _)| *time));
+            }
+        }
+
+        log_internal.reindex_chronologically();
+        timestamp_parser.report.attributes = log_internal.infer_schema();
+        let attribute_names: Vec<&str> = vmap_columns.iter().chain(ovmap_columns.iter()).map(|(key, _)| *key).collect();
+        fill_globals(&mut log_internal, "1.0", attribute_names, object_types.iter().map(|s| s.as_str()).collect());
+
+        Ok((log_internal, timestamp_parser.report))
+    }
+}
+
+fn attribute_series(name: &str, values: Vec<Option<&Value>>) -> Series {
+    let present: Vec<&Value> = values.iter().flatten().copied().filter(|v| !v.is_null()).collect();
+    if !present.is_empty() && present.iter().all(|v| v.is_boolean()) {
+        Series::new(name, values.iter().map(|v| v.and_then(Value::as_bool)).collect::<Vec<Option<bool>>>())
+    } else if !present.is_empty() && present.iter().all(|v| v.is_i64()) {
+        Series::new(name, values.iter().map(|v| v.and_then(Value::as_i64)).collect::<Vec<Option<i64>>>())
+    } else if !present.is_empty() && present.iter().all(|v| v.is_number()) {
+        Series::new(name, values.iter().map(|v| v.and_then(Value::as_f64)).collect::<Vec<Option<f64>>>())
+    } else {
+        Series::new(name, values.iter().map(|v| match v {
+            Some(Value::Null) | None => None,
+            Some(Value::String(s)) => Some(s.to_owned()),
+            Some(other) => Some(other.to_string())
+        }).collect::<Vec<Option<String>>>())
+    }
+}
+
+type AttributeColumn<'a> = (&'a str, Vec<Option<Value>>);
+
+fn attribute_columns<'a>(df: &'a DataFrame, reserved: &[&str]) -> Result<Vec<AttributeColumn<'a>>, Error> {
+    df.get_column_names().into_iter()
+      .filter(|name| !reserved.contains(name))
+      .map(|name| Ok((name, value_column(df, name)?)))
+      .collect()
+}
+
+/// Datetime columns hold UTC instants without an offset, text columns are read by the parser.
+fn timestamp_column(df: &DataFrame, column: &str, timestamp_parser: &mut TimestampParser) -> Result<Vec<Option<ParsedTimestamp>>, Error> {
+    let series = df.column(column)?;
+    let values = match series.dtype() {
+        DataType::Datetime(_, _) => series.cast(&DataType::Datetime(TimeUnit::Microseconds, None))?
+                                          .cast(&DataType::Int64)?
+                                          .i64()?
+                                          .into_iter()
+                                          .map(|v| v.and_then(NaiveDateTime::from_timestamp_micros).map(|dt| (dt.and_utc(), None)))
+                                          .collect(),
+        _ => string_column(df, column)?.into_iter().map(|v| v.map(|s| timestamp_parser.parse(&s)).transpose()).collect::<Result<_, Error>>()?
+    };
+    Ok(values)
+}
+
+pub(crate) fn string_column(df: &DataFrame, column: &str) -> Result<Vec<Option<String>>, Error> {
+    let series = df.column(column)?.cast(&DataType::Utf8)?;
+    let values = series.utf8()?.into_iter().map(|v| v.map(|s| s.to_owned())).collect();
+    Ok(values)
+}
+
+/// Values of a column that may be left out, all of them are null if it is.
+fn optional_string_column(df: &DataFrame, column: &str) -> Result<Vec<Option<String>>, Error> {
+    match df.get_column_names().contains(&column) {
+        true => string_column(df, column),
+        false => Ok(vec![None; df.height()])
+    }
+}
+
+/// Numbers and booleans keep their type, everything else is read as a string.
+pub(crate) fn value_column(df: &DataFrame, column: &str) -> Result<Vec<Option<Value>>, Error> {
+    let series = df.column(column)?;
+    let values = match series.dtype() {
+        DataType::Boolean => series.bool()?.into_iter().map(|v| v.map(Value::from)).collect(),
+        dtype if dtype.is_float() => series.cast(&DataType::Float64)?.f64()?.into_iter().map(|v| v.map(Value::from)).collect(),
+        dtype if dtype.is_integer() => series.cast(&DataType::Int64)?.i64()?.into_iter().map(|v| v.map(Value::from)).collect(),
+        _ => string_column(df, column)?.into_iter().map(|v| v.map(Value::String)).collect()
+    };
+    Ok(values)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ahash::{AHashMap, AHashSet};
+    use chrono::{Duration, FixedOffset};
+    use serde_json::json;
+    use crate::objects::ocel::importer::import_ocel;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    #[test]
+    fn test_to_dataframes() {
+        let frames = OCEL.to_dataframes().unwrap();
+        assert_eq!(frames.events.height(), OCEL.events.len());
+        assert_eq!(frames.objects.height(), OCEL.objects.len());
+        assert_eq!(frames.relations.height(), OCEL.events.values().map(|ev| ev.omap.len()).sum::<usize>());
+        assert!(matches!(frames.events.column(TIMESTAMP).unwrap().dtype(), DataType::Datetime(_, _)));
+    }
+
+    #[test]
+    fn test_dataframes_round_trip() {
+        let log = Ocel::from_dataframes(&OCEL.to_dataframes().unwrap()).unwrap();
+        assert_eq!(log.activities().iter().sorted().collect::<Vec<_>>(), OCEL.activities().iter().sorted().collect::<Vec<_>>());
+
+        for (eid, eid_nh) in OCEL.event_map.iter() {
+            let original = &OCEL.events[eid_nh];
+            let ev = &log.events[log.event_map.get_by_left(eid).unwrap()];
+            assert_eq!(log.activity(ev), OCEL.activity(original));
+            assert_eq!(ev.timestamp, original.timestamp);
+            assert_eq!(ev.vmap, original.vmap.iter().filter(|(_, v)| !v.is_null()).map(|(k, v)| (k.to_owned(), v.to_owned())).collect::<AHashMap<String, Value>>());
+            let omap: AHashSet<&str> = ev.omap.iter().map(|oid| log.object_map.get_by_right(oid).unwrap().as_str()).collect();
+            assert_eq!(omap, original.omap.iter().map(|oid| OCEL.object_map.get_by_right(oid).unwrap().as_str()).collect());
+        }
+        for (oid, oid_nh) in OCEL.object_map.iter() {
+            let obj = &log.objects[log.object_map.get_by_left(oid).unwrap()];
+            assert_eq!(log.object_type(obj), OCEL.object_type(&OCEL.objects[oid_nh]));
+            assert_eq!(obj.events.len(), OCEL.objects[oid_nh].events.len());
+        }
+    }
+
+    #[test]
+    fn test_string_timestamps() {
+        let frames = OcelDataFrames { events: DataFrame::new(vec![Series::new(EVENT_ID, vec!["e1", "e2"]),
+                                                                  Series::new(ACTIVITY, vec!["a", "b"]),
+                                                                  Series::new(TIMESTAMP, vec!["2023-01-01 10:00:00", "yesterday"])]).unwrap(),
+                                      objects: DataFrame::new(vec![Series::new(OBJECT_ID, vec!["o1"]), Series::new(OBJECT_TYPE, vec!["order"])]).unwrap(),
+                                      relations: DataFrame::new(vec![Series::new(EVENT_ID, vec!["e1", "e2"]), Series::new(OBJECT_ID, vec!["o1", "o1"])]).unwrap(),
+                                      o2o: DataFrame::default(),
+                                      changes: DataFrame::default() };
+        let options = ImportOptions { default_offset: FixedOffset::east_opt(7200).unwrap(), ..Default::default() };
+        let (log, report) = Ocel::from_dataframes_with_options(&frames, &options).unwrap();
+        assert_eq!(report.naive_timestamps, 1);
+        assert_eq!(report.invalid_timestamps, 1);
+        let e1 = &log.events[log.event_map.get_by_left("e1").unwrap()];
+        assert_eq!(e1.timestamp.to_rfc3339(), "2023-01-01T08:00:00+00:00");
+        assert_eq!(e1.offset, FixedOffset::east_opt(7200));
+
+        let strict = ImportOptions { strict_timestamps: true, ..options };
+        assert!(Ocel::from_dataframes_with_options(&frames, &strict).is_err());
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let mut log = OCEL.clone();
+        let changed_at = log.events.values().map(|ev| ev.timestamp).max().unwrap();
+        for (i, obj) in log.objects_mut().values_mut().filter(|obj| obj.ovmap.contains_key("price")).take(3).enumerate() {
+            obj.history.insert("price".to_string(), vec![(changed_at, json!(i as f64 + 0.5)), (changed_at + Duration::days(1), json!(i as f64 + 1.5))]);
+        }
+        let oid = *log.object_map.get_by_left("i1").unwrap();
+        log.objects_mut().get_mut(&oid).unwrap().history.insert("status".to_string(), vec![(changed_at, json!("shipped"))]);
+
+        let frames = log.to_dataframes().unwrap();
+        assert_eq!(frames.changes.height(), 7);
+        let reimported = Ocel::from_dataframes(&frames).unwrap();
+        for (oid, oid_nh) in log.object_map.iter() {
+            assert_eq!(reimported.objects[reimported.object_map.get_by_left(oid).unwrap()].history, log.objects[oid_nh].history);
+        }
+    }
+
+    #[test]
+    fn test_undeclared_relation_fails() {
+        let mut frames = OCEL.to_dataframes().unwrap();
+        frames.relations = DataFrame::new(vec![Series::new(EVENT_ID, vec!["no such event"]), Series::new(OBJECT_ID, vec!["no such object"])]).unwrap();
+        assert!(Ocel::from_dataframes(&frames).is_err());
+    }
+}
@@ -1,20 +1,91 @@
 pub(crate) mod variants;
-use std::error::Error;
+use crate::Error;
+use std::io::Write;
 
 use crate::objects::ocel::Ocel;
+use crate::objects::ocel::format::{OcelFormat, ocel_format_handler};
 
-use self::variants::jsonocel::{export_json_ocel, export_json_ocel_pretty, generate_ocel_serde};
+use self::variants::jsonocel::{export_json_ocel, export_json_ocel_pretty, export_json_ocel_to_writer, export_json_ocel_pretty_to_writer, generate_ocel_serde};
+use self::variants::jsonocel2::{export_json_ocel2, export_json_ocel2_pretty, export_json_ocel2_to_writer};
+use self::variants::sqliteocel::export_sqlite_ocel;
+use self::variants::xmlocel::{export_xml_ocel, export_xml_ocel_to_writer};
 
 use super::OcelSerde;
 
-pub fn export_ocel(log: &Ocel, file_path: &str) -> Result<bool, Box<dyn Error>> {
-    return export_json_ocel(log, file_path);
+/// How the log exporters write timestamps and attribute history.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Write timestamps in the offset they were imported with instead of UTC.
+    pub keep_offsets: bool,
+    /// Fail instead of leaving out the changes of object attributes in jsonocel and xmlocel,
+    /// which only hold the initial values.
+    pub strict_history: bool
 }
 
-pub fn export_ocel_pretty(log: &Ocel, file_path: &str) -> Result<bool, Box<dyn Error>> {
-    return export_json_ocel_pretty(log, file_path);
+/// Export a log in the format belonging to the extension of the path, jsonocel if it has none.
+pub fn export_ocel(log: &Ocel, file_path: &str) -> Result<bool, Error> {
+    export_ocel_as(log, file_path, OcelFormat::from_extension(file_path).unwrap_or(OcelFormat::JsonOcel))
 }
 
-pub fn generate_ocel_external_repr(log: &Ocel) -> OcelSerde {
-    return generate_ocel_serde(log); 
+pub fn export_ocel_as(log: &Ocel, file_path: &str, format: OcelFormat) -> Result<bool, Error> {
+    export_ocel_with_options(log, file_path, format, &ExportOptions::default())
+}
+
+/// Export a log with the given handling of timestamps, custom formats ignore the options.
+pub fn export_ocel_with_options(log: &Ocel, file_path: &str, format: OcelFormat, options: &ExportOptions) -> Result<bool, Error> {
+    match format {
+        OcelFormat::JsonOcel => export_json_ocel(log, file_path, options),
+        OcelFormat::XmlOcel => export_xml_ocel(log, file_path, options),
+        OcelFormat::JsonOcel2 => export_json_ocel2(log, file_path, options),
+        OcelFormat::SqliteOcel2 => export_sqlite_ocel(log, file_path, options),
+        OcelFormat::Custom(name) => ocel_format_handler(&name)?.export(log, file_path)
+    }
+}
+
+pub fn export_ocel_to_writer<W: Write>(log: &Ocel, writer: W, format: OcelFormat) -> Result<bool, Error> {
+    export_ocel_to_writer_with_options(log, writer, format, &ExportOptions::default())
+}
+
+pub fn export_ocel_to_writer_with_options<W: Write>(log: &Ocel, mut writer: W, format: OcelFormat, options: &ExportOptions) -> Result<bool, Error> {
+    match format {
+        OcelFormat::JsonOcel => export_json_ocel_to_writer(log, writer, options),
+        OcelFormat::XmlOcel => export_xml_ocel_to_writer(log, writer, options),
+        OcelFormat::JsonOcel2 => export_json_ocel2_to_writer(log, writer, options),
+        OcelFormat::SqliteOcel2 => Err(Error::UnsupportedFormat("sqlite logs can only be exported to a file.".to_string())),
+        OcelFormat::Custom(name) => ocel_format_handler(&name)?.export_to_writer(log, &mut writer)
+    }
+}
+
+pub fn export_ocel_to_string(log: &Ocel, format: OcelFormat) -> Result<String, Error> {
+    let mut buf: Vec<u8> = vec![];
+    export_ocel_to_writer(log, &mut buf, format)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+pub fn export_ocel_pretty(log: &Ocel, file_path: &str) -> Result<bool, Error> {
+    export_json_ocel_pretty(log, file_path, &ExportOptions::default())
+}
+
+pub fn export_ocel_pretty_to_writer<W: Write>(log: &Ocel, writer: W) -> Result<bool, Error> {
+    export_json_ocel_pretty_to_writer(log, writer, &ExportOptions::default())
+}
+
+pub fn export_ocel_xml(log: &Ocel, file_path: &str) -> Result<bool, Error> {
+    export_xml_ocel(log, file_path, &ExportOptions::default())
+}
+
+pub fn export_ocel2(log: &Ocel, file_path: &str) -> Result<bool, Error> {
+    export_json_ocel2(log, file_path, &ExportOptions::default())
+}
+
+pub fn export_ocel2_pretty(log: &Ocel, file_path: &str) -> Result<bool, Error> {
+    export_json_ocel2_pretty(log, file_path, &ExportOptions::default())
+}
+
+pub fn export_ocel2_sqlite(log: &Ocel, file_path: &str) -> Result<bool, Error> {
+    export_sqlite_ocel(log, file_path, &ExportOptions::default())
+}
+
+pub fn generate_ocel_external_repr(log: &Ocel) -> Result<OcelSerde, Error> {
+    generate_ocel_serde(log, &ExportOptions::default())
 }
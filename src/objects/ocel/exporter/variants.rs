@@ -1 +1,17 @@
 pub(super) mod jsonocel;
+pub(super) mod jsonocel2;
+pub(super) mod sqliteocel;
+pub(super) mod xmlocel;
+
+use crate::Error;
+use crate::objects::ocel::Ocel;
+use super::ExportOptions;
+
+/// Formats without attribute history keep the initial values of the objects, in strict mode a log with changes is not exported.
+pub(super) fn check_history(log: &Ocel, format: &str, options: &ExportOptions) -> Result<(), Error> {
+    let changes: usize = log.objects.values().flat_map(|obj| obj.history.values()).map(|changes| changes.len()).sum();
+    match options.strict_history && changes > 0 {
+        true => Err(Error::UnsupportedFormat(format!("{} has no attribute history, {} changes of object attributes would be lost.", format, changes))),
+        false => Ok(())
+    }
+}
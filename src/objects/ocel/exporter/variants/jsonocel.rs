@@ -1,52 +1,71 @@
 #[allow(unused_imports)]
-use crate::objects::ocel::{Ocel, OcelSerde, OcelEvent, OcelObject, OcelEventSerde, OcelObjectSerde};
+use crate::objects::ocel::{Ocel, OcelSerde, OcelEvent, OcelObject, OcelEventSerde, OcelObjectSerde, OcelRelationshipSerde};
+use crate::objects::ocel::exporter::ExportOptions;
+use super::check_history;
 use ahash::{AHashMap, AHashSet, RandomState};
+use chrono::SecondsFormat;
 use indexmap::IndexMap;
-use std::{fs::OpenOptions, io::{BufWriter, Write}, error::Error};
+use std::{fs::OpenOptions, io::{BufWriter, Write}};
+use crate::Error;
 
-pub(crate) fn export_json_ocel(log: &Ocel, file_path: &str) -> Result<bool, Box<dyn Error>> {
-    let log_serde: OcelSerde = generate_ocel_serde(log);
+pub(crate) fn export_json_ocel(log: &Ocel, file_path: &str, options: &ExportOptions) -> Result<bool, Error> {
+    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path)?;
+    export_json_ocel_to_writer(log, BufWriter::new(output_file), options)
+}
 
-    let serde_ocel = serde_json::to_string(&log_serde).unwrap();
-    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path).unwrap();
-    
-    let mut f = BufWriter::new(output_file);
-    f.write_all(serde_ocel.as_bytes()).expect("Unable to write data");
+pub(crate) fn export_json_ocel_pretty(log: &Ocel, file_path: &str, options: &ExportOptions) -> Result<bool, Error> {
+    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path)?;
+    export_json_ocel_pretty_to_writer(log, BufWriter::new(output_file), options)
+}
+
+pub(crate) fn export_json_ocel_to_writer<W: Write>(log: &Ocel, mut writer: W, options: &ExportOptions) -> Result<bool, Error> {
+    let log_serde: OcelSerde = generate_ocel_serde(log, options)?;
+    serde_json::to_writer(&mut writer, &log_serde)?;
+    writer.flush()?;
 
     Ok(true)
 }
 
-pub(crate) fn export_json_ocel_pretty(log: &Ocel, file_path: &str) -> Result<bool, Box<dyn Error>> {
-    let log_serde: OcelSerde = generate_ocel_serde(log);
-
-    let serde_ocel = serde_json::to_string_pretty(&log_serde).unwrap();
-    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path).unwrap();
-    
-    let mut f = BufWriter::new(output_file);
-    f.write_all(serde_ocel.as_bytes()).expect("Unable to write data");
+pub(crate) fn export_json_ocel_pretty_to_writer<W: Write>(log: &Ocel, mut writer: W, options: &ExportOptions) -> Result<bool, Error> {
+    let log_serde: OcelSerde = generate_ocel_serde(log, options)?;
+    serde_json::to_writer_pretty(&mut writer, &log_serde)?;
+    writer.flush()?;
 
     Ok(true)
 }
 
 
-pub(crate) fn generate_ocel_serde(log: &Ocel) -> OcelSerde {
+pub(crate) fn generate_ocel_serde(log: &Ocel, options: &ExportOptions) -> Result<OcelSerde, Error> {
+    check_history(log, "jsonocel", options)?;
     let hasher = RandomState::new();
     let mut log_serde: OcelSerde = OcelSerde { global_log: log.global_log.to_owned(), global_event: log.global_event.to_owned(), global_object: log.global_object.to_owned(), objects: AHashMap::new(), events: IndexMap::with_hasher(hasher) };
 
+    let object_name = |oid: &usize| log.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)));
+    let relationship = |oid: &usize, qualifier: &str| -> Result<OcelRelationshipSerde, Error> {
+        Ok(OcelRelationshipSerde { object_id: object_name(oid)?.to_owned(), qualifier: qualifier.to_owned() })
+    };
+
     for (oid, data) in &log.objects {
-        log_serde.objects.insert(log.object_map.get_by_right(oid).expect("This can't fail").to_owned(), OcelObjectSerde { obj_type: data.obj_type.to_owned(), ovmap: data.ovmap.to_owned() });
+        let o2o = log.o2o.get(oid).into_iter().flatten().map(|link| relationship(&link.target, &link.qualifier)).collect::<Result<_, Error>>()?;
+        log_serde.objects.insert(object_name(oid)?.to_owned(), OcelObjectSerde { obj_type: log.object_type(data).to_owned(), ovmap: data.ovmap.to_owned(), o2o });
     }
 
 
     for (eid, data) in &log.events {
-        let mut serde_event = OcelEventSerde {activity: data.activity.to_owned(), timestamp: data.timestamp, vmap: data.vmap.to_owned(), omap: AHashSet::new()};
+        let mut serde_event = OcelEventSerde {activity: log.activity(data).to_owned(), timestamp: data.export_timestamp(options.keep_offsets).to_rfc3339_opts(SecondsFormat::AutoSi, true), vmap: data.vmap.to_owned(), omap: AHashSet::new(), qualifiers: vec![]};
 
         for oid in data.omap.iter() {
-            serde_event.omap.insert(log.object_map.get_by_right(oid).expect("This can't fail").to_owned());
+            serde_event.omap.insert(object_name(oid)?.to_owned());
+        }
+        for (oid, qualifiers) in &data.qualifiers {
+            for qualifier in qualifiers {
+                serde_event.qualifiers.push(relationship(oid, qualifier)?);
+            }
         }
-        log_serde.events.insert(log.event_map.get_by_right(eid).expect("This can't fail").to_owned(), serde_event);
+        let eid_str = log.event_map.get_by_right(eid).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is missing from the event map.", eid)))?;
+        log_serde.events.insert(eid_str.to_owned(), serde_event);
     }
     
-    log_serde
+    Ok(log_serde)
 
 }
@@ -0,0 +1,114 @@
+use crate::objects::ocel::Ocel;
+use crate::objects::ocel::variants::jsonocel2::{Ocel2Serde, Ocel2TypeSerde, Ocel2AttributeDeclSerde, Ocel2ObjectSerde, Ocel2EventSerde, Ocel2AttributeSerde, Ocel2RelationshipSerde, infer_attribute_type};
+use crate::objects::ocel::exporter::ExportOptions;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use std::{fs::OpenOptions, io::{BufWriter, Write}};
+use crate::Error;
+
+// OCEL 2.0 requires a time for every object attribute value, initial values start at the epoch
+const STATIC_ATTRIBUTE_TIME: &str = "1970-01-01T00:00:00Z";
+
+pub(crate) fn export_json_ocel2(log: &Ocel, file_path: &str, options: &ExportOptions) -> Result<bool, Error> {
+    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path)?;
+    export_json_ocel2_to_writer(log, BufWriter::new(output_file), options)
+}
+
+pub(crate) fn export_json_ocel2_pretty(log: &Ocel, file_path: &str, options: &ExportOptions) -> Result<bool, Error> {
+    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path)?;
+    export_json_ocel2_pretty_to_writer(log, BufWriter::new(output_file), options)
+}
+
+pub(crate) fn export_json_ocel2_to_writer<W: Write>(log: &Ocel, mut writer: W, options: &ExportOptions) -> Result<bool, Error> {
+    let log_serde: Ocel2Serde = generate_ocel2_serde(log, options)?;
+    serde_json::to_writer(&mut writer, &log_serde)?;
+    writer.flush()?;
+
+    Ok(true)
+}
+
+pub(crate) fn export_json_ocel2_pretty_to_writer<W: Write>(log: &Ocel, mut writer: W, options: &ExportOptions) -> Result<bool, Error> {
+    let log_serde: Ocel2Serde = generate_ocel2_serde(log, options)?;
+    serde_json::to_writer_pretty(&mut writer, &log_serde)?;
+    writer.flush()?;
+
+    Ok(true)
+}
+
+
+pub(crate) fn generate_ocel2_serde(log: &Ocel, options: &ExportOptions) -> Result<Ocel2Serde, Error> {
+    let mut log_serde = Ocel2Serde::default();
+    let mut event_types: IndexMap<&str, IndexMap<&str, &str>> = IndexMap::new();
+    let mut object_types: IndexMap<&str, IndexMap<&str, &str>> = IndexMap::new();
+
+    // keep the declared object type order, even for types without objects
+    if let Some(declared) = log.global_log.get("ocel:object-types").and_then(|ots| ots.as_array()) {
+        for ot in declared.iter().filter_map(|ot| ot.as_str()) {
+            object_types.entry(ot).or_default();
+        }
+    }
+
+    for oid in log.objects.keys().sorted() {
+        let obj = &log.objects[oid];
+        let oid_str = log.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)))?;
+        let attr_types = object_types.entry(log.object_type(obj)).or_default();
+        let mut attributes: Vec<Ocel2AttributeSerde> = vec![];
+
+        for (key, value) in obj.ovmap.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            attr_types.entry(key.as_str()).or_insert(infer_attribute_type(value));
+            attributes.push(Ocel2AttributeSerde { name: key.to_owned(), time: Some(STATIC_ATTRIBUTE_TIME.to_string()), value: value.to_owned() });
+        }
+        for (key, changes) in obj.history.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            for (time, value) in changes {
+                attr_types.entry(key.as_str()).or_insert(infer_attribute_type(value));
+                attributes.push(Ocel2AttributeSerde { name: key.to_owned(), time: Some(time.to_rfc3339()), value: value.to_owned() });
+            }
+        }
+
+        let mut relationships: Vec<Ocel2RelationshipSerde> = vec![];
+        for link in log.o2o.get(oid).into_iter().flatten() {
+            let target = log.object_map.get_by_right(&link.target).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is linked to the unknown object {}.", oid_str, link.target)))?;
+            relationships.push(Ocel2RelationshipSerde { object_id: target.to_owned(), qualifier: link.qualifier.to_owned() });
+        }
+
+        log_serde.objects.push(Ocel2ObjectSerde { id: oid_str.to_owned(), obj_type: log.object_type(obj).to_owned(), attributes, relationships });
+    }
+
+    for eid in log.events.keys().sorted() {
+        let ev = &log.events[eid];
+        let eid_str = log.event_map.get_by_right(eid).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is missing from the event map.", eid)))?;
+        let attr_types = event_types.entry(log.activity(ev)).or_default();
+        let mut attributes: Vec<Ocel2AttributeSerde> = vec![];
+        let mut relationships: Vec<Ocel2RelationshipSerde> = vec![];
+
+        for (key, value) in ev.vmap.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            attr_types.entry(key.as_str()).or_insert(infer_attribute_type(value));
+            attributes.push(Ocel2AttributeSerde { name: key.to_owned(), time: None, value: value.to_owned() });
+        }
+
+        for oid in ev.omap.iter().sorted() {
+            let oid_str = log.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} references the unknown object {}.", eid_str, oid)))?;
+            match ev.qualifiers.get(oid) {
+                Some(qualifiers) => relationships.extend(qualifiers.iter().map(|qualifier| Ocel2RelationshipSerde { object_id: oid_str.to_owned(), qualifier: qualifier.to_owned() })),
+                None => relationships.push(Ocel2RelationshipSerde { object_id: oid_str.to_owned(), qualifier: String::new() })
+            }
+        }
+
+        log_serde.events.push(Ocel2EventSerde { id: eid_str.to_owned(), activity: log.activity(ev).to_owned(), time: ev.export_timestamp(options.keep_offsets).to_rfc3339(), attributes, relationships });
+    }
+
+    log_serde.object_types = object_types.into_iter()
+                                         .map(|(name, attrs)| Ocel2TypeSerde { name: name.to_owned(), attributes: decl_attributes(attrs) })
+                                         .collect();
+    log_serde.event_types = event_types.into_iter()
+                                       .map(|(name, attrs)| Ocel2TypeSerde { name: name.to_owned(), attributes: decl_attributes(attrs) })
+                                       .collect();
+
+    Ok(log_serde)
+}
+
+fn decl_attributes(attrs: IndexMap<&str, &str>) -> Vec<Ocel2AttributeDeclSerde> {
+    attrs.into_iter()
+         .map(|(name, attr_type)| Ocel2AttributeDeclSerde { name: name.to_owned(), attr_type: attr_type.to_owned() })
+         .collect()
+}
@@ -0,0 +1,142 @@
+use crate::objects::ocel::Ocel;
+use crate::objects::ocel::variants::sqliteocel::{EVENT_TABLE, EVENT_MAP_TYPE_TABLE, OBJECT_TABLE, OBJECT_MAP_TYPE_TABLE, EVENT_OBJECT_TABLE, OBJECT_OBJECT_TABLE, quote_identifier, infer_column_type, merge_column_type, json_to_sql, type_map_name};
+use crate::objects::ocel::exporter::ExportOptions;
+use ahash::AHashSet;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use rusqlite::{Connection, params_from_iter};
+use rusqlite::types::Value as SqlValue;
+use crate::Error;
+use std::path::Path;
+
+// initial values of object attributes start at the epoch, their changes follow in rows of their own
+const STATIC_ATTRIBUTE_TIME: &str = "1970-01-01T00:00:00Z";
+
+pub(crate) fn export_sqlite_ocel(log: &Ocel, file_path: &str, options: &ExportOptions) -> Result<bool, Error> {
+    if Path::new(file_path).exists() {
+        std::fs::remove_file(file_path)?;
+    }
+    let mut conn = Connection::open(file_path)?;
+    let tx = conn.transaction()?;
+
+    tx.execute_batch(format!("CREATE TABLE {} (ocel_id TEXT PRIMARY KEY, ocel_type TEXT);
+                              CREATE TABLE {} (ocel_type TEXT PRIMARY KEY, ocel_type_map TEXT);
+                              CREATE TABLE {} (ocel_id TEXT PRIMARY KEY, ocel_type TEXT);
+                              CREATE TABLE {} (ocel_type TEXT PRIMARY KEY, ocel_type_map TEXT);
+                              CREATE TABLE {} (ocel_event_id TEXT, ocel_object_id TEXT, ocel_qualifier TEXT, PRIMARY KEY (ocel_event_id, ocel_object_id, ocel_qualifier));
+                              CREATE TABLE {} (ocel_source_id TEXT, ocel_target_id TEXT, ocel_qualifier TEXT, PRIMARY KEY (ocel_source_id, ocel_target_id, ocel_qualifier));",
+                             quote_identifier(EVENT_TABLE), quote_identifier(EVENT_MAP_TYPE_TABLE),
+                             quote_identifier(OBJECT_TABLE), quote_identifier(OBJECT_MAP_TYPE_TABLE),
+                             quote_identifier(EVENT_OBJECT_TABLE), quote_identifier(OBJECT_OBJECT_TABLE)).as_str())?;
+
+    // column types per event and object type, attributes that are always null are stored as text
+    let mut event_types: IndexMap<&str, IndexMap<&str, Option<&'static str>>> = IndexMap::new();
+    let mut object_types: IndexMap<&str, IndexMap<&str, Option<&'static str>>> = IndexMap::new();
+
+    if let Some(declared) = log.global_log.get("ocel:object-types").and_then(|ots| ots.as_array()) {
+        for ot in declared.iter().filter_map(|ot| ot.as_str()) {
+            object_types.entry(ot).or_default();
+        }
+    }
+
+    for oid in log.objects.keys().sorted() {
+        let obj = &log.objects[oid];
+        let columns = object_types.entry(log.object_type(obj)).or_default();
+        let changes = obj.history.iter().sorted_by(|a, b| a.0.cmp(b.0)).flat_map(|(key, changes)| changes.iter().map(move |(_, value)| (key, value)));
+        for (key, value) in obj.ovmap.iter().sorted_by(|a, b| a.0.cmp(b.0)).chain(changes) {
+            let column = columns.entry(key.as_str()).or_insert(None);
+            if !value.is_null() {
+                *column = Some(column.map_or(infer_column_type(value), |curr| merge_column_type(curr, infer_column_type(value))));
+            }
+        }
+    }
+
+    for eid in log.events.keys().sorted() {
+        let ev = &log.events[eid];
+        let columns = event_types.entry(log.activity(ev)).or_default();
+        for (key, value) in ev.vmap.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            let column = columns.entry(key.as_str()).or_insert(None);
+            if !value.is_null() {
+                *column = Some(column.map_or(infer_column_type(value), |curr| merge_column_type(curr, infer_column_type(value))));
+            }
+        }
+    }
+
+    let mut used_names: AHashSet<String> = AHashSet::new();
+    let mut event_tables: IndexMap<&str, String> = IndexMap::new();
+    for (activity, columns) in &event_types {
+        let type_map = type_map_name(activity, &mut used_names);
+        tx.execute(format!("INSERT INTO {} (ocel_type, ocel_type_map) VALUES (?1, ?2)", quote_identifier(EVENT_MAP_TYPE_TABLE)).as_str(), (activity, &type_map))?;
+        let table = format!("{}_{}", EVENT_TABLE, type_map);
+        let column_defs = columns.iter().map(|(name, col_type)| format!(", {} {}", quote_identifier(name), col_type.unwrap_or("TEXT"))).join("");
+        tx.execute_batch(format!("CREATE TABLE {} (ocel_id TEXT PRIMARY KEY, ocel_time TIMESTAMP{});", quote_identifier(&table), column_defs).as_str())?;
+        event_tables.insert(activity, table);
+    }
+
+    let mut used_names: AHashSet<String> = AHashSet::new();
+    let mut object_tables: IndexMap<&str, String> = IndexMap::new();
+    for (obj_type, columns) in &object_types {
+        let type_map = type_map_name(obj_type, &mut used_names);
+        tx.execute(format!("INSERT INTO {} (ocel_type, ocel_type_map) VALUES (?1, ?2)", quote_identifier(OBJECT_MAP_TYPE_TABLE)).as_str(), (obj_type, &type_map))?;
+        let table = format!("{}_{}", OBJECT_TABLE, type_map);
+        let column_defs = columns.iter().map(|(name, col_type)| format!(", {} {}", quote_identifier(name), col_type.unwrap_or("TEXT"))).join("");
+        tx.execute_batch(format!("CREATE TABLE {} (ocel_id TEXT, ocel_time TIMESTAMP, ocel_changed_field TEXT{});", quote_identifier(&table), column_defs).as_str())?;
+        object_tables.insert(obj_type, table);
+    }
+
+    for oid in log.objects.keys().sorted() {
+        let obj = &log.objects[oid];
+        let oid_str = log.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)))?;
+        tx.execute(format!("INSERT INTO {} (ocel_id, ocel_type) VALUES (?1, ?2)", quote_identifier(OBJECT_TABLE)).as_str(), (oid_str, log.object_type(obj)))?;
+
+        let columns = &object_types[log.object_type(obj)];
+        let mut values: Vec<SqlValue> = vec![SqlValue::Text(oid_str.to_owned()), SqlValue::Text(STATIC_ATTRIBUTE_TIME.to_string()), SqlValue::Null];
+        values.extend(columns.keys().map(|key| obj.ovmap.get(*key).map(json_to_sql).unwrap_or(SqlValue::Null)));
+        let placeholders = (1..=values.len()).map(|i| format!("?{}", i)).join(", ");
+        let column_list = ["ocel_id", "ocel_time", "ocel_changed_field"].into_iter().chain(columns.keys().copied()).map(quote_identifier).join(", ");
+        let insert = format!("INSERT INTO {} ({}) VALUES ({})", quote_identifier(&object_tables[log.object_type(obj)]), column_list, placeholders);
+        tx.execute(insert.as_str(), params_from_iter(values))?;
+
+        for (key, changes) in obj.history.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            for (time, value) in changes {
+                let mut values: Vec<SqlValue> = vec![SqlValue::Text(oid_str.to_owned()), SqlValue::Text(time.to_rfc3339()), SqlValue::Text(key.to_owned())];
+                values.extend(columns.keys().map(|column| if column == key {json_to_sql(value)} else {SqlValue::Null}));
+                tx.execute(insert.as_str(), params_from_iter(values))?;
+            }
+        }
+    }
+
+    for eid in log.events.keys().sorted() {
+        let ev = &log.events[eid];
+        let eid_str = log.event_map.get_by_right(eid).ok_or_else(|| Error::ReferentialIntegrity(format!("Event {} is missing from the event map.", eid)))?;
+        tx.execute(format!("INSERT INTO {} (ocel_id, ocel_type) VALUES (?1, ?2)", quote_identifier(EVENT_TABLE)).as_str(), (eid_str, log.activity(ev)))?;
+
+        let columns = &event_types[log.activity(ev)];
+        let mut values: Vec<SqlValue> = vec![SqlValue::Text(eid_str.to_owned()), SqlValue::Text(ev.export_timestamp(options.keep_offsets).to_rfc3339())];
+        values.extend(columns.keys().map(|key| ev.vmap.get(*key).map(json_to_sql).unwrap_or(SqlValue::Null)));
+        let placeholders = (1..=values.len()).map(|i| format!("?{}", i)).join(", ");
+        let column_list = ["ocel_id", "ocel_time"].into_iter().chain(columns.keys().copied()).map(quote_identifier).join(", ");
+        tx.execute(format!("INSERT INTO {} ({}) VALUES ({})", quote_identifier(&event_tables[log.activity(ev)]), column_list, placeholders).as_str(), params_from_iter(values))?;
+
+        for oid in ev.omap.iter().sorted() {
+            let oid_str = log.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)))?;
+            let unqualified = [String::new()];
+            let qualifiers = ev.qualifiers.get(oid).map(|qualifiers| qualifiers.as_slice()).unwrap_or(&unqualified);
+            for qualifier in qualifiers {
+                tx.execute(format!("INSERT INTO {} (ocel_event_id, ocel_object_id, ocel_qualifier) VALUES (?1, ?2, ?3)", quote_identifier(EVENT_OBJECT_TABLE)).as_str(), (eid_str, oid_str, qualifier))?;
+            }
+        }
+    }
+
+    for oid in log.o2o.keys().sorted() {
+        let source = log.object_map.get_by_right(oid).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)))?;
+        for link in &log.o2o[oid] {
+            let target = log.object_map.get_by_right(&link.target).ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", link.target)))?;
+            tx.execute(format!("INSERT INTO {} (ocel_source_id, ocel_target_id, ocel_qualifier) VALUES (?1, ?2, ?3)", quote_identifier(OBJECT_OBJECT_TABLE)).as_str(), (source, target, &link.qualifier))?;
+        }
+    }
+
+    tx.commit()?;
+
+    Ok(true)
+}
@@ -0,0 +1,112 @@
+use crate::objects::ocel::Ocel;
+use crate::objects::ocel::variants::xmlocel::{LIST_ITEM_KEY, write_attributes, write_relationships, write_typed, write_value};
+use crate::objects::ocel::exporter::ExportOptions;
+use super::check_history;
+use itertools::Itertools;
+use quick_xml::events::{BytesDecl, Event};
+use quick_xml::Writer;
+use std::{fs::OpenOptions, io::{BufWriter, Write}};
+use crate::Error;
+
+pub(crate) fn export_xml_ocel(log: &Ocel, file_path: &str, options: &ExportOptions) -> Result<bool, Error> {
+    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path)?;
+    export_xml_ocel_to_writer(log, BufWriter::new(output_file), options)
+}
+
+pub(crate) fn export_xml_ocel_to_writer<W: Write>(log: &Ocel, writer: W, options: &ExportOptions) -> Result<bool, Error> {
+    let mut writer = Writer::new_with_indent(writer, b' ', 2);
+    write_xml_ocel(log, &mut writer, options)?;
+    writer.into_inner().flush()?;
+
+    Ok(true)
+}
+
+/// Fail before anything is written if an event or object has no id in the maps of the log.
+fn check_references(log: &Ocel) -> Result<(), Error> {
+    if let Some(eid) = log.events.keys().find(|eid| !log.event_map.contains_right(eid)) {
+        return Err(Error::ReferentialIntegrity(format!("Event {} is missing from the event map.", eid)));
+    }
+    if let Some(oid) = log.objects.keys().find(|oid| !log.object_map.contains_right(oid)) {
+        return Err(Error::ReferentialIntegrity(format!("Object {} is missing from the object map.", oid)));
+    }
+    for (eid, ev) in &log.events {
+        if let Some(oid) = ev.omap.iter().chain(ev.qualifiers.keys()).find(|oid| !log.object_map.contains_right(oid)) {
+            return Err(Error::ReferentialIntegrity(format!("Event {} references the unknown object {}.", log.event_map.get_by_right(eid).expect("checked above"), oid)));
+        }
+    }
+    for oid in log.objects.keys() {
+        if let Some(link) = log.o2o.get(oid).into_iter().flatten().find(|link| !log.object_map.contains_right(&link.target)) {
+            return Err(Error::ReferentialIntegrity(format!("Object {} is linked to the unknown object {}.", log.object_map.get_by_right(oid).expect("checked above"), link.target)));
+        }
+    }
+    Ok(())
+}
+
+fn write_xml_ocel<W: Write>(log: &Ocel, writer: &mut Writer<W>, options: &ExportOptions) -> Result<(), Error> {
+    check_references(log)?;
+    check_history(log, "xmlocel", options)?;
+    let oname = |oid: &usize| log.object_map.get_by_right(oid).expect("checked above").as_str();
+
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer.create_element("log").write_inner_content(|w| {
+        for (scope, global) in [("log", &log.global_log), ("event", &log.global_event), ("object", &log.global_object)] {
+            w.create_element("global").with_attribute(("scope", scope)).write_inner_content(|w| {
+                for (key, value) in global.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+                    let key = key.strip_prefix("ocel:").unwrap_or(key);
+                    let item_key = match key {
+                        "attribute-names" => "attribute-name",
+                        "object-types" => "object-type",
+                        _ => LIST_ITEM_KEY
+                    };
+                    write_value(w, key, value, item_key)?;
+                }
+                Ok(())
+            })?;
+        }
+
+        w.create_element("events").write_inner_content(|w| {
+            for eid in log.events.keys().sorted() {
+                let ev = &log.events[eid];
+                let eid_str = log.event_map.get_by_right(eid).expect("checked above");
+                w.create_element("event").write_inner_content(|w| {
+                    write_typed(w, "string", "id", eid_str)?;
+                    write_typed(w, "string", "activity", log.activity(ev))?;
+                    write_typed(w, "date", "timestamp", &ev.export_timestamp(options.keep_offsets).to_rfc3339())?;
+                    w.create_element("list").with_attribute(("key", "omap")).write_inner_content(|w| {
+                        for oid in ev.omap.iter().sorted() {
+                            write_typed(w, "string", "object-id", oname(oid))?;
+                        }
+                        Ok(())
+                    })?;
+                    let qualified = ev.qualifiers.iter().sorted_by_key(|(oid, _)| **oid)
+                                                 .flat_map(|(oid, qualifiers)| qualifiers.iter().map(move |q| (oname(oid), q.as_str())))
+                                                 .collect();
+                    write_relationships(w, "qualifiers", qualified)?;
+                    write_attributes(w, "vmap", &ev.vmap)
+                })?;
+            }
+            Ok(())
+        })?;
+
+        w.create_element("objects").write_inner_content(|w| {
+            for oid in log.objects.keys().sorted() {
+                let obj = &log.objects[oid];
+                let oid_str = oname(oid);
+                w.create_element("object").write_inner_content(|w| {
+                    write_typed(w, "string", "id", oid_str)?;
+                    write_typed(w, "string", "type", log.object_type(obj))?;
+                    let links = log.o2o.get(oid).into_iter().flatten()
+                                       .map(|link| (oname(&link.target), link.qualifier.as_str()))
+                                       .collect();
+                    write_relationships(w, "o2o", links)?;
+                    write_attributes(w, "ovmap", &obj.ovmap)
+                })?;
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
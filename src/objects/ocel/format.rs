@@ -0,0 +1,250 @@
+use crate::Error;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use crate::objects::ocel::Ocel;
+
+/// Number of leading bytes looked at when sniffing the format of a file.
+const SNIFF_LENGTH: usize = 4096;
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+/// Top-level keys of OCEL 2.0 json, all keys of OCEL 1.0 json start with "ocel:".
+const JSON_OCEL2_KEYS: [&[u8]; 4] = [b"objectTypes", b"eventTypes", b"objects", b"events"];
+/// Longest top-level key that is kept while scanning, longer ones belong to neither format.
+const MAX_KEY_LENGTH: usize = 64;
+
+lazy_static::lazy_static!{
+    static ref OCEL_FORMATS: RwLock<Vec<Arc<dyn OcelFormatHandler>>> = RwLock::new(vec![]);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcelFormat {
+    JsonOcel,
+    XmlOcel,
+    JsonOcel2,
+    SqliteOcel2,
+    /// A format registered with `register_ocel_format`, referenced by its name.
+    Custom(String)
+}
+
+/// Import and export of a log format that is not part of the crate.
+pub trait OcelFormatHandler: Send + Sync {
+    fn name(&self) -> &str;
+    /// File extensions without the leading dot.
+    fn extensions(&self) -> Vec<&str>;
+    /// Whether the first bytes of a file belong to this format.
+    fn sniff(&self, _head: &[u8]) -> bool {
+        false
+    }
+    fn import(&self, file_path: &str) -> Result<Ocel, Error>;
+    fn export(&self, log: &Ocel, file_path: &str) -> Result<bool, Error>;
+    fn import_from_reader(&self, _reader: &mut dyn Read) -> Result<Ocel, Error> {
+        Err(Error::UnsupportedFormat(format!("The ocel format {} can only be imported from a file.", self.name())))
+    }
+    fn export_to_writer(&self, _log: &Ocel, _writer: &mut dyn Write) -> Result<bool, Error> {
+        Err(Error::UnsupportedFormat(format!("The ocel format {} can only be exported to a file.", self.name())))
+    }
+}
+
+/// Register a custom format. A later registration with the same name replaces the earlier one.
+pub fn register_ocel_format(handler: Arc<dyn OcelFormatHandler>) {
+    let mut formats = OCEL_FORMATS.write().unwrap_or_else(|e| e.into_inner());
+    formats.retain(|f| f.name() != handler.name());
+    formats.push(handler);
+}
+
+pub(crate) fn ocel_format_handler(name: &str) -> Result<Arc<dyn OcelFormatHandler>, Error> {
+    let formats = OCEL_FORMATS.read().unwrap_or_else(|e| e.into_inner());
+    match formats.iter().find(|f| f.name() == name) {
+        Some(handler) => Ok(handler.clone()),
+        None => Err(Error::UnsupportedFormat(format!("The ocel format {} has not been registered.", name)))
+    }
+}
+
+impl OcelFormat {
+    /// Detect the format by file extension and fall back to the content of the file.
+    pub fn detect(file_path: &str) -> Result<OcelFormat, Error> {
+        if let Some(format) = OcelFormat::from_extension(file_path) {
+            return Ok(format);
+        }
+
+        let mut head: Vec<u8> = vec![];
+        File::open(file_path)?.take(SNIFF_LENGTH as u64).read_to_end(&mut head)?;
+        let format = match OcelFormat::sniff(&head) {
+            // the deciding key of a json log can come after the head
+            None if first_char(&head) == Some('{') => json_format(File::open(file_path)?),
+            format => format
+        };
+        format.ok_or_else(|| Error::UnsupportedFormat(format!("Could not detect the ocel format of {}.", file_path)))
+    }
+
+    /// Format belonging to the extension of a path. Plain `.json` files are ambiguous and return `None`.
+    pub fn from_extension(file_path: &str) -> Option<OcelFormat> {
+        let ext = Path::new(file_path).extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "jsonocel" => Some(OcelFormat::JsonOcel),
+            "xmlocel" | "xml" => Some(OcelFormat::XmlOcel),
+            "sqlite" | "sqlite3" | "db" => Some(OcelFormat::SqliteOcel2),
+            _ => {
+                let formats = OCEL_FORMATS.read().unwrap_or_else(|e| e.into_inner());
+                formats.iter()
+                       .find(|f| f.extensions().iter().any(|f_ext| f_ext.eq_ignore_ascii_case(&ext)))
+                       .map(|f| OcelFormat::Custom(f.name().to_string()))
+            }
+        }
+    }
+
+    /// Format belonging to the first bytes of a file.
+    pub fn sniff(head: &[u8]) -> Option<OcelFormat> {
+        {
+            let formats = OCEL_FORMATS.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(f) = formats.iter().find(|f| f.sniff(head)) {
+                return Some(OcelFormat::Custom(f.name().to_string()));
+            }
+        }
+
+        if head.starts_with(SQLITE_HEADER) {
+            return Some(OcelFormat::SqliteOcel2);
+        }
+
+        match first_char(head) {
+            Some('<') => Some(OcelFormat::XmlOcel),
+            Some('{') => json_format(head),
+            _ => None
+        }
+    }
+}
+
+fn first_char(head: &[u8]) -> Option<char> {
+    String::from_utf8_lossy(head).trim_start_matches('\u{feff}').trim_start().chars().next()
+}
+
+/// Json flavour decided by the first top-level key that belongs to one of the formats.
+/// Values are skipped without being parsed, so keys nested in them or in strings are not mistaken for top-level keys.
+fn json_format<R: Read>(reader: R) -> Option<OcelFormat> {
+    let mut depth: usize = 0;
+    let (mut in_string, mut escaped, mut expect_key) = (false, false, false);
+    // the top-level key that is being read
+    let mut key: Option<Vec<u8>> = None;
+
+    for byte in BufReader::new(reader).bytes() {
+        let byte = byte.ok()?;
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+                match key.take() {
+                    Some(name) if name.starts_with(b"ocel:") => return Some(OcelFormat::JsonOcel),
+                    Some(name) if JSON_OCEL2_KEYS.contains(&name.as_slice()) => return Some(OcelFormat::JsonOcel2),
+                    _ => {}
+                }
+                continue;
+            }
+            key = key.filter(|name| name.len() < MAX_KEY_LENGTH).map(|mut name| {name.push(byte); name});
+            continue;
+        }
+        match byte {
+            b'"' => {
+                in_string = true;
+                if depth == 1 && expect_key {
+                    key = Some(vec![]);
+                    expect_key = false;
+                }
+            },
+            b'{' | b'[' => {
+                depth += 1;
+                expect_key = depth == 1;
+            },
+            b'}' | b']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return None;
+                }
+            },
+            b',' => expect_key = depth == 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestFormat;
+
+    impl OcelFormatHandler for TestFormat {
+        fn name(&self) -> &str {
+            "test-format"
+        }
+
+        fn extensions(&self) -> Vec<&str> {
+            vec!["testocel"]
+        }
+
+        fn sniff(&self, head: &[u8]) -> bool {
+            head.starts_with(b"TESTOCEL")
+        }
+
+        fn import(&self, _file_path: &str) -> Result<Ocel, Error> {
+            Ok(Ocel::default())
+        }
+
+        fn export(&self, _log: &Ocel, _file_path: &str) -> Result<bool, Error> {
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn test_detect_by_extension() {
+        assert_eq!(OcelFormat::from_extension("logs/min.jsonocel"), Some(OcelFormat::JsonOcel));
+        assert_eq!(OcelFormat::from_extension("log.XMLOCEL"), Some(OcelFormat::XmlOcel));
+        assert_eq!(OcelFormat::from_extension("log.sqlite"), Some(OcelFormat::SqliteOcel2));
+        assert_eq!(OcelFormat::from_extension("logs/ocel2-test.json"), None);
+        assert_eq!(OcelFormat::from_extension("log"), None);
+    }
+
+    #[test]
+    fn test_detect_by_content() {
+        assert_eq!(OcelFormat::detect("logs/ocel2-test.json").unwrap(), OcelFormat::JsonOcel2);
+        assert_eq!(OcelFormat::sniff(br#"{"ocel:global-log": {}}"#), Some(OcelFormat::JsonOcel));
+        assert_eq!(OcelFormat::sniff(b"<?xml version=\"1.0\"?><log>"), Some(OcelFormat::XmlOcel));
+        assert_eq!(OcelFormat::sniff(b"SQLite format 3\0rest"), Some(OcelFormat::SqliteOcel2));
+        assert_eq!(OcelFormat::sniff(b"id,activity"), None);
+    }
+
+    #[test]
+    fn test_detect_by_top_level_keys() {
+        let padded = format!(r#"{{"meta": "{}", "objectTypes": [], "eventTypes": [], "objects": [], "events": []}}"#, "x".repeat(2 * SNIFF_LENGTH));
+        assert_eq!(OcelFormat::sniff(padded.as_bytes()), Some(OcelFormat::JsonOcel2));
+        assert_eq!(OcelFormat::sniff(br#"{"meta": {"ocel:note": "\"ocel:"}, "objects": []}"#), Some(OcelFormat::JsonOcel2));
+        assert_eq!(OcelFormat::sniff(br#"{"note": "objects", "ocel:events": {}}"#), Some(OcelFormat::JsonOcel));
+        assert_eq!(OcelFormat::sniff(br#"{"id": 1, "activity": "a"}"#), None);
+
+        let path = std::env::temp_dir().join("pmrs-detect-padded.json");
+        std::fs::write(&path, padded).unwrap();
+        assert_eq!(OcelFormat::detect(path.to_str().unwrap()).unwrap(), OcelFormat::JsonOcel2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_import_detected_format() {
+        let log = crate::objects::ocel::importer::import_ocel("logs/ocel2-test.json").expect("What did you do to the file?");
+        assert_eq!(log.events.len(), 3);
+    }
+
+    #[test]
+    fn test_custom_format() {
+        register_ocel_format(Arc::new(TestFormat));
+        assert_eq!(OcelFormat::from_extension("log.testocel"), Some(OcelFormat::Custom("test-format".to_string())));
+        assert_eq!(OcelFormat::sniff(b"TESTOCEL v1"), Some(OcelFormat::Custom("test-format".to_string())));
+        assert!(ocel_format_handler("test-format").is_ok());
+        assert!(ocel_format_handler("unknown-format").is_err());
+    }
+}
@@ -1,8 +1,299 @@
 pub(crate) mod variants;
 use crate::objects::ocel::Ocel;
-use crate::objects::ocel::importer::variants::jsonocel::import_json_ocel;
-use std::error::Error;
+use crate::objects::ocel::format::{OcelFormat, ocel_format_handler};
+use crate::objects::ocel::importer::variants::jsonocel::{import_json_ocel, import_json_ocel_from_reader, import_json_ocel_from_slice};
+use crate::objects::ocel::importer::variants::jsonocel2::{import_json_ocel2, import_json_ocel2_from_reader};
+use crate::objects::ocel::importer::variants::sqliteocel::import_sqlite_ocel;
+use crate::objects::ocel::importer::variants::table::{import_csv_table, import_csv_table_from_reader, import_dataframe_table};
+use crate::objects::ocel::importer::variants::xmlocel::{import_xml_ocel, import_xml_ocel_from_reader};
+use chrono::{FixedOffset, Offset, Utc};
+use polars::prelude::DataFrame;
+use crate::objects::ocel::attributes::ConversionReport;
+use crate::Error;
+use std::fs::File;
+use std::io::Read;
 
-pub fn import_ocel(file_path: &str) -> Result<Ocel, Box<dyn Error>> {
-    return import_json_ocel(file_path);
+/// State of a running import, handed to the progress callback of the streaming importer.
+#[derive(Debug, Clone, Default)]
+pub struct ImportProgress {
+    pub bytes_read: u64,
+    pub objects: usize,
+    pub events: usize
+}
+
+/// How the log importers read timestamps.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Fail on timestamps that do not parse instead of replacing them with the Unix epoch.
+    pub strict_timestamps: bool,
+    /// chrono formats tried after RFC 3339, with or without an offset.
+    pub timestamp_formats: Vec<String>,
+    /// Offset of timestamps that are written without one.
+    pub default_offset: FixedOffset
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions { strict_timestamps: false, timestamp_formats: vec![], default_offset: Utc.fix() }
+    }
+}
+
+/// Values that were coerced while importing a log.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Timestamps without an offset that were read in the default offset.
+    pub naive_timestamps: usize,
+    /// Timestamps that did not parse and were replaced by the Unix epoch.
+    pub invalid_timestamps: usize,
+    /// Attributes with values of different types, found while inferring the schema.
+    pub attributes: ConversionReport,
+    /// Rows of an event table that were left out of the log.
+    pub skipped_rows: Vec<RowError>
+}
+
+impl ImportReport {
+    pub fn coerced(&self) -> usize {
+        self.naive_timestamps + self.invalid_timestamps
+    }
+}
+
+/// Columns of an event table and how they map to a log. Every row is an event.
+#[derive(Debug, Clone)]
+pub struct ColumnMapping {
+    /// Column holding the event id, the row number is used if there is none.
+    pub event_id: Option<String>,
+    pub activity: String,
+    pub timestamp: String,
+    /// How the timestamp column is read.
+    pub timestamps: ImportOptions,
+    /// Pairs of column and object type, each cell holds a list of object ids.
+    pub object_columns: Vec<(String, String)>,
+    /// Columns copied into the vmap of the events.
+    pub attribute_columns: Vec<String>,
+    pub id_separator: char
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping { event_id: None, activity: "activity".to_string(), timestamp: "timestamp".to_string(), timestamps: ImportOptions::default(),
+                        object_columns: vec![], attribute_columns: vec![], id_separator: ',' }
+    }
+}
+
+/// A row of an event table that was left out of the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    pub row: usize,
+    pub reason: String
+}
+
+/// Import a log in the format detected from its extension or content.
+pub fn import_ocel(file_path: &str) -> Result<Ocel, Error> {
+    import_ocel_as(file_path, OcelFormat::detect(file_path)?)
+}
+
+pub fn import_ocel_as(file_path: &str, format: OcelFormat) -> Result<Ocel, Error> {
+    Ok(import_ocel_with_options(file_path, format, &ImportOptions::default())?.0)
+}
+
+/// Import a log with the given handling of timestamps, along with a report of the values that were coerced.
+/// Custom formats read their timestamps themselves and report nothing.
+pub fn import_ocel_with_options(file_path: &str, format: OcelFormat, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    match format {
+        OcelFormat::JsonOcel => import_json_ocel(file_path, options),
+        OcelFormat::XmlOcel => import_xml_ocel(file_path, options),
+        OcelFormat::JsonOcel2 => import_json_ocel2(file_path, options),
+        OcelFormat::SqliteOcel2 => import_sqlite_ocel(file_path, options),
+        OcelFormat::Custom(name) => Ok((reindexed(ocel_format_handler(&name)?.import(file_path)?), ImportReport::default()))
+    }
+}
+
+pub fn import_ocel_from_reader<R: Read>(reader: R, format: OcelFormat) -> Result<Ocel, Error> {
+    Ok(import_ocel_from_reader_with_options(reader, format, &ImportOptions::default())?.0)
+}
+
+pub fn import_ocel_from_reader_with_options<R: Read>(mut reader: R, format: OcelFormat, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    match format {
+        OcelFormat::JsonOcel => import_json_ocel_from_reader(reader, None, options),
+        OcelFormat::XmlOcel => import_xml_ocel_from_reader(reader, options),
+        OcelFormat::JsonOcel2 => import_json_ocel2_from_reader(reader, options),
+        OcelFormat::SqliteOcel2 => Err(Error::UnsupportedFormat("sqlite logs can only be imported from a file.".to_string())),
+        OcelFormat::Custom(name) => Ok((reindexed(ocel_format_handler(&name)?.import_from_reader(&mut reader)?), ImportReport::default()))
+    }
+}
+
+/// Logs of custom formats get the same time-ordered event ids and schema as the built-in ones.
+fn reindexed(mut log: Ocel) -> Ocel {
+    log.reindex_chronologically();
+    log.infer_schema();
+    log
+}
+
+/// Import a log held in memory, the format is detected from its content.
+pub fn import_ocel_from_slice(bytes: &[u8]) -> Result<Ocel, Error> {
+    match OcelFormat::sniff(bytes).ok_or_else(|| Error::UnsupportedFormat("Could not detect the ocel format of the input.".to_string()))? {
+        OcelFormat::JsonOcel => Ok(import_json_ocel_from_slice(bytes, &ImportOptions::default())?.0),
+        format => import_ocel_from_reader(bytes, format)
+    }
+}
+
+pub fn import_ocel_from_str(s: &str) -> Result<Ocel, Error> {
+    import_ocel_from_slice(s.as_bytes())
+}
+
+/// Import a jsonocel log without holding its serialized form in memory.
+/// The callback is called every few thousand objects and events and once at the end.
+pub fn import_ocel_streaming(file_path: &str, progress: Option<&mut dyn FnMut(&ImportProgress)>) -> Result<Ocel, Error> {
+    Ok(import_json_ocel_from_reader(File::open(file_path)?, progress, &ImportOptions::default())?.0)
+}
+
+pub fn import_ocel_streaming_from_reader<R: Read>(reader: R, progress: Option<&mut dyn FnMut(&ImportProgress)>) -> Result<Ocel, Error> {
+    Ok(import_json_ocel_from_reader(reader, progress, &ImportOptions::default())?.0)
+}
+
+pub fn import_ocel_xml(file_path: &str) -> Result<Ocel, Error> {
+    import_ocel_as(file_path, OcelFormat::XmlOcel)
+}
+
+pub fn import_ocel2(file_path: &str) -> Result<Ocel, Error> {
+    import_ocel_as(file_path, OcelFormat::JsonOcel2)
+}
+
+pub fn import_ocel2_sqlite(file_path: &str) -> Result<Ocel, Error> {
+    import_ocel_as(file_path, OcelFormat::SqliteOcel2)
+}
+
+/// Import an event table from a csv file with a header row.
+/// Rows that could not be parsed are left out of the log and listed in the report.
+pub fn import_csv_ocel(file_path: &str, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    import_csv_table(file_path, mapping)
+}
+
+pub fn import_csv_ocel_from_reader<R: Read>(reader: R, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    import_csv_table_from_reader(reader, mapping)
+}
+
+/// Import an event table from a parquet file, needs the `parquet` feature.
+#[cfg(feature = "parquet")]
+pub fn import_parquet_ocel(file_path: &str, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    variants::table::import_parquet_table(file_path, mapping)
+}
+
+/// Import an event table that is already loaded.
+pub fn import_dataframe_ocel(df: &DataFrame, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    import_dataframe_table(df, mapping)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ocel::exporter::{export_ocel_to_string, export_ocel_to_writer_with_options, export_ocel2_sqlite, ExportOptions};
+    use std::collections::BTreeSet;
+    use crate::objects::ocel::validator::{validate_ocel, validate_ocel_from_reader};
+
+    #[test]
+    fn test_in_memory_round_trip() {
+        let log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        for format in [OcelFormat::JsonOcel, OcelFormat::XmlOcel, OcelFormat::JsonOcel2] {
+            let s = export_ocel_to_string(&log, format.clone()).expect("could not export the log");
+            let reimported = import_ocel_from_str(&s).expect("could not import the exported log");
+            assert_eq!(log.events.len(), reimported.events.len());
+            assert_eq!(log.objects.len(), reimported.objects.len());
+
+            let reimported = import_ocel_from_reader(s.as_bytes(), format).expect("could not import the exported log");
+            for (eid_str, eid) in log.event_map.iter() {
+                let ev = &reimported.events[reimported.event_map.get_by_left(eid_str).unwrap()];
+                assert_eq!(log.activity(&log.events[eid]), reimported.activity(ev));
+                assert_eq!(log.events[eid].timestamp, ev.timestamp);
+            }
+        }
+    }
+
+    #[test]
+    fn test_in_memory_validation() {
+        let s = std::fs::read_to_string("logs/min.jsonocel").expect("What did you do to the file?");
+        assert_eq!(validate_ocel_from_reader(s.as_bytes()).unwrap(), validate_ocel("logs/min.jsonocel").unwrap());
+        assert!(validate_ocel_from_reader(&b"{not json"[..]).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_sqlite_fails() {
+        assert!(import_ocel_from_reader("".as_bytes(), OcelFormat::SqliteOcel2).is_err());
+    }
+
+    #[test]
+    fn test_keep_offsets() {
+        let input = r#"{"ocel:global-log": {}, "ocel:global-event": {}, "ocel:global-object": {}, "ocel:objects": {},
+            "ocel:events": {"e1": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T10:00:00+02:00", "ocel:omap": [], "ocel:vmap": {}}}}"#;
+        let log = import_ocel_from_str(input).unwrap();
+        let keep = ExportOptions { keep_offsets: true, ..Default::default() };
+        for format in [OcelFormat::JsonOcel, OcelFormat::XmlOcel, OcelFormat::JsonOcel2] {
+            assert!(!export_ocel_to_string(&log, format.clone()).unwrap().contains("+02:00"));
+
+            let mut buf: Vec<u8> = vec![];
+            export_ocel_to_writer_with_options(&log, &mut buf, format.clone(), &keep).unwrap();
+            assert!(String::from_utf8(buf.clone()).unwrap().contains("2022-01-01T10:00:00+02:00"));
+            let reimported = import_ocel_from_reader(buf.as_slice(), format).unwrap();
+            assert_eq!(reimported.events[&0].timestamp, log.events[&0].timestamp);
+            assert_eq!(reimported.events[&0].offset, log.events[&0].offset);
+        }
+    }
+
+    #[test]
+    fn test_export_inconsistent_log() {
+        let mut log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        log.events_mut().values_mut().next().unwrap().omap.insert(usize::MAX);
+        for format in [OcelFormat::JsonOcel, OcelFormat::XmlOcel, OcelFormat::JsonOcel2] {
+            assert!(matches!(export_ocel_to_string(&log, format), Err(Error::ReferentialIntegrity(_))));
+        }
+    }
+
+    #[test]
+    fn test_export_history_strict() {
+        let mut log = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+        let changed_at = log.events.values().map(|ev| ev.timestamp).max().unwrap();
+        log.objects_mut().values_mut().next().unwrap().history.insert("status".to_string(), vec![(changed_at, serde_json::json!("shipped"))]);
+        let strict = ExportOptions { strict_history: true, ..Default::default() };
+        for format in [OcelFormat::JsonOcel, OcelFormat::XmlOcel] {
+            assert!(export_ocel_to_string(&log, format.clone()).is_ok());
+            assert!(matches!(export_ocel_to_writer_with_options(&log, vec![], format, &strict), Err(Error::UnsupportedFormat(_))));
+        }
+        assert!(export_ocel_to_writer_with_options(&log, vec![], OcelFormat::JsonOcel2, &strict).is_ok());
+    }
+
+    type NamedLinks = BTreeSet<(String, String, String)>;
+
+    /// Qualified links by the names of their events and objects.
+    fn named_links(log: &Ocel) -> (NamedLinks, NamedLinks) {
+        let oname = |oid: &usize| log.object_map.get_by_right(oid).unwrap().to_owned();
+        let e2o = log.events.iter().flat_map(|(eid, ev)| ev.qualifiers.iter().flat_map(move |(oid, qualifiers)| qualifiers.iter().map(move |q| (*eid, *oid, q))))
+                                   .map(|(eid, oid, q)| (log.event_map.get_by_right(&eid).unwrap().to_owned(), oname(&oid), q.to_owned()))
+                                   .collect();
+        let o2o = log.o2o.iter().flat_map(|(oid, links)| links.iter().map(move |link| (oid, link)))
+                                .map(|(oid, link)| (oname(oid), oname(&link.target), link.qualifier.to_owned()))
+                                .collect();
+        (e2o, o2o)
+    }
+
+    #[test]
+    fn test_qualified_links_round_trip() {
+        let log = import_ocel("logs/ocel2-test.json").unwrap();
+        let (e2o, o2o) = named_links(&log);
+        assert!(e2o.contains(&("e1".to_string(), "o1".to_string(), "order".to_string())));
+        assert_eq!(o2o, BTreeSet::from([("o1".to_string(), "i1".to_string(), "contains".to_string())]));
+
+        for format in [OcelFormat::JsonOcel, OcelFormat::XmlOcel, OcelFormat::JsonOcel2] {
+            let exported = export_ocel_to_string(&log, format.clone()).unwrap();
+            let reimported = import_ocel_from_reader(exported.as_bytes(), format).unwrap();
+            assert_eq!(named_links(&reimported), (e2o.clone(), o2o.clone()));
+        }
+
+        let export_path = std::env::temp_dir().join("pmrs-qualified-links.sqlite");
+        export_ocel2_sqlite(&log, export_path.to_str().unwrap()).unwrap();
+        assert_eq!(named_links(&import_ocel(export_path.to_str().unwrap()).unwrap()), (e2o.clone(), o2o.clone()));
+
+        let from_frames = Ocel::from_dataframes(&log.to_dataframes().unwrap()).unwrap();
+        assert_eq!(named_links(&from_frames), (e2o, o2o));
+    }
 }
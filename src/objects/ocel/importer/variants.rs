@@ -1 +1,5 @@
 pub(super) mod jsonocel;
+pub(super) mod jsonocel2;
+pub(super) mod sqliteocel;
+pub(super) mod xmlocel;
+pub(super) mod table;
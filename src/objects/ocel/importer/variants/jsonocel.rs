@@ -1,42 +1,308 @@
-use crate::objects::ocel::{Ocel, OcelSerde, OcelEvent, OcelObject};
-use ahash::AHashSet;
-use bimap::BiMap;
+use crate::objects::ocel::{Ocel, OcelEvent, OcelObject, AttributeHistory, OcelEventSerde, OcelObjectSerde, TimestampParser};
+use crate::objects::ocel::importer::{ImportOptions, ImportProgress, ImportReport};
 use nohash_hasher::{IntMap, IntSet};
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+use std::cell::Cell;
+use std::fmt;
 use std::fs::File;
-use std::io::Read;
-use std::error::Error;
-
-pub(crate) fn import_json_ocel(file_path: &str) -> Result<Ocel, Box<dyn Error>> {
-    let mut s = String::new();
-    File::open(file_path)?.read_to_string(&mut s)?;
-    let log: OcelSerde = serde_json::from_str(&s)?;
-    let mut log_internal: Ocel = Ocel { global_log: log.global_log, global_event: log.global_event, global_object: log.global_object, events: IntMap::default() , objects: IntMap::default(), object_map: BiMap::new(), event_map: BiMap::new(), activities: vec![] };
-    let mut activity_set: AHashSet<String> = AHashSet::new();
-    
-    let mut oid_nh: usize = usize::MIN; 
-    for (oid, data) in log.objects {
-        log_internal.object_map.insert(oid, oid_nh);
-        log_internal.objects.insert(oid_nh, OcelObject {obj_type: data.obj_type, ovmap: data.ovmap, events: vec![] });
-        oid_nh = oid_nh + 1;
-    }
-
-    let mut eid_nh: usize = usize::MIN;
-    for (eid, data) in log.events {
-        activity_set.insert(data.activity.clone());
-        let mut fast_event = OcelEvent {activity: data.activity, timestamp: data.timestamp, vmap: data.vmap, omap: IntSet::default()};
-        log_internal.event_map.insert(eid, eid_nh);
-
-        for oid in data.omap.iter() {
-            let oid_num = *log_internal.object_map.get_by_left(oid as &str).expect("This was just added so it cannot fail.");
-            fast_event.omap.insert(oid_num);
-            log_internal.objects.get_mut(&oid_num).unwrap().events.push(eid_nh);
-        }
-
-        log_internal.events.insert(eid_nh, fast_event);
-        eid_nh = eid_nh + 1;
-    }
-
-    log_internal.activities.extend(activity_set);
-    
-    Ok(log_internal)
+use std::io::{BufReader, Read};
+use crate::Error;
+use std::rc::Rc;
+
+/// Number of objects and events between two progress reports.
+const PROGRESS_INTERVAL: usize = 10_000;
+
+pub(crate) type ProgressCallback<'p> = Option<&'p mut dyn FnMut(&ImportProgress)>;
+
+pub(crate) fn import_json_ocel(file_path: &str, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    import_json_ocel_from_reader(File::open(file_path)?, None, options)
+}
+
+/// Fill the log while walking through the json, so only a single object or event
+/// is held in its serialized form at any time.
+pub(crate) fn import_json_ocel_from_reader<R: Read>(reader: R, progress: ProgressCallback, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    let bytes_read: Rc<Cell<u64>> = Rc::new(Cell::new(0));
+    let counting = CountingReader { inner: BufReader::new(reader), bytes_read: bytes_read.clone() };
+    let mut de = serde_json::Deserializer::from_reader(counting);
+
+    let mut state = StreamState::new(progress, bytes_read, options);
+    let result = (&mut de).deserialize_map(LogVisitor { state: &mut state });
+    state.check(result)?;
+    de.end()?;
+    state.finish()
+}
+
+pub(crate) fn import_json_ocel_from_slice(bytes: &[u8], options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    let mut de = serde_json::Deserializer::from_slice(bytes);
+
+    let mut state = StreamState::new(None, Rc::new(Cell::new(bytes.len() as u64)), options);
+    let result = (&mut de).deserialize_map(LogVisitor { state: &mut state });
+    state.check(result)?;
+    de.end()?;
+    state.finish()
+}
+
+struct CountingReader<R: Read> {
+    inner: R,
+    bytes_read: Rc<Cell<u64>>
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + n as u64);
+        Ok(n)
+    }
+}
+
+struct StreamState<'p, 'o> {
+    log: Ocel,
+    // objects that events referenced before the object itself was read
+    undeclared: IntSet<usize>,
+    next_oid: usize,
+    entries_read: usize,
+    progress: ProgressCallback<'p>,
+    bytes_read: Rc<Cell<u64>>,
+    timestamps: TimestampParser<'o>,
+    // serde only carries a message, the actual error is kept here
+    error: Option<Error>
+}
+
+impl<'p, 'o> StreamState<'p, 'o> {
+    fn new(progress: ProgressCallback<'p>, bytes_read: Rc<Cell<u64>>, options: &'o ImportOptions) -> Self {
+        StreamState { log: Ocel::default(), undeclared: IntSet::default(), next_oid: 0, entries_read: 0, progress, bytes_read,
+                      timestamps: TimestampParser::new(options), error: None }
+    }
+
+    fn object_number(&mut self, oid: &str) -> usize {
+        match self.log.object_map.get_by_left(oid) {
+            Some(oid_nh) => *oid_nh,
+            None => {
+                let oid_nh = self.next_oid;
+                self.next_oid += 1;
+                self.log.object_map.insert(oid.to_owned(), oid_nh);
+                oid_nh
+            }
+        }
+    }
+
+    /// Number of a referenced object, objects that have not been read yet are declared later.
+    fn referenced_object(&mut self, oid: &str) -> usize {
+        match self.log.object_map.get_by_left(oid) {
+            Some(oid_nh) => *oid_nh,
+            None => {
+                let oid_nh = self.object_number(oid);
+                self.undeclared.insert(oid_nh);
+                // the type is set once the object is declared
+                self.log.objects.insert(oid_nh, OcelObject { obj_type: 0, ovmap: Default::default(), events: vec![], history: AttributeHistory::new() });
+                oid_nh
+            }
+        }
+    }
+
+    fn add_object(&mut self, oid: String, data: OcelObjectSerde) -> Result<(), String> {
+        let is_new = self.log.object_map.get_by_left(&oid).is_none();
+        let oid_nh = self.object_number(&oid);
+        if !is_new && !self.undeclared.remove(&oid_nh) {
+            return Err(self.fail(Error::ReferentialIntegrity(format!("Object id {} is declared more than once.", oid))));
+        }
+
+        let obj_type = self.log.intern_object_type(&data.obj_type);
+        let obj = self.log.objects.entry(oid_nh).or_insert(OcelObject { obj_type, ovmap: Default::default(), events: vec![], history: AttributeHistory::new() });
+        obj.obj_type = obj_type;
+        obj.ovmap = data.ovmap;
+        for link in data.o2o {
+            let target = self.referenced_object(&link.object_id);
+            self.log.link_object(oid_nh, target, &link.qualifier);
+        }
+        self.report_progress(false);
+        Ok(())
+    }
+
+    fn add_event(&mut self, eid: String, data: OcelEventSerde) -> Result<(), String> {
+        let eid_nh = self.log.events.len();
+        if self.log.event_map.insert_no_overwrite(eid.to_owned(), eid_nh).is_err() {
+            return Err(self.fail(Error::ReferentialIntegrity(format!("Event id {} is declared more than once.", eid))));
+        }
+        let (timestamp, offset) = self.timestamps.parse(&data.timestamp).map_err(|e| self.fail(e))?;
+        let activity = self.log.intern_activity(&data.activity);
+
+        let mut fast_event = OcelEvent { activity, timestamp, vmap: data.vmap, omap: IntSet::default(), offset, qualifiers: IntMap::default() };
+        let qualified = data.qualifiers.iter().map(|rel| (&rel.object_id, rel.qualifier.as_str()));
+        for (oid, qualifier) in data.omap.iter().map(|oid| (oid, "")).chain(qualified) {
+            let oid_nh = self.referenced_object(oid);
+            if fast_event.omap.insert(oid_nh) {
+                self.log.objects.get_mut(&oid_nh).expect("referenced objects are part of the log").events.push(eid_nh);
+            }
+            fast_event.qualify(oid_nh, qualifier);
+        }
+
+        self.log.events.insert(eid_nh, fast_event);
+        self.report_progress(false);
+        Ok(())
+    }
+
+    fn fail(&mut self, e: Error) -> String {
+        let msg = e.to_string();
+        self.error = Some(e);
+        msg
+    }
+
+    fn check(&mut self, result: Result<(), serde_json::Error>) -> Result<(), Error> {
+        match (result, self.error.take()) {
+            (Err(_), Some(e)) => Err(e),
+            (result, _) => Ok(result?)
+        }
+    }
+
+    fn report_progress(&mut self, force: bool) {
+        if !force {
+            self.entries_read += 1;
+        }
+        if let Some(callback) = self.progress.as_mut() {
+            if force || self.entries_read.is_multiple_of(PROGRESS_INTERVAL) {
+                let (objects, events) = (self.log.objects.len() - self.undeclared.len(), self.log.events.len());
+                callback(&ImportProgress { bytes_read: self.bytes_read.get(), objects, events });
+            }
+        }
+    }
+
+    fn finish(mut self) -> Result<(Ocel, ImportReport), Error> {
+        if let Some(oid_nh) = self.undeclared.iter().next() {
+            let oid = self.log.object_map.get_by_right(oid_nh).expect("object map is filled with the number");
+            return Err(Error::ReferentialIntegrity(format!("An event references the undeclared object {}.", oid)));
+        }
+        self.report_progress(true);
+        self.log.reindex_chronologically();
+        self.timestamps.report.attributes = self.log.infer_schema();
+        Ok((self.log, self.timestamps.report))
+    }
+}
+
+struct LogVisitor<'s, 'p, 'o> {
+    state: &'s mut StreamState<'p, 'o>
+}
+
+impl<'de, 's, 'p, 'o> Visitor<'de> for LogVisitor<'s, 'p, 'o> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a jsonocel log")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "ocel:global-log" => self.state.log.global_log = map.next_value()?,
+                "ocel:global-event" => self.state.log.global_event = map.next_value()?,
+                "ocel:global-object" => self.state.log.global_object = map.next_value()?,
+                "ocel:objects" => map.next_value_seed(EntriesSeed { state: self.state, events: false })?,
+                "ocel:events" => map.next_value_seed(EntriesSeed { state: self.state, events: true })?,
+                _ => {map.next_value::<IgnoredAny>()?;}
+            }
+        }
+        Ok(())
+    }
+}
+
+struct EntriesSeed<'s, 'p, 'o> {
+    state: &'s mut StreamState<'p, 'o>,
+    events: bool
+}
+
+impl<'de, 's, 'p, 'o> DeserializeSeed<'de> for EntriesSeed<'s, 'p, 'o> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 's, 'p, 'o> Visitor<'de> for EntriesSeed<'s, 'p, 'o> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of jsonocel {}", if self.events {"events"} else {"objects"})
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        use serde::de::Error as DeError;
+        if self.events {
+            while let Some((eid, data)) = map.next_entry::<String, OcelEventSerde>()? {
+                self.state.add_event(eid, data).map_err(A::Error::custom)?;
+            }
+        } else {
+            while let Some((oid, data)) = map.next_entry::<String, OcelObjectSerde>()? {
+                self.state.add_object(oid, data).map_err(A::Error::custom)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_events_before_objects() {
+        let log = import_json_ocel_from_slice(br#"{
+            "ocel:events": {"e1": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T10:00:00Z", "ocel:omap": ["o1"], "ocel:vmap": {}},
+                            "e2": {"ocel:activity": "b", "ocel:timestamp": "2022-01-01T11:00:00Z", "ocel:omap": ["o1", "o2"], "ocel:vmap": {}}},
+            "ocel:global-log": {}, "ocel:global-event": {}, "ocel:global-object": {},
+            "ocel:objects": {"o2": {"ocel:type": "item", "ocel:ovmap": {}}, "o1": {"ocel:type": "order", "ocel:ovmap": {"price": 3}}}
+        }"#, &ImportOptions::default()).expect("valid jsonocel").0;
+
+        let o1 = &log.objects[log.object_map.get_by_left("o1").unwrap()];
+        assert_eq!(log.object_type(o1), "order");
+        assert_eq!(o1.events, vec![0, 1]);
+        assert_eq!(log.activities(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_undeclared_object_fails() {
+        assert!(import_json_ocel_from_slice(br#"{"ocel:global-log": {}, "ocel:global-event": {}, "ocel:global-object": {}, "ocel:objects": {},
+            "ocel:events": {"e1": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T10:00:00Z", "ocel:omap": ["o1"], "ocel:vmap": {}}}}"#, &ImportOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_progress_reports() {
+        let mut reports: Vec<ImportProgress> = vec![];
+        let mut callback = |p: &ImportProgress| reports.push(p.clone());
+        let (log, _) = import_json_ocel_from_reader(File::open("logs/ocel-complex-test.jsonocel").unwrap(), Some(&mut callback), &ImportOptions::default()).expect("What did you do to the file?");
+
+        let last = reports.last().expect("the final progress is always reported");
+        assert_eq!(last.events, log.events.len());
+        assert_eq!(last.objects, log.objects.len());
+        assert_eq!(last.bytes_read, std::fs::metadata("logs/ocel-complex-test.jsonocel").unwrap().len());
+    }
+
+    #[test]
+    fn test_timestamp_options() {
+        let input = br#"{"ocel:global-log": {}, "ocel:global-event": {}, "ocel:global-object": {}, "ocel:objects": {"o1": {"ocel:type": "order", "ocel:ovmap": {}}},
+            "ocel:events": {"e1": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T10:00:00+02:00", "ocel:omap": ["o1"], "ocel:vmap": {}},
+                            "e2": {"ocel:activity": "b", "ocel:timestamp": "2022-01-01 11:00:00", "ocel:omap": ["o1"], "ocel:vmap": {}},
+                            "e3": {"ocel:activity": "c", "ocel:timestamp": "01.01.2022 12:00", "ocel:omap": ["o1"], "ocel:vmap": {}}}}"#;
+
+        let event = |log: &Ocel, name: &str| log.events[log.event_map.get_by_left(name).unwrap()].clone();
+        let (log, report) = import_json_ocel_from_slice(input, &ImportOptions::default()).unwrap();
+        assert_eq!(report, ImportReport { naive_timestamps: 1, invalid_timestamps: 1, ..Default::default() });
+        assert_eq!(event(&log, "e1").timestamp.to_rfc3339(), "2022-01-01T08:00:00+00:00");
+        assert_eq!(event(&log, "e1").offset, FixedOffset::east_opt(2 * 3600));
+        assert_eq!(event(&log, "e3").timestamp.timestamp(), 0);
+        assert_eq!(event(&log, "e3").offset, None);
+
+        let options = ImportOptions { timestamp_formats: vec!["%d.%m.%Y %H:%M".to_string()],
+                                      default_offset: FixedOffset::east_opt(3600).unwrap(),
+                                      ..Default::default() };
+        let (log, report) = import_json_ocel_from_slice(input, &options).unwrap();
+        assert_eq!(report.coerced(), 2);
+        assert_eq!(event(&log, "e2").timestamp.to_rfc3339(), "2022-01-01T10:00:00+00:00");
+        assert_eq!(event(&log, "e3").timestamp.to_rfc3339(), "2022-01-01T11:00:00+00:00");
+
+        let strict = ImportOptions { strict_timestamps: true, ..Default::default() };
+        assert!(matches!(import_json_ocel_from_slice(input, &strict), Err(Error::Parse(_))));
+    }
 }
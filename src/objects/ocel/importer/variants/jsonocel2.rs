@@ -0,0 +1,186 @@
+use crate::objects::ocel::{Ocel, AttributeHistory, TimestampParser};
+use crate::objects::ocel::importer::{ImportOptions, ImportReport};
+use crate::objects::ocel::variants::{declare_event, declare_object, fill_globals, relate_object, relate_objects, split_history};
+use crate::objects::ocel::variants::jsonocel2::{Ocel2Serde, convert_attribute_value};
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Read;
+use crate::Error;
+
+pub(crate) fn import_json_ocel2(file_path: &str, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    import_json_ocel2_from_reader(File::open(file_path)?, options)
+}
+
+pub(crate) fn import_json_ocel2_from_reader<R: Read>(mut reader: R, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    let mut buf: Vec<u8> = vec![];
+    reader.read_to_end(&mut buf)?;
+    import_json_ocel2_from_slice(&buf, options)
+}
+
+pub(crate) fn import_json_ocel2_from_slice(bytes: &[u8], options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    let log: Ocel2Serde = serde_json::from_slice(bytes)?;
+    ocel2_serde_to_ocel(log, options)
+}
+
+pub(crate) fn ocel2_serde_to_ocel(log: Ocel2Serde, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    let mut log_internal: Ocel = Ocel::default();
+    let mut timestamps = TimestampParser::new(options);
+
+    // declared attribute types per event and object type
+    let mut event_attr_types: AHashMap<&str, AHashMap<&str, &str>> = AHashMap::new();
+    let mut object_attr_types: AHashMap<&str, AHashMap<&str, &str>> = AHashMap::new();
+    let mut attribute_names: Vec<&str> = vec![];
+
+    for et in &log.event_types {
+        for attr in &et.attributes {
+            event_attr_types.entry(et.name.as_str()).or_default().insert(attr.name.as_str(), attr.attr_type.as_str());
+            attribute_names.push(attr.name.as_str());
+        }
+    }
+
+    for ot in &log.object_types {
+        for attr in &ot.attributes {
+            object_attr_types.entry(ot.name.as_str()).or_default().insert(attr.name.as_str(), attr.attr_type.as_str());
+            attribute_names.push(attr.name.as_str());
+        }
+    }
+
+    let object_types: Vec<&str> = log.object_types.iter().map(|ot| ot.name.as_str()).collect();
+    fill_globals(&mut log_internal, "2.0", attribute_names, object_types);
+
+    for obj in &log.objects {
+        let attr_types = object_attr_types.get(obj.obj_type.as_str());
+        let mut timed: AttributeHistory = AHashMap::new();
+        for attr in &obj.attributes {
+            // values without a time are initial values
+            let attr_time = match attr.time.as_deref() {
+                Some(time) => timestamps.parse(time)?.0,
+                None => DateTime::<Utc>::default()
+            };
+            let attr_type = attr_types.and_then(|types| types.get(attr.name.as_str()).copied());
+            let attr_value = convert_attribute_value(attr.value.clone(), attr_type);
+            timed.entry(attr.name.to_owned()).or_default().push((attr_time, attr_value));
+        }
+
+        let (ovmap, history) = split_history(timed);
+        declare_object(&mut log_internal, &obj.id, &obj.obj_type, ovmap, history)?;
+    }
+
+    for obj in &log.objects {
+        for rel in &obj.relationships {
+            relate_objects(&mut log_internal, &obj.id, &rel.object_id, &rel.qualifier)?;
+        }
+    }
+
+    for ev in &log.events {
+        let attr_types = event_attr_types.get(ev.activity.as_str());
+        let vmap: AHashMap<String, Value> = ev.attributes.iter()
+                                                         .map(|attr| {
+                                                             let attr_type = attr_types.and_then(|types| types.get(attr.name.as_str()).copied());
+                                                             (attr.name.to_owned(), convert_attribute_value(attr.value.clone(), attr_type))
+                                                         })
+                                                         .collect();
+
+        let timestamp = timestamps.parse(&ev.time)?;
+        let eid_nh = declare_event(&mut log_internal, &ev.id, &ev.activity, timestamp, vmap)?;
+        for rel in &ev.relationships {
+            relate_object(&mut log_internal, eid_nh, &rel.object_id, &rel.qualifier)?;
+        }
+    }
+
+    log_internal.reindex_chronologically();
+    timestamps.report.attributes = log_internal.infer_schema();
+    Ok((log_internal, timestamps.report))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ahash::AHashSet;
+    use serde_json::json;
+
+    use super::*;
+    use crate::objects::ocel::importer::import_ocel;
+    use crate::objects::ocel::exporter::{export_ocel2, export_ocel_to_string};
+    use crate::objects::ocel::format::OcelFormat;
+
+    fn assert_same_log(log1: &Ocel, log2: &Ocel) {
+        assert_eq!(log1.events.len(), log2.events.len());
+        assert_eq!(log1.objects.len(), log2.objects.len());
+
+        for (eid_str, eid1) in log1.event_map.iter() {
+            let eid2 = log2.event_map.get_by_left(eid_str).expect("event went missing");
+            let (ev1, ev2) = (&log1.events[eid1], &log2.events[eid2]);
+            assert_eq!(log1.activity(ev1), log2.activity(ev2));
+            assert_eq!(ev1.timestamp, ev2.timestamp);
+            assert_eq!(ev1.vmap, ev2.vmap);
+            let omap1: AHashSet<&String> = ev1.omap.iter().map(|oid| log1.object_map.get_by_right(oid).unwrap()).collect();
+            let omap2: AHashSet<&String> = ev2.omap.iter().map(|oid| log2.object_map.get_by_right(oid).unwrap()).collect();
+            assert_eq!(omap1, omap2);
+        }
+
+        for (oid_str, oid1) in log1.object_map.iter() {
+            let oid2 = log2.object_map.get_by_left(oid_str).expect("object went missing");
+            let (obj1, obj2) = (&log1.objects[oid1], &log2.objects[oid2]);
+            assert_eq!(log1.object_type(obj1), log2.object_type(obj2));
+            assert_eq!(obj1.ovmap, obj2.ovmap);
+            let oe1: Vec<&String> = obj1.events.iter().map(|eid| log1.event_map.get_by_right(eid).unwrap()).collect();
+            let oe2: Vec<&String> = obj2.events.iter().map(|eid| log2.event_map.get_by_right(eid).unwrap()).collect();
+            assert_eq!(oe1, oe2);
+        }
+    }
+
+    #[test]
+    fn test_ocel2_round_trip() {
+        for file in ["min", "ocel-complex-test", "ocel-transformation-test", "ocel-decomposition-test"] {
+            let log = import_ocel(format!("logs/{}.jsonocel", file).as_str()).expect("What did you do to the file?");
+            let export_path = std::env::temp_dir().join(format!("pmrs-round-trip-{}.json", file));
+            export_ocel2(&log, export_path.to_str().unwrap()).expect("could not export the log");
+
+            let reimported = import_json_ocel2(export_path.to_str().unwrap(), &ImportOptions::default()).expect("could not import the exported log").0;
+            assert_same_log(&log, &reimported);
+            // declared types survive, undeclared types that objects use are added
+            let reimported_types = reimported.global_log["ocel:object-types"].as_array().unwrap();
+            for ot in log.global_log["ocel:object-types"].as_array().unwrap() {
+                assert!(reimported_types.contains(ot));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ocel2_import() {
+        let log = import_json_ocel2("logs/ocel2-test.json", &ImportOptions::default()).expect("What did you do to the file?").0;
+        assert_eq!(log.events.len(), 3);
+        assert_eq!(log.objects.len(), 3);
+
+        let e1 = &log.events[log.event_map.get_by_left("e1").unwrap()];
+        assert_eq!(log.activity(e1), "place order");
+        assert_eq!(e1.omap.len(), 2);
+        assert_eq!(e1.vmap["prepaid-amount"], json!(1000.0));
+
+        // typed string values are converted, the earliest attribute value is the initial one
+        let o1 = &log.objects[log.object_map.get_by_left("o1").unwrap()];
+        assert_eq!(o1.ovmap["status"], json!("placed"));
+        assert_eq!(o1.ovmap["price"], json!(2999.99));
+        assert_eq!(o1.events.len(), 3);
+
+        let shipped: DateTime<Utc> = "2022-01-02T10:00:00Z".parse().unwrap();
+        assert_eq!(o1.history["status"], vec![(shipped, json!("shipped"))]);
+        assert!(!o1.history.contains_key("price"));
+        assert_eq!(o1.value_at("status", &(shipped - chrono::Duration::seconds(1))), Some(&json!("placed")));
+        assert_eq!(o1.value_at("status", &shipped), Some(&json!("shipped")));
+
+        let exported = export_ocel_to_string(&log, OcelFormat::JsonOcel2).unwrap();
+        let reimported = import_json_ocel2_from_slice(exported.as_bytes(), &ImportOptions::default()).unwrap().0;
+        assert_eq!(reimported.objects[reimported.object_map.get_by_left("o1").unwrap()].history, o1.history);
+    }
+
+    #[test]
+    fn test_ocel2_undeclared_object_fails() {
+        let log: Ocel2Serde = serde_json::from_str(r#"{"objectTypes": [], "eventTypes": [], "objects": [],
+            "events": [{"id": "e1", "type": "A", "time": "2022-01-01T10:00:00Z", "attributes": [], "relationships": [{"objectId": "o1", "qualifier": ""}]}]}"#).unwrap();
+        assert!(ocel2_serde_to_ocel(log, &ImportOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::objects::ocel::{Ocel, AttributeHistory, TimestampParser};
+use crate::objects::ocel::importer::{ImportOptions, ImportReport};
+use crate::objects::ocel::variants::{declare_event, declare_object, declared_event, fill_globals, relate_object, relate_objects, split_history};
+use crate::objects::ocel::variants::sqliteocel::{EVENT_TABLE, EVENT_MAP_TYPE_TABLE, OBJECT_TABLE, OBJECT_MAP_TYPE_TABLE, EVENT_OBJECT_TABLE, OBJECT_OBJECT_TABLE, RESERVED_COLUMNS, quote_identifier, sql_to_json};
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde_json::Value;
+use crate::Error;
+
+type TypeTable = (Vec<String>, Vec<(String, TypeTableRow)>);
+
+struct TypeTableRow {
+    time: Option<String>,
+    changed_fields: Option<Vec<String>>,
+    attributes: Vec<(String, Value)>
+}
+
+pub(crate) fn import_sqlite_ocel(file_path: &str, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    // no uri flag, so only plain local database files can be opened
+    let conn = Connection::open_with_flags(file_path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+    let mut log_internal = Ocel::default();
+    let mut timestamps = TimestampParser::new(options);
+
+    let event_types = read_type_map(&conn, EVENT_MAP_TYPE_TABLE)?;
+    let object_types = read_type_map(&conn, OBJECT_MAP_TYPE_TABLE)?;
+    let mut attribute_names: Vec<String> = vec![];
+
+    // rows of attribute values and changes of every object
+    let mut object_rows: AHashMap<String, Vec<TypeTableRow>> = AHashMap::new();
+    for (_, type_map) in &object_types {
+        let (columns, rows) = read_type_table(&conn, format!("{}_{}", OBJECT_TABLE, type_map).as_str())?;
+        attribute_names.extend(columns);
+        for (oid, row) in rows {
+            object_rows.entry(oid).or_default().push(row);
+        }
+    }
+
+    let mut stmt = conn.prepare(format!("SELECT ocel_id, ocel_type FROM {} ORDER BY rowid", quote_identifier(OBJECT_TABLE)).as_str())?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let oid: String = row.get(0)?;
+        let obj_type: String = row.get(1)?;
+        let (ovmap, history) = split_history(timed_attributes(object_rows.remove(&oid).unwrap_or_default(), &mut timestamps)?);
+        declare_object(&mut log_internal, &oid, &obj_type, ovmap, history)?;
+    }
+
+    let mut event_rows: AHashMap<String, TypeTableRow> = AHashMap::new();
+    for (_, type_map) in &event_types {
+        let (columns, rows) = read_type_table(&conn, format!("{}_{}", EVENT_TABLE, type_map).as_str())?;
+        attribute_names.extend(columns);
+        event_rows.extend(rows);
+    }
+
+    let mut stmt = conn.prepare(format!("SELECT ocel_id, ocel_type FROM {} ORDER BY rowid", quote_identifier(EVENT_TABLE)).as_str())?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let eid: String = row.get(0)?;
+        let activity: String = row.get(1)?;
+        let type_row = event_rows.remove(&eid).ok_or_else(|| Error::Schema(format!("Event {} is missing from its event type table.", eid)))?;
+        let timestamp = timestamps.parse(type_row.time.as_deref().unwrap_or_default())?;
+        declare_event(&mut log_internal, &eid, &activity, timestamp, type_row.attributes.into_iter().collect())?;
+    }
+
+    let mut stmt = conn.prepare(format!("SELECT ocel_event_id, ocel_object_id, ocel_qualifier FROM {} ORDER BY rowid", quote_identifier(EVENT_OBJECT_TABLE)).as_str())?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let eid: String = row.get(0)?;
+        let oid: String = row.get(1)?;
+        let qualifier: Option<String> = row.get(2)?;
+        let eid_nh = declared_event(&log_internal, &eid)?;
+        relate_object(&mut log_internal, eid_nh, &oid, qualifier.as_deref().unwrap_or_default())?;
+    }
+
+    let mut stmt = conn.prepare(format!("SELECT ocel_source_id, ocel_target_id, ocel_qualifier FROM {} ORDER BY rowid", quote_identifier(OBJECT_OBJECT_TABLE)).as_str())?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let source: String = row.get(0)?;
+        let target: String = row.get(1)?;
+        let qualifier: Option<String> = row.get(2)?;
+        relate_objects(&mut log_internal, &source, &target, qualifier.as_deref().unwrap_or_default())?;
+    }
+
+    log_internal.reindex_chronologically();
+    timestamps.report.attributes = log_internal.infer_schema();
+
+    let object_type_names: Vec<&str> = object_types.iter().map(|(ot, _)| ot.as_str()).collect();
+    fill_globals(&mut log_internal, "2.0", attribute_names.iter().map(|s| s.as_str()).collect(), object_type_names);
+
+    Ok((log_internal, timestamps.report))
+}
+
+fn read_type_map(conn: &Connection, table: &str) -> Result<Vec<(String, String)>, Error> {
+    let mut stmt = conn.prepare(format!("SELECT ocel_type, ocel_type_map FROM {} ORDER BY rowid", quote_identifier(table)).as_str())?;
+    let type_map = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                       .collect::<Result<Vec<(String, String)>, _>>()?;
+    Ok(type_map)
+}
+
+fn read_type_table(conn: &Connection, table: &str) -> Result<TypeTable, Error> {
+    let mut column_stmt = conn.prepare(format!("PRAGMA table_info({})", quote_identifier(table)).as_str())?;
+    let columns: Vec<(String, String)> = column_stmt.query_map([], |row| Ok((row.get(1)?, row.get(2)?)))?
+                                                    .collect::<Result<Vec<(String, String)>, _>>()?;
+    if columns.is_empty() {
+        return Err(Error::Schema(format!("The type table {} does not exist.", table)));
+    }
+
+    let attributes: Vec<(usize, &String, &String)> = columns.iter()
+                                                            .enumerate()
+                                                            .filter(|(_, (name, _))| !RESERVED_COLUMNS.contains(&name.as_str()))
+                                                            .map(|(i, (name, col_type))| (i, name, col_type))
+                                                            .collect();
+
+    let column_list = columns.iter().map(|(name, _)| quote_identifier(name)).collect::<Vec<String>>().join(", ");
+    let mut stmt = conn.prepare(format!("SELECT {} FROM {} ORDER BY rowid", column_list, quote_identifier(table)).as_str())?;
+    let position = |name: &str| columns.iter().position(|(col, _)| col == name);
+    let (id_pos, time_pos, changed_pos) = (position("ocel_id").ok_or_else(|| Error::Schema(format!("The type table {} has no ocel_id column.", table)))?, position("ocel_time"), position("ocel_changed_field"));
+
+    let mut rows = stmt.query([])?;
+    let mut parsed: Vec<(String, TypeTableRow)> = vec![];
+    while let Some(row) = rows.next()? {
+        let id: String = row.get(id_pos)?;
+        let time: Option<String> = match time_pos { Some(pos) => row.get(pos)?, None => None };
+        let changed: Option<String> = match changed_pos { Some(pos) => row.get(pos)?, _ => None };
+        let mut row_attributes: Vec<(String, Value)> = vec![];
+        for (i, name, col_type) in &attributes {
+            let value = sql_to_json(row.get_ref(*i)?, col_type);
+            // a type table has a column for every attribute of the type, empty ones are not set
+            if !value.is_null() {
+                row_attributes.push((name.to_string(), value));
+            }
+        }
+        let changed_fields = changed.map(|fields| fields.split(',').map(|f| f.trim().to_string()).collect());
+        parsed.push((id, TypeTableRow { time, changed_fields, attributes: row_attributes }));
+    }
+
+    Ok((attributes.into_iter().map(|(_, name, _)| name.to_owned()).collect(), parsed))
+}
+
+/// Values of the attributes with the time they were set, rows of changes only set their changed fields.
+fn timed_attributes(rows: Vec<TypeTableRow>, timestamps: &mut TimestampParser) -> Result<AttributeHistory, Error> {
+    let mut timed: AttributeHistory = AHashMap::new();
+    for row in rows {
+        // rows without a time hold initial values
+        let row_time = match row.time.as_deref() {
+            Some(time) => timestamps.parse(time)?.0,
+            None => DateTime::<Utc>::default()
+        };
+        for (name, value) in row.attributes {
+            if let Some(changed) = &row.changed_fields {
+                if !changed.contains(&name) {
+                    continue;
+                }
+            }
+            timed.entry(name).or_default().push((row_time, value));
+        }
+    }
+    Ok(timed)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::objects::ocel::importer::import_ocel;
+
+    fn non_null(map: &AHashMap<String, Value>) -> AHashMap<&String, &Value> {
+        map.iter().filter(|(_, v)| !v.is_null()).collect()
+    }
+    use crate::objects::ocel::exporter::export_ocel2_sqlite;
+
+    #[test]
+    fn test_sqlite_attribute_history() {
+        let log = import_ocel("logs/ocel2-test.json").expect("What did you do to the file?");
+        let export_path = std::env::temp_dir().join("pmrs-attribute-history.sqlite");
+        export_ocel2_sqlite(&log, export_path.to_str().unwrap()).expect("could not export the log");
+        let reimported = import_sqlite_ocel(export_path.to_str().unwrap(), &ImportOptions::default()).expect("could not import the exported log").0;
+
+        for (oid_str, oid1) in log.object_map.iter() {
+            let (obj1, obj2) = (&log.objects[oid1], &reimported.objects[reimported.object_map.get_by_left(oid_str).unwrap()]);
+            assert_eq!(non_null(&obj1.ovmap), non_null(&obj2.ovmap));
+            assert_eq!(obj1.history, obj2.history);
+        }
+        let o1 = &reimported.objects[reimported.object_map.get_by_left("o1").unwrap()];
+        assert_eq!(o1.history["status"][0].1, json!("shipped"));
+    }
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        for file in ["min", "ocel-complex-test", "ocel-transformation-test", "ocel-decomposition-test"] {
+            let log = import_ocel(format!("logs/{}.jsonocel", file).as_str()).expect("What did you do to the file?");
+            let export_path = std::env::temp_dir().join(format!("pmrs-round-trip-{}.sqlite", file));
+            export_ocel2_sqlite(&log, export_path.to_str().unwrap()).expect("could not export the log");
+
+            let reimported = import_sqlite_ocel(export_path.to_str().unwrap(), &ImportOptions::default()).expect("could not import the exported log").0;
+            assert_eq!(log.events.len(), reimported.events.len());
+            assert_eq!(log.objects.len(), reimported.objects.len());
+
+            for (eid_str, eid1) in log.event_map.iter() {
+                let (ev1, ev2) = (&log.events[eid1], &reimported.events[reimported.event_map.get_by_left(eid_str).unwrap()]);
+                assert_eq!(log.activity(ev1), reimported.activity(ev2));
+                assert_eq!(ev1.timestamp, ev2.timestamp);
+                assert_eq!(non_null(&ev1.vmap), non_null(&ev2.vmap));
+                assert_eq!(ev1.omap.len(), ev2.omap.len());
+            }
+
+            for (oid_str, oid1) in log.object_map.iter() {
+                let (obj1, obj2) = (&log.objects[oid1], &reimported.objects[reimported.object_map.get_by_left(oid_str).unwrap()]);
+                assert_eq!(log.object_type(obj1), reimported.object_type(obj2));
+                assert_eq!(non_null(&obj1.ovmap), non_null(&obj2.ovmap));
+                let oe1: Vec<&String> = obj1.events.iter().map(|eid| log.event_map.get_by_right(eid).unwrap()).collect();
+                let oe2: Vec<&String> = obj2.events.iter().map(|eid| reimported.event_map.get_by_right(eid).unwrap()).collect();
+                assert_eq!(oe1, oe2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqlite_attribute_changes() {
+        let path = std::env::temp_dir().join("pmrs-attribute-changes.sqlite");
+        let _ = std::fs::remove_file(&path);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(r#"
+            CREATE TABLE event (ocel_id TEXT, ocel_type TEXT);
+            CREATE TABLE event_map_type (ocel_type TEXT, ocel_type_map TEXT);
+            CREATE TABLE object (ocel_id TEXT, ocel_type TEXT);
+            CREATE TABLE object_map_type (ocel_type TEXT, ocel_type_map TEXT);
+            CREATE TABLE event_object (ocel_event_id TEXT, ocel_object_id TEXT, ocel_qualifier TEXT);
+            CREATE TABLE object_object (ocel_source_id TEXT, ocel_target_id TEXT, ocel_qualifier TEXT);
+            CREATE TABLE event_PlaceOrder (ocel_id TEXT, ocel_time TIMESTAMP, express BOOLEAN);
+            CREATE TABLE object_Order (ocel_id TEXT, ocel_time TIMESTAMP, ocel_changed_field TEXT, status TEXT, price REAL);
+            INSERT INTO event VALUES ('e1', 'place order');
+            INSERT INTO event_map_type VALUES ('place order', 'PlaceOrder');
+            INSERT INTO object VALUES ('o1', 'order');
+            INSERT INTO object_map_type VALUES ('order', 'Order');
+            INSERT INTO event_object VALUES ('e1', 'o1', 'order');
+            INSERT INTO event_PlaceOrder VALUES ('e1', '2022-01-01T10:00:00Z', 1);
+            INSERT INTO object_Order VALUES ('o1', '2022-01-02T10:00:00Z', 'status', 'shipped', NULL);
+            INSERT INTO object_Order VALUES ('o1', '1970-01-01T00:00:00Z', NULL, 'placed', 10.5);
+        "#).unwrap();
+        drop(conn);
+
+        let log = import_sqlite_ocel(path.to_str().unwrap(), &ImportOptions::default()).expect("could not import the database").0;
+        let o1 = &log.objects[log.object_map.get_by_left("o1").unwrap()];
+        assert_eq!(o1.ovmap["status"], json!("placed"));
+        assert_eq!(o1.ovmap["price"], json!(10.5));
+        assert_eq!(o1.events, vec![0]);
+        assert_eq!(log.events[&0].vmap["express"], json!(true));
+    }
+
+    #[test]
+    fn test_sqlite_missing_file_fails() {
+        assert!(import_sqlite_ocel("logs/does-not-exist.sqlite", &ImportOptions::default()).is_err());
+    }
+}
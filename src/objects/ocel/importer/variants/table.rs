@@ -0,0 +1,207 @@
+use crate::objects::ocel::{Ocel, AttributeHistory, TimestampParser};
+use crate::objects::ocel::dataframe::{string_column, value_column};
+use crate::objects::ocel::importer::{ColumnMapping, ImportReport, RowError};
+use crate::objects::ocel::variants::{declare_event, declare_object, fill_globals, relate_object};
+use ahash::AHashMap;
+use itertools::Itertools;
+use polars::prelude::{CsvReader, DataFrame, SerReader};
+use serde_json::Value;
+use crate::Error;
+use std::io::{Cursor, Read};
+
+pub(crate) fn import_csv_table(file_path: &str, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    let df = CsvReader::from_path(file_path)?.has_header(true).finish()?;
+    import_dataframe_table(&df, mapping)
+}
+
+pub(crate) fn import_csv_table_from_reader<R: Read>(mut reader: R, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    let mut bytes: Vec<u8> = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let df = CsvReader::new(Cursor::new(bytes)).has_header(true).finish()?;
+    import_dataframe_table(&df, mapping)
+}
+
+#[cfg(feature = "parquet")]
+pub(crate) fn import_parquet_table(file_path: &str, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    let df = polars::prelude::ParquetReader::new(std::fs::File::open(file_path)?).finish()?;
+    import_dataframe_table(&df, mapping)
+}
+
+/// Build the log row by row. Rows without an activity or timestamp, with a repeated event id or an object id
+/// that is used for two object types are skipped and reported, as are timestamps that do not parse in strict mode.
+pub(crate) fn import_dataframe_table(df: &DataFrame, mapping: &ColumnMapping) -> Result<(Ocel, ImportReport), Error> {
+    let event_ids = match &mapping.event_id {
+        Some(column) => Some(string_column(df, column)?),
+        None => None
+    };
+    let activities = string_column(df, &mapping.activity)?;
+    let timestamps = string_column(df, &mapping.timestamp)?;
+    let object_columns = mapping.object_columns.iter()
+                                .map(|(column, obj_type)| Ok((string_column(df, column)?, obj_type.as_str())))
+                                .collect::<Result<Vec<(Vec<Option<String>>, &str)>, Error>>()?;
+    let attribute_columns = mapping.attribute_columns.iter()
+                                   .map(|column| Ok((column.as_str(), value_column(df, column)?)))
+                                   .collect::<Result<Vec<(&str, Vec<Option<Value>>)>, Error>>()?;
+
+    let mut log_internal = Ocel::default();
+    let mut timestamp_parser = TimestampParser::new(&mapping.timestamps);
+    let mut row_errors: Vec<RowError> = vec![];
+
+    'rows: for row in 0..df.height() {
+        let eid = match &event_ids {
+            Some(ids) => match &ids[row] {
+                Some(eid) => eid.to_owned(),
+                None => {
+                    row_errors.push(RowError { row, reason: "The event id is missing.".to_string() });
+                    continue;
+                }
+            },
+            None => row.to_string()
+        };
+        if log_internal.event_map.contains_left(&eid) {
+            row_errors.push(RowError { row, reason: format!("Event id {} is used more than once.", eid) });
+            continue;
+        }
+        let activity = match activities[row].as_deref() {
+            Some(activity) if !activity.is_empty() => activity,
+            _ => {
+                row_errors.push(RowError { row, reason: "The activity is missing.".to_string() });
+                continue;
+            }
+        };
+        let timestamp = match timestamps[row].as_deref().map(|ts| timestamp_parser.parse(ts)) {
+            Some(Ok(timestamp)) => timestamp,
+            Some(Err(_)) => {
+                row_errors.push(RowError { row, reason: format!("Could not parse the timestamp {}.", timestamps[row].as_deref().unwrap_or_default()) });
+                continue;
+            },
+            None => {
+                row_errors.push(RowError { row, reason: "The timestamp is missing.".to_string() });
+                continue;
+            }
+        };
+
+        let mut row_objects: Vec<(&str, &str)> = vec![];
+        for (ids, obj_type) in &object_columns {
+            for oid in ids[row].as_deref().unwrap_or_default().split(mapping.id_separator).map(|oid| oid.trim()).filter(|oid| !oid.is_empty()) {
+                let known_type = log_internal.object_map.get_by_left(oid).map(|oid_nh| log_internal.object_type(&log_internal.objects[oid_nh]))
+                                             .or(row_objects.iter().find(|(other, _)| *other == oid).map(|(_, other_type)| *other_type));
+                if known_type.is_some_and(|known_type| known_type != *obj_type) {
+                    row_errors.push(RowError { row, reason: format!("Object id {} is used for more than one object type.", oid) });
+                    continue 'rows;
+                }
+                row_objects.push((oid, obj_type));
+            }
+        }
+
+        let vmap: AHashMap<String, Value> = attribute_columns.iter()
+                                                             .filter_map(|(column, values)| values[row].to_owned().map(|value| (column.to_string(), value)))
+                                                             .collect();
+        let eid_nh = declare_event(&mut log_internal, &eid, activity, timestamp, vmap)?;
+        for (oid, obj_type) in row_objects {
+            if !log_internal.object_map.contains_left(oid) {
+                declare_object(&mut log_internal, oid, obj_type, AHashMap::new(), AttributeHistory::new())?;
+            }
+            relate_object(&mut log_internal, eid_nh, oid, "")?;
+        }
+    }
+
+    log_internal.reindex_chronologically();
+    timestamp_parser.report.attributes = log_internal.infer_schema();
+    let object_types: Vec<&str> = mapping.object_columns.iter().map(|(_, obj_type)| obj_type.as_str()).unique().collect();
+    fill_globals(&mut log_internal, "1.0", mapping.attribute_columns.iter().map(|column| column.as_str()).collect(), object_types);
+
+    timestamp_parser.report.skipped_rows = row_errors;
+    Ok((log_internal, timestamp_parser.report))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ocel::importer::ImportOptions;
+    use chrono::{FixedOffset, Offset, Utc};
+
+    fn mapping() -> ColumnMapping {
+        ColumnMapping { event_id: Some("event_id".to_string()),
+                        object_columns: vec![("orders".to_string(), "order".to_string()), ("items".to_string(), "item".to_string())],
+                        attribute_columns: vec!["price".to_string(), "express".to_string()],
+                        ..Default::default() }
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let mapping = ColumnMapping { timestamps: ImportOptions { strict_timestamps: true, ..Default::default() }, ..mapping() };
+        let (log, report) = import_csv_table("logs/table-test.csv", &mapping).expect("What did you do to the file?");
+        assert_eq!(log.events.len(), 6);
+        assert_eq!(log.objects.len(), 6);
+        assert_eq!(log.activities(), vec!["place order", "pick item", "pay order", "ship order"]);
+        assert_eq!(report.skipped_rows.iter().map(|e| e.row).collect::<Vec<usize>>(), vec![4, 6, 7, 8]);
+        assert_eq!(report.skipped_rows[0].reason, "Could not parse the timestamp not a timestamp.");
+
+        let e1 = &log.events[log.event_map.get_by_left("e1").unwrap()];
+        assert_eq!(e1.omap.len(), 3);
+        assert_eq!(e1.vmap["price"], Value::from(120.5));
+        assert_eq!(e1.vmap["express"], Value::Bool(true));
+        assert!(!log.events[log.event_map.get_by_left("e2").unwrap()].vmap.contains_key("price"));
+
+        // object lifecycles are ordered by time, not by row
+        let o1 = &log.objects[log.object_map.get_by_left("o1").unwrap()];
+        let o1_events: Vec<&str> = o1.events.iter().map(|eid| log.event_map.get_by_right(eid).unwrap().as_str()).collect();
+        assert_eq!(o1_events, vec!["e1", "e9", "e4"]);
+        assert_eq!(log.object_type(&log.objects[log.object_map.get_by_left("i4").unwrap()]), "item");
+    }
+
+    #[test]
+    fn test_import_csv_invalid_timestamps() {
+        let (log, report) = import_csv_table("logs/table-test.csv", &mapping()).unwrap();
+        assert_eq!(log.events.len(), 7);
+        assert_eq!(report.skipped_rows.iter().map(|e| e.row).collect::<Vec<usize>>(), vec![6, 7, 8]);
+        assert_eq!(report.invalid_timestamps, 1);
+        assert_eq!(report.naive_timestamps, 7);
+        assert_eq!(log.event_map.get_by_left("e5"), Some(&0));
+        assert_eq!(log.events[&0].timestamp.timestamp(), 0);
+    }
+
+    #[test]
+    fn test_import_csv_timestamp_format() {
+        let csv = "activity;timestamp;case\na;01.02.2023 10:00;c1\nb;01.02.2023 11:15;c1\nc;2023-02-01T12:00:00Z;c1\n";
+        let timestamps = ImportOptions { timestamp_formats: vec!["%d.%m.%Y %H:%M".to_string()],
+                                         default_offset: FixedOffset::east_opt(3600).unwrap(),
+                                         ..Default::default() };
+        let mut mapping = ColumnMapping { timestamps,
+                                          object_columns: vec![("case".to_string(), "case".to_string())],
+                                          ..Default::default() };
+        mapping.id_separator = ';';
+        let df = CsvReader::new(Cursor::new(csv)).has_header(true).with_delimiter(b';').finish().unwrap();
+        let (log, report) = import_dataframe_table(&df, &mapping).unwrap();
+
+        assert_eq!(log.events.len(), 3);
+        assert!(report.skipped_rows.is_empty());
+        assert_eq!(report.naive_timestamps, 2);
+        assert_eq!(log.event_map.get_by_left("1"), Some(&1));
+        assert_eq!(log.events[&1].timestamp.to_rfc3339(), "2023-02-01T10:15:00+00:00");
+        assert_eq!(log.events[&1].offset, FixedOffset::east_opt(3600));
+        assert_eq!(log.events[&2].offset, Some(Utc.fix()));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_import_parquet() {
+        let mut df = CsvReader::from_path("logs/table-test.csv").unwrap().has_header(true).finish().unwrap();
+        let parquet_path = std::env::temp_dir().join("pmrs-table-test.parquet");
+        polars::prelude::ParquetWriter::new(std::fs::File::create(&parquet_path).unwrap()).finish(&mut df).unwrap();
+
+        let (log, report) = import_parquet_table(parquet_path.to_str().unwrap(), &mapping()).unwrap();
+        let (from_csv, csv_report) = import_csv_table("logs/table-test.csv", &mapping()).unwrap();
+        assert_eq!(log.events.len(), from_csv.events.len());
+        assert_eq!(log.objects.len(), from_csv.objects.len());
+        assert_eq!(report, csv_report);
+    }
+
+    #[test]
+    fn test_missing_column_fails() {
+        let mapping = ColumnMapping { activity: "no such column".to_string(), ..mapping() };
+        assert!(import_csv_table_from_reader(std::fs::File::open("logs/table-test.csv").unwrap(), &mapping).is_err());
+    }
+}
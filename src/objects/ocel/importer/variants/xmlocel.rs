@@ -0,0 +1,189 @@
+use crate::objects::ocel::{Ocel, AttributeHistory, TimestampParser};
+use crate::objects::ocel::importer::{ImportOptions, ImportReport};
+use crate::objects::ocel::variants::{declare_event, declare_object, relate_object, relate_objects};
+use crate::objects::ocel::variants::xmlocel::{XmlNode, parse_xml_tree, xml_to_value};
+use ahash::AHashMap;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use crate::Error;
+
+pub(crate) fn import_xml_ocel(file_path: &str, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    import_xml_ocel_from_reader(File::open(file_path)?, options)
+}
+
+pub(crate) fn import_xml_ocel_from_reader<R: Read>(reader: R, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    let root = parse_xml_tree(BufReader::new(reader))?;
+    xml_tree_to_ocel(&root, options)
+}
+
+pub(crate) fn xml_tree_to_ocel(root: &XmlNode, options: &ImportOptions) -> Result<(Ocel, ImportReport), Error> {
+    let mut log_internal: Ocel = Ocel::default();
+    let mut timestamps = TimestampParser::new(options);
+
+    for global in root.children.iter().filter(|c| c.tag == "global") {
+        let scope = global.children.iter().map(|attr| {
+            // global lists (attribute names, object types) are plain lists of values
+            let value = match attr.tag.as_str() {
+                "list" => Value::Array(attr.children.iter().map(xml_to_value).collect()),
+                _ => xml_to_value(attr)
+            };
+            let key = attr.key.to_owned().unwrap_or_default();
+            (if key.starts_with("ocel:") {key} else {format!("ocel:{}", key)}, value)
+        });
+        match global.key.as_deref() {
+            Some("log") => log_internal.global_log.extend(scope),
+            Some("event") => log_internal.global_event.extend(scope),
+            Some("object") => log_internal.global_object.extend(scope),
+            _ => return Err(Error::Schema("A global element has an unknown scope.".to_string()))
+        }
+    }
+
+    let objects = root.child("objects").map(|objs| objs.children.as_slice()).unwrap_or_default();
+    for obj in objects {
+        let oid = obj.keyed_value("id").ok_or_else(|| Error::Schema("An object has no id.".to_string()))?;
+        let obj_type = obj.keyed_value("type").ok_or_else(|| Error::Schema(format!("Object {} has no type.", oid)))?;
+        let ovmap: AHashMap<String, Value> = obj.keyed_child("ovmap")
+                                                .map(|ovmap| ovmap.children.iter().map(|attr| (attr.key.to_owned().unwrap_or_default(), xml_to_value(attr))).collect())
+                                                .unwrap_or_default();
+
+        declare_object(&mut log_internal, oid, obj_type, ovmap, AttributeHistory::new())?;
+    }
+
+    for obj in objects {
+        for (target, qualifier) in relationships(obj, "o2o") {
+            relate_objects(&mut log_internal, obj.keyed_value("id").unwrap_or_default(), target, qualifier)?;
+        }
+    }
+
+    let events = root.child("events").map(|evs| evs.children.as_slice()).unwrap_or_default();
+    for ev in events {
+        let eid = ev.keyed_value("id").ok_or_else(|| Error::Schema("An event has no id.".to_string()))?;
+        let activity = ev.keyed_value("activity").ok_or_else(|| Error::Schema(format!("Event {} has no activity.", eid)))?;
+        let timestamp = timestamps.parse(ev.keyed_value("timestamp").ok_or_else(|| Error::Schema(format!("Event {} has no timestamp.", eid)))?)?;
+        let vmap: AHashMap<String, Value> = ev.keyed_child("vmap")
+                                              .map(|vmap| vmap.children.iter().map(|attr| (attr.key.to_owned().unwrap_or_default(), xml_to_value(attr))).collect())
+                                              .unwrap_or_default();
+
+        let eid_nh = declare_event(&mut log_internal, eid, activity, timestamp, vmap)?;
+        let omap = ev.keyed_child("omap").map(|omap| omap.children.as_slice()).unwrap_or_default();
+        for (oid, qualifier) in omap.iter().map(|oid| (oid.value.as_deref().unwrap_or_default(), "")).chain(relationships(ev, "qualifiers")) {
+            relate_object(&mut log_internal, eid_nh, oid, qualifier)?;
+        }
+    }
+
+    log_internal.reindex_chronologically();
+    timestamps.report.attributes = log_internal.infer_schema();
+    Ok((log_internal, timestamps.report))
+}
+
+/// Linked objects and qualifiers of a list of qualified links.
+fn relationships<'a>(node: &'a XmlNode, key: &str) -> impl Iterator<Item = (&'a str, &'a str)> {
+    node.keyed_child(key)
+        .map(|links| links.children.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|link| (link.key.as_deref().unwrap_or_default(), link.value.as_deref().unwrap_or_default()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::objects::ocel::importer::import_ocel;
+    use crate::objects::ocel::exporter::export_ocel_xml;
+
+    fn non_null(map: &AHashMap<String, Value>) -> AHashMap<&String, &Value> {
+        map.iter().filter(|(_, v)| !v.is_null()).collect()
+    }
+
+    #[test]
+    fn test_xmlocel_round_trip() {
+        for file in ["min", "ocel-complex-test", "ocel-transformation-test", "ocel-decomposition-test"] {
+            let log = import_ocel(format!("logs/{}.jsonocel", file).as_str()).expect("What did you do to the file?");
+            let export_path = std::env::temp_dir().join(format!("pmrs-round-trip-{}.xmlocel", file));
+            export_ocel_xml(&log, export_path.to_str().unwrap()).expect("could not export the log");
+
+            let reimported = import_xml_ocel(export_path.to_str().unwrap(), &ImportOptions::default()).expect("could not import the exported log").0;
+            assert_eq!(log.global_log, reimported.global_log);
+            assert_eq!(log.global_event, reimported.global_event);
+            assert_eq!(log.global_object, reimported.global_object);
+            assert_eq!(log.events.len(), reimported.events.len());
+            assert_eq!(log.objects.len(), reimported.objects.len());
+
+            for (eid_str, eid1) in log.event_map.iter() {
+                let (ev1, ev2) = (&log.events[eid1], &reimported.events[reimported.event_map.get_by_left(eid_str).unwrap()]);
+                assert_eq!(log.activity(ev1), reimported.activity(ev2));
+                assert_eq!(ev1.timestamp, ev2.timestamp);
+                assert_eq!(non_null(&ev1.vmap), non_null(&ev2.vmap));
+                assert_eq!(ev1.omap.len(), ev2.omap.len());
+            }
+
+            for (oid_str, oid1) in log.object_map.iter() {
+                let (obj1, obj2) = (&log.objects[oid1], &reimported.objects[reimported.object_map.get_by_left(oid_str).unwrap()]);
+                assert_eq!(log.object_type(obj1), reimported.object_type(obj2));
+                assert_eq!(non_null(&obj1.ovmap), non_null(&obj2.ovmap));
+                let oe1: Vec<&String> = obj1.events.iter().map(|eid| log.event_map.get_by_right(eid).unwrap()).collect();
+                let oe2: Vec<&String> = obj2.events.iter().map(|eid| reimported.event_map.get_by_right(eid).unwrap()).collect();
+                assert_eq!(oe1, oe2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_xmlocel_typed_attributes() {
+        let root = parse_xml_tree(r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log>
+              <global scope="log">
+                <list key="object-types"><string key="object-type" value="order"/></list>
+                <string key="version" value="1.0"/>
+              </global>
+              <events>
+                <event>
+                  <string key="id" value="e1"/>
+                  <string key="activity" value="place order"/>
+                  <date key="timestamp" value="2022-01-01T10:00:00+01:00"/>
+                  <list key="omap"><string key="object-id" value="o1"/></list>
+                  <list key="vmap">
+                    <int key="amount" value="3"/>
+                    <float key="price" value="10.5"/>
+                    <boolean key="express" value="True"/>
+                    <date key="due" value="2022-01-05T00:00:00Z"/>
+                    <int key="broken" value="three"/>
+                  </list>
+                </event>
+              </events>
+              <objects>
+                <object>
+                  <string key="id" value="o1"/>
+                  <string key="type" value="order"/>
+                  <list key="ovmap"><string key="status" value="open"/></list>
+                </object>
+              </objects>
+            </log>"#.as_bytes()).expect("valid xml");
+        let log = xml_tree_to_ocel(&root, &ImportOptions::default()).expect("valid xmlocel").0;
+
+        assert_eq!(log.global_log["ocel:object-types"], json!(["order"]));
+        assert_eq!(log.global_log["ocel:version"], json!("1.0"));
+        let e1 = &log.events[&0];
+        assert_eq!(e1.timestamp, "2022-01-01T09:00:00Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap());
+        assert_eq!(e1.vmap["amount"], json!(3));
+        assert_eq!(e1.vmap["price"], json!(10.5));
+        assert_eq!(e1.vmap["express"], json!(true));
+        assert_eq!(e1.vmap["due"], json!("2022-01-05T00:00:00Z"));
+        assert_eq!(e1.vmap["broken"], json!("three"));
+        assert_eq!(log.objects[&0].ovmap["status"], json!("open"));
+        assert_eq!(log.objects[&0].events, vec![0]);
+    }
+
+    #[test]
+    fn test_xmlocel_undeclared_object_fails() {
+        let root = parse_xml_tree(r#"<log><events><event>
+            <string key="id" value="e1"/><string key="activity" value="a"/><date key="timestamp" value="2022-01-01T10:00:00Z"/>
+            <list key="omap"><string key="object-id" value="o1"/></list>
+            </event></events></log>"#.as_bytes()).expect("valid xml");
+        assert!(xml_tree_to_ocel(&root, &ImportOptions::default()).is_err());
+    }
+}
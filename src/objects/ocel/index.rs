@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use nohash_hasher::IntMap;
+use std::sync::OnceLock;
+
+use super::Ocel;
+use super::interner::{ActivityId, ObjectTypeId};
+
+/// The index of a log once it is built. A clone starts without one, as it is usually made to be edited.
+#[derive(Debug, Default)]
+pub(crate) struct IndexCell(pub(crate) OnceLock<OcelIndex>);
+
+impl Clone for IndexCell {
+    fn clone(&self) -> Self {
+        IndexCell::default()
+    }
+}
+
+/// Lookups that would otherwise scan the whole log, built from a log by `Ocel::index`.
+/// Event and object ids are sorted within every entry.
+#[derive(Debug, Clone, Default)]
+pub struct OcelIndex {
+    events_by_activity: IntMap<ActivityId, Vec<usize>>,
+    /// Activities that have events, sorted by id.
+    activities: Vec<ActivityId>,
+    objects_by_type: IntMap<ObjectTypeId, Vec<usize>>,
+    /// Position of an event within the lifecycle of each of its objects.
+    lifecycle_positions: IntMap<usize, IntMap<usize, usize>>,
+    /// Events sorted by their timestamp and id, with the timestamps alongside for range queries.
+    events_by_time: Vec<usize>,
+    timestamps: Vec<DateTime<Utc>>
+}
+
+impl OcelIndex {
+    pub fn new(log: &Ocel) -> Self {
+        let mut index = OcelIndex::default();
+
+        for (eid, ev) in &log.events {
+            index.events_by_activity.entry(ev.activity).or_default().push(*eid);
+        }
+        for (oid, obj) in &log.objects {
+            index.objects_by_type.entry(obj.obj_type).or_default().push(*oid);
+            for (pos, eid) in obj.events.iter().enumerate() {
+                index.lifecycle_positions.entry(*eid).or_default().insert(*oid, pos);
+            }
+        }
+        index.events_by_activity.values_mut().for_each(|eids| eids.sort_unstable());
+        index.activities = index.events_by_activity.keys().copied().collect();
+        index.activities.sort_unstable();
+        index.objects_by_type.values_mut().for_each(|oids| oids.sort_unstable());
+
+        let mut by_time: Vec<(DateTime<Utc>, usize)> = log.events.iter().map(|(eid, ev)| (ev.timestamp, *eid)).collect();
+        by_time.sort_unstable();
+        (index.timestamps, index.events_by_time) = by_time.into_iter().unzip();
+        index
+    }
+
+    pub fn events_of_activity(&self, activity: ActivityId) -> &[usize] {
+        self.events_by_activity.get(&activity).map(|eids| eids.as_slice()).unwrap_or_default()
+    }
+
+    pub fn activities(&self) -> &[ActivityId] {
+        &self.activities
+    }
+
+    pub fn objects_of_type(&self, obj_type: ObjectTypeId) -> &[usize] {
+        self.objects_by_type.get(&obj_type).map(|oids| oids.as_slice()).unwrap_or_default()
+    }
+
+    /// Position of an event in the events of an object, `None` if the event does not belong to the object.
+    pub fn lifecycle_position(&self, eid: usize, oid: usize) -> Option<usize> {
+        self.lifecycle_positions.get(&eid)?.get(&oid).copied()
+    }
+
+    /// All events sorted by time, events with the same timestamp by their id.
+    pub fn events_by_time(&self) -> &[usize] {
+        &self.events_by_time
+    }
+
+    /// Events in `[from, to)` sorted by time.
+    pub fn events_between(&self, from: &DateTime<Utc>, to: &DateTime<Utc>) -> &[usize] {
+        let start = self.timestamps.partition_point(|time| time < from);
+        let end = self.timestamps.partition_point(|time| time < to).max(start);
+        &self.events_by_time[start..end]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::objects::ocel::importer::import_ocel;
+    use crate::objects::ocel::builder::{BuilderObject, LogBuilder};
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    #[test]
+    fn test_index_matches_scans() {
+        let index = OCEL.index();
+        for act in OCEL.activities() {
+            let scanned: Vec<usize> = OCEL.events.iter().filter(|(_, ev)| OCEL.activity(ev) == act).map(|(eid, _)| *eid).sorted().collect();
+            assert_eq!(OCEL.events_of_activity(act), scanned.as_slice());
+        }
+        for (oid, obj) in &OCEL.objects {
+            assert!(OCEL.objects_of_type(OCEL.object_type(obj)).contains(oid));
+            for (pos, eid) in obj.events.iter().enumerate() {
+                assert_eq!(index.lifecycle_position(*eid, *oid), Some(pos));
+            }
+        }
+        assert_eq!(OCEL.events_of_activity("not an activity"), &[] as &[usize]);
+        assert_eq!(index.events_by_time().len(), OCEL.events.len());
+    }
+
+    #[test]
+    fn test_events_between() {
+        let index = OCEL.index();
+        let first = OCEL.events[&index.events_by_time()[0]].timestamp;
+        let to = first + Duration::days(2);
+        let scanned = OCEL.events.values().filter(|ev| ev.timestamp >= first && ev.timestamp < to).count();
+        assert_eq!(index.events_between(&first, &to).len(), scanned);
+        assert!(index.events_between(&to, &first).is_empty());
+    }
+
+    #[test]
+    fn test_builder_invalidates() {
+        let mut log = OCEL.clone();
+        let before = log.events_of_activity("place order").len();
+        let time = log.events.values().map(|ev| ev.timestamp).max().unwrap() + Duration::hours(1);
+        log.add_event("late order", time, "place order", vec![BuilderObject("order", "late-o1")], None).unwrap();
+        assert_eq!(log.events_of_activity("place order").len(), before + 1);
+        assert_eq!(log.objects_of_type("order").len(), OCEL.objects_of_type("order").len() + 1);
+        assert_eq!(log.index().events_by_time().last(), log.event_map.get_by_left("late order"));
+
+        log.remove_event("late order").unwrap();
+        assert_eq!(log.events_of_activity("place order").len(), before);
+    }
+
+    #[test]
+    fn test_clone_and_edit() {
+        OCEL.index();
+        let mut log = OCEL.clone();
+        assert!(log.index.0.get().is_none());
+
+        let place_order = log.activity_id("place order").unwrap();
+        let pay_order = log.intern_activity("pay order");
+        let before = log.events_of_activity("pay order").len();
+        log.events_mut().values_mut().filter(|ev| ev.activity == place_order).for_each(|ev| ev.activity = pay_order);
+        assert!(log.events_of_activity("place order").is_empty());
+        assert_eq!(log.events_of_activity("pay order").len(), before + OCEL.events_of_activity("place order").len());
+    }
+}
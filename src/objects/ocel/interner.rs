@@ -0,0 +1,72 @@
+use ahash::AHashMap;
+
+/// Id of an activity, its position in `Ocel::activity_table`.
+pub type ActivityId = u32;
+/// Id of an object type, its position in `Ocel::object_type_table`.
+pub type ObjectTypeId = u32;
+
+/// Names that are stored once and referenced by their position in the table.
+/// Names are never removed, so ids stay valid while the log is edited.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    names: Vec<String>,
+    ids: AHashMap<String, u32>
+}
+
+impl Interner {
+    /// Id of a name, the name is added if it is not part of the table yet.
+    pub fn intern(&mut self, name: &str) -> u32 {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = u32::try_from(self.names.len()).expect("fewer than u32::MAX names are interned");
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), id);
+        id
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&str> {
+        self.names.get(id as usize).map(|name| name.as_str())
+    }
+
+    /// Name of an id of this table, ids of other tables panic like an index out of bounds.
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Names in the order of their ids.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_str())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut table = Interner::default();
+        assert_eq!(table.intern("place order"), 0);
+        assert_eq!(table.intern("pay order"), 1);
+        assert_eq!(table.intern("place order"), 0);
+        assert_eq!(table.id("pay order"), Some(1));
+        assert_eq!(table.id("ship order"), None);
+        assert_eq!(table.name(1), "pay order");
+        assert_eq!(table.get(2), None);
+        assert_eq!(table.names().collect::<Vec<&str>>(), vec!["place order", "pay order"]);
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use ahash::{AHashMap, AHashSet};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde_json::Value;
+
+use crate::Error;
+use super::Ocel;
+use super::builder::{BuilderObject, LogBuilder};
+
+/// Name of an object in the merged log from the position of its log and its id there.
+pub type ObjectIdentity = Arc<dyn Fn(usize, &str) -> String + Send + Sync>;
+
+/// What to keep when logs disagree on an attribute of the same object or on a global value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Keep the value of the first log that has the attribute.
+    #[default]
+    First,
+    /// Keep the value of the last log that has the attribute.
+    Last,
+    /// Fail with `Error::ReferentialIntegrity`.
+    Error,
+    /// Keep the distinct values as a list, in the order of the logs.
+    Collect
+}
+
+#[derive(Clone, Default)]
+pub struct MergePolicy {
+    pub conflicts: ConflictPolicy,
+    /// Objects with the same name are unified, the object id is the name without an identity.
+    pub identity: Option<ObjectIdentity>
+}
+
+impl MergePolicy {
+    pub fn with_identity(conflicts: ConflictPolicy, identity: impl Fn(usize, &str) -> String + Send + Sync + 'static) -> Self {
+        MergePolicy { conflicts, identity: Some(Arc::new(identity)) }
+    }
+
+    fn object_name(&self, log: usize, oid: &str) -> String {
+        match &self.identity {
+            Some(identity) => identity(log, oid),
+            None => oid.to_owned()
+        }
+    }
+}
+
+/// Activity, timestamp, names of the objects and the sorted attributes of an event.
+type EventKey = (String, DateTime<Utc>, Vec<String>, String);
+
+/// Merge logs into one with new internal ids. Objects with the same name are unified and must have
+/// the same type, their attributes are combined by the conflict policy. Events with the same activity,
+/// timestamp, objects and attributes are only kept once, other events whose id is already taken are
+/// renamed to `<id>#<position of their log>`, or the next free number after it. Lists in the `global_*` sections are joined.
+pub fn merge_ocels(logs: &[Ocel], policy: &MergePolicy) -> Result<Ocel, Error> {
+    let mut merged = Ocel::default();
+    // values of the attributes per object that conflicted so far, for the collect policy
+    let mut collected: AHashMap<(usize, String), Vec<Value>> = AHashMap::new();
+    let mut collected_globals: [AHashMap<String, Vec<Value>>; 3] = Default::default();
+
+    for (i, log) in logs.iter().enumerate() {
+        let [collected_log, collected_event, collected_object] = &mut collected_globals;
+        for (scope, globals, collected) in [(&mut merged.global_log, &log.global_log, collected_log), (&mut merged.global_event, &log.global_event, collected_event), (&mut merged.global_object, &log.global_object, collected_object)] {
+            for (key, value) in globals.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+                merge_global(scope, collected, key, value, policy.conflicts)?;
+            }
+        }
+
+        for oid in log.objects.keys().sorted() {
+            let obj = &log.objects[oid];
+            let Some(oid_str) = log.object_map.get_by_right(oid) else {
+                continue;
+            };
+            let name = policy.object_name(i, oid_str);
+            let obj_type = log.object_type(obj);
+            let Some(merged_oid) = merged.object_map.get_by_left(&name).copied() else {
+                let merged_oid = merged.add_object(&name, obj_type, Some(obj.ovmap.clone()))?;
+                merged.objects_mut().get_mut(&merged_oid).expect("the object was just added").history = obj.history.clone();
+                continue;
+            };
+
+            let merged_type = merged.object_type(merged.objects.get(&merged_oid).expect("object map and objects are kept together"));
+            if merged_type != obj_type {
+                return Err(Error::ReferentialIntegrity(format!("Object {} is of type {} and {}.", name, merged_type, obj_type)));
+            }
+            let merged_obj = merged.objects_mut().get_mut(&merged_oid).expect("checked above");
+            for (key, value) in obj.ovmap.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+                let Some(current) = merged_obj.ovmap.get(key) else {
+                    merged_obj.ovmap.insert(key.to_owned(), value.to_owned());
+                    continue;
+                };
+                if current == value && !collected.contains_key(&(merged_oid, key.to_owned())) {
+                    continue;
+                }
+                match policy.conflicts {
+                    ConflictPolicy::First => {},
+                    ConflictPolicy::Last => {merged_obj.ovmap.insert(key.to_owned(), value.to_owned());},
+                    ConflictPolicy::Error => return Err(Error::ReferentialIntegrity(format!("Object {} has the conflicting values {} and {} for {}.", name, current, value, key))),
+                    ConflictPolicy::Collect => {
+                        let values = collected.entry((merged_oid, key.to_owned())).or_insert_with(|| vec![current.to_owned()]);
+                        if !values.contains(value) {
+                            values.push(value.to_owned());
+                        }
+                    }
+                }
+            }
+            for (key, changes) in &obj.history {
+                let merged_changes = merged_obj.history.entry(key.to_owned()).or_default();
+                for change in changes {
+                    if !merged_changes.contains(change) {
+                        merged_changes.push(change.to_owned());
+                    }
+                }
+                merged_changes.sort_by_key(|(time, _)| *time);
+            }
+        }
+
+        for (oid, links) in &log.o2o {
+            let Some(source) = log.object_map.get_by_right(oid) else {
+                continue;
+            };
+            for link in links {
+                if let Some(target) = log.object_map.get_by_right(&link.target) {
+                    merged.link_objects(&policy.object_name(i, source), &policy.object_name(i, target), &link.qualifier)?;
+                }
+            }
+        }
+    }
+
+    for ((merged_oid, key), values) in collected {
+        merged.objects_mut().get_mut(&merged_oid).expect("only merged objects are collected").ovmap.insert(key, Value::Array(values));
+    }
+    let [collected_log, collected_event, collected_object] = collected_globals;
+    for (scope, collected) in [(&mut merged.global_log, collected_log), (&mut merged.global_event, collected_event), (&mut merged.global_object, collected_object)] {
+        scope.extend(collected.into_iter().map(|(key, values)| (key, Value::Array(values))));
+    }
+
+    let mut seen: AHashMap<EventKey, String> = AHashMap::new();
+    for (i, log) in logs.iter().enumerate() {
+        for eid in log.events.keys().sorted() {
+            let ev = &log.events[eid];
+            let Some(eid_str) = log.event_map.get_by_right(eid) else {
+                continue;
+            };
+            let objects: Vec<(String, usize)> = ev.omap.iter()
+                                                   .filter_map(|oid| Some((policy.object_name(i, log.object_map.get_by_right(oid)?), *oid)))
+                                                   .sorted()
+                                                   .collect();
+            let vmap: BTreeMap<&String, &Value> = ev.vmap.iter().collect();
+            let key: EventKey = (log.activity(ev).to_owned(), ev.timestamp, objects.iter().map(|(name, _)| name.to_owned()).collect(), serde_json::to_string(&vmap)?);
+
+            let name = match seen.get(&key) {
+                Some(name) => name.to_owned(),
+                None => {
+                    let mut name = eid_str.to_owned();
+                    let mut suffix = i;
+                    while merged.event_map.contains_left(&name) {
+                        name = format!("{}#{}", eid_str, suffix);
+                        suffix += 1;
+                    }
+                    let typed_objects = objects.iter().map(|(name, oid)| BuilderObject(log.object_type(&log.objects[oid]), name)).collect();
+                    let merged_eid = merged.add_event(&name, ev.timestamp, log.activity(ev), typed_objects, Some(ev.vmap.clone()))?;
+                    merged.events_mut().get_mut(&merged_eid).expect("the event was just added").offset = ev.offset;
+                    seen.insert(key, name.to_owned());
+                    name
+                }
+            };
+            for (object_name, oid) in &objects {
+                for qualifier in ev.qualifiers.get(oid).into_iter().flatten() {
+                    merged.qualify_object(&name, object_name, qualifier)?;
+                }
+            }
+        }
+    }
+
+    merged.reindex_chronologically();
+    merged.infer_schema();
+    Ok(merged)
+}
+
+/// Collected values are kept apart in `collected` until all logs are merged, so that they are not mistaken for lists.
+fn merge_global(scope: &mut AHashMap<String, Value>, collected: &mut AHashMap<String, Vec<Value>>, key: &str, value: &Value, conflicts: ConflictPolicy) -> Result<(), Error> {
+    let Some(current) = scope.get_mut(key) else {
+        scope.insert(key.to_owned(), value.to_owned());
+        return Ok(());
+    };
+    match (current, value) {
+        (Value::Array(items), Value::Array(new_items)) => {
+            let known: AHashSet<String> = items.iter().map(|item| item.to_string()).collect();
+            items.extend(new_items.iter().filter(|item| !known.contains(&item.to_string())).cloned());
+        },
+        (current, value) if current == value => {},
+        (current, value) => match conflicts {
+            ConflictPolicy::First => {},
+            ConflictPolicy::Last => *current = value.to_owned(),
+            ConflictPolicy::Error => return Err(Error::ReferentialIntegrity(format!("The logs have the conflicting values {} and {} for {}.", current, value, key))),
+            ConflictPolicy::Collect => {
+                let values = collected.entry(key.to_owned()).or_insert_with(|| vec![current.to_owned()]);
+                if !values.contains(value) {
+                    values.push(value.to_owned());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::objects::ocel::importer::import_ocel;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    fn split_log() -> (Ocel, Ocel) {
+        let mut first = OCEL.clone();
+        let mut second = OCEL.clone();
+        let eids: Vec<String> = OCEL.event_map.left_values().sorted().cloned().collect();
+        for (n, eid) in eids.iter().enumerate() {
+            if n % 2 == 0 {
+                second.remove_event(eid).unwrap();
+            } else if n % 3 != 0 {
+                first.remove_event(eid).unwrap();
+            }
+        }
+        (first, second)
+    }
+
+    #[test]
+    fn test_merge_split_log() {
+        let (first, second) = split_log();
+        let merged = merge_ocels(&[first, second], &MergePolicy::default()).expect("the logs agree");
+        assert_eq!(merged.events.len(), OCEL.events.len());
+        assert_eq!(merged.objects.len(), OCEL.objects.len());
+        assert_eq!(merged.activities().len(), OCEL.activities().len());
+        for (name, oid) in merged.object_map.iter() {
+            let original = &OCEL.objects[OCEL.object_map.get_by_left(name).unwrap()];
+            let events: Vec<&String> = merged.objects[oid].events.iter().map(|eid| merged.event_map.get_by_right(eid).unwrap()).collect();
+            assert_eq!(events, original.events.iter().map(|eid| OCEL.event_map.get_by_right(eid).unwrap()).collect::<Vec<&String>>());
+            assert_eq!(merged.objects[oid].ovmap, original.ovmap);
+        }
+        assert_eq!(merged.global_log, OCEL.global_log);
+        merged.build().expect("the merged log is consistent");
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let mut changed = OCEL.clone();
+        let oid = *changed.object_map.get_by_left("o1").unwrap();
+        changed.objects_mut().get_mut(&oid).unwrap().ovmap.insert("total".to_string(), json!(1));
+        let logs = [OCEL.clone(), changed];
+        let total = |merged: &Ocel| merged.objects[merged.object_map.get_by_left("o1").unwrap()].ovmap["total"].clone();
+
+        let merged = merge_ocels(&logs, &MergePolicy::default()).unwrap();
+        assert_eq!(total(&merged), json!(2999.99));
+        // identical events are only kept once
+        assert_eq!(merged.events.len(), OCEL.events.len());
+
+        let merged = merge_ocels(&logs, &MergePolicy { conflicts: ConflictPolicy::Last, ..Default::default() }).unwrap();
+        assert_eq!(total(&merged), json!(1));
+
+        let merged = merge_ocels(&logs, &MergePolicy { conflicts: ConflictPolicy::Collect, ..Default::default() }).unwrap();
+        assert_eq!(total(&merged), json!([2999.99, 1]));
+        assert_eq!(merged.objects[merged.object_map.get_by_left("o1").unwrap()].ovmap["vat"], json!(250));
+
+        assert!(matches!(merge_ocels(&logs, &MergePolicy { conflicts: ConflictPolicy::Error, ..Default::default() }), Err(Error::ReferentialIntegrity(_))));
+    }
+
+    #[test]
+    fn test_merge_collected_globals() {
+        let logs: Vec<Ocel> = ["0.1", "1.0", "2.0", "1.0"].iter().map(|version| {
+            let mut log = OCEL.clone();
+            log.global_log.insert("ocel:version".to_string(), json!(version));
+            log
+        }).collect();
+        let merged = merge_ocels(&logs, &MergePolicy { conflicts: ConflictPolicy::Collect, ..Default::default() }).unwrap();
+        assert_eq!(merged.global_log["ocel:version"], json!(["0.1", "1.0", "2.0"]));
+        assert_eq!(merged.global_log["ocel:object-types"], OCEL.global_log["ocel:object-types"]);
+    }
+
+    #[test]
+    fn test_merge_taken_event_names() {
+        // the first log already uses the name an event of the second log would be renamed to
+        let mut first = OCEL.clone();
+        let (_, eid) = first.event_map.remove_by_left("e1").unwrap();
+        first.event_map.insert("e1#1".to_string(), eid);
+        let time = first.events[&eid].timestamp;
+        first.add_event("e1", time, "place order", vec![], None).unwrap();
+        let mut second = OCEL.clone();
+        let eid = *second.event_map.get_by_left("e1").unwrap();
+        second.events_mut().get_mut(&eid).unwrap().vmap.insert("changed".to_string(), json!(true));
+
+        let merged = merge_ocels(&[first, second], &MergePolicy::default()).unwrap();
+        assert!(["e1", "e1#1", "e1#2"].iter().all(|name| merged.event_map.contains_left(*name)));
+        assert_eq!(merged.events.len(), OCEL.events.len() + 2);
+        merged.build().expect("the merged log is consistent");
+    }
+
+    #[test]
+    fn test_merge_with_identity() {
+        let policy = MergePolicy::with_identity(ConflictPolicy::First, |log, oid| format!("{}-{}", log, oid));
+        let merged = merge_ocels(&[OCEL.clone(), OCEL.clone()], &policy).unwrap();
+        assert_eq!(merged.objects.len(), 2 * OCEL.objects.len());
+        assert_eq!(merged.events.len(), 2 * OCEL.events.len());
+        assert!(merged.object_map.contains_left("1-o1"));
+        let renamed = merged.event_map.left_values().filter(|name| name.ends_with("#1")).count();
+        assert_eq!(renamed, OCEL.events.len());
+        merged.build().expect("the merged log is consistent");
+    }
+}
@@ -1,11 +1,56 @@
 pub(crate) mod variants;
-use std::error::Error;
-use crate::objects::ocel::validator::variants::jsonocel::{validate_json, validate_json_verbose};
+use crate::Error;
+use std::io::Read;
+use crate::objects::ocel::Ocel;
+use crate::objects::ocel::importer::ImportOptions;
+use crate::objects::ocel::validator::variants::semantic::{validate_imported_log, validate_json_semantics, validate_json_semantics_from_reader};
+use crate::objects::ocel::validator::variants::jsonocel::{validate_json, validate_json_from_reader, validate_json_verbose, validate_json_verbose_from_reader};
 
-pub fn validate_ocel(file_path: &str) -> Result<bool, Box<dyn Error>>{
-    return validate_json(file_path);
+/// A problem with the content of a log that the json schema cannot express.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    DanglingObjectReference { event: String, object: String },
+    ObjectWithoutEvents { object: String },
+    EventWithoutObjects { event: String },
+    /// The timestamp does not parse with the import options, the importers read it as 1970-01-01.
+    FallbackTimestamp { event: String, value: String },
+    DuplicateEventId { event: String },
+    DuplicateObjectId { object: String },
+    UndeclaredEventAttribute { event: String, key: String },
+    UndeclaredObjectAttribute { object: String, key: String },
+    /// The json type differs from the type of the first value of the same key.
+    InconsistentEventAttributeType { event: String, key: String, expected: &'static str, found: &'static str },
+    InconsistentObjectAttributeType { object: String, key: String, expected: &'static str, found: &'static str }
 }
 
-pub fn validate_ocel_verbose(file_path: &str) -> Result<Vec<(String, String)>, Box<dyn Error>>{
-    return validate_json_verbose(file_path);
+pub fn validate_ocel(file_path: &str) -> Result<bool, Error>{
+    validate_json(file_path)
+}
+
+/// `validate_ocel` for a log that is read from a reader, in-memory logs can be passed as `&[u8]`.
+pub fn validate_ocel_from_reader<R: Read>(reader: R) -> Result<bool, Error>{
+    validate_json_from_reader(reader)
+}
+
+pub fn validate_ocel_verbose(file_path: &str) -> Result<Vec<(String, String)>, Error>{
+    validate_json_verbose(file_path)
+}
+
+pub fn validate_ocel_verbose_from_reader<R: Read>(reader: R) -> Result<Vec<(String, String)>, Error>{
+    validate_json_verbose_from_reader(reader)
+}
+
+/// Check the content of a jsonocel log, the log should already match the json schema.
+/// Timestamps are read as the importers read them with the options, the default options if there are none.
+pub fn validate_ocel_semantics(file_path: &str, options: Option<&ImportOptions>) -> Result<Vec<ValidationIssue>, Error>{
+    validate_json_semantics(file_path, options.unwrap_or(&ImportOptions::default()))
+}
+
+pub fn validate_ocel_semantics_from_reader<R: Read>(reader: R, options: Option<&ImportOptions>) -> Result<Vec<ValidationIssue>, Error>{
+    validate_json_semantics_from_reader(reader, options.unwrap_or(&ImportOptions::default()))
+}
+
+/// Check the references and attributes of a log in memory, e.g. one put together by hand.
+pub fn validate_log(log: &Ocel) -> Vec<ValidationIssue> {
+    validate_imported_log(log)
 }
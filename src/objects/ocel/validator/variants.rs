@@ -1 +1,2 @@
 pub(super) mod jsonocel;
+pub(super) mod semantic;
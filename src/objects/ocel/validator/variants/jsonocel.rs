@@ -1,22 +1,41 @@
 use jsonschema::JSONSchema;
 use serde_json::Value;
-use std::{error::Error, fs};
+use std::{fs::File, io::Read};
+use crate::Error;
 
-pub(crate) fn validate_json(file_path: &str) -> Result<bool, Box<dyn Error>> {
-    let log = fs::read_to_string(file_path)?;
+pub(crate) fn validate_json(file_path: &str) -> Result<bool, Error> {
+    validate_json_from_reader(File::open(file_path)?)
+}
+
+pub(crate) fn validate_json_from_reader<R: Read>(mut reader: R) -> Result<bool, Error> {
+    let mut buf: Vec<u8> = vec![];
+    reader.read_to_end(&mut buf)?;
+    validate_json_from_slice(&buf)
+}
+
+pub(crate) fn validate_json_from_slice(bytes: &[u8]) -> Result<bool, Error> {
     let schema =  serde_json::from_str(include_str!("schema.json")).expect("JSON schema has been moved?");
     let compiled = JSONSchema::compile(&schema).expect("Schema is not valid.");
-    let json_log: Value = serde_json::from_str(&log.as_str()).unwrap();
+    let json_log: Value = serde_json::from_slice(bytes)?;
 
     Ok(compiled.is_valid(&json_log))
 }
 
-pub(crate) fn validate_json_verbose(file_path: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
-    let log = fs::read_to_string(file_path)?;
+pub(crate) fn validate_json_verbose(file_path: &str) -> Result<Vec<(String, String)>, Error> {
+    validate_json_verbose_from_reader(File::open(file_path)?)
+}
+
+pub(crate) fn validate_json_verbose_from_reader<R: Read>(mut reader: R) -> Result<Vec<(String, String)>, Error> {
+    let mut buf: Vec<u8> = vec![];
+    reader.read_to_end(&mut buf)?;
+    validate_json_verbose_from_slice(&buf)
+}
+
+pub(crate) fn validate_json_verbose_from_slice(bytes: &[u8]) -> Result<Vec<(String, String)>, Error> {
     let schema =  serde_json::from_str(include_str!("schema.json"))?;
     let compiled = JSONSchema::compile(&schema).expect("What have you done with the existing json schema?");
     
-    let json_log: Value = serde_json::from_str(&log.as_str())?;
+    let json_log: Value = serde_json::from_slice(bytes)?;
     let result = compiled.validate(&json_log);
     let mut extracted_errors: Vec<(String, String)> = vec![];
 
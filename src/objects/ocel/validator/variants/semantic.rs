@@ -0,0 +1,283 @@
+use ahash::{AHashMap, AHashSet};
+use itertools::Itertools;
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+use crate::objects::ocel::{Ocel, TimestampParser};
+use crate::objects::ocel::importer::ImportOptions;
+use crate::objects::ocel::validator::ValidationIssue;
+
+#[derive(Deserialize, Default)]
+struct RawLog {
+    #[serde(rename = "ocel:global-log", default)]
+    global_log: Map<String, Value>,
+    #[serde(rename = "ocel:global-event", default)]
+    global_event: Map<String, Value>,
+    #[serde(rename = "ocel:global-object", default)]
+    global_object: Map<String, Value>,
+    #[serde(rename = "ocel:events", default)]
+    events: Entries,
+    #[serde(rename = "ocel:objects", default)]
+    objects: Entries
+}
+
+/// Entries of a json map in document order, keeping repeated keys that a `Map` would merge.
+#[derive(Default)]
+struct Entries(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for Entries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = Entries;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map of jsonocel entries")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Entries, A::Error> {
+                let mut entries = vec![];
+                while let Some(entry) = map.next_entry::<String, Value>()? {
+                    entries.push(entry);
+                }
+                Ok(Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+pub(crate) fn validate_json_semantics(file_path: &str, options: &ImportOptions) -> Result<Vec<ValidationIssue>, Error> {
+    validate_json_semantics_from_reader(File::open(file_path)?, options)
+}
+
+pub(crate) fn validate_json_semantics_from_reader<R: Read>(reader: R, options: &ImportOptions) -> Result<Vec<ValidationIssue>, Error> {
+    let raw: RawLog = serde_json::from_reader(BufReader::new(reader))?;
+    Ok(check_raw_log(&raw, options))
+}
+
+fn check_raw_log(raw: &RawLog, options: &ImportOptions) -> Vec<ValidationIssue> {
+    let timestamps = TimestampParser::new(options);
+    let mut checker = AttributeChecker::new(&raw.global_log, raw.global_event.keys(), raw.global_object.keys());
+    let mut issues: Vec<ValidationIssue> = vec![];
+
+    let mut object_ids: AHashSet<&str> = AHashSet::new();
+    for (oid, obj) in &raw.objects.0 {
+        if !object_ids.insert(oid) {
+            issues.push(ValidationIssue::DuplicateObjectId { object: oid.to_owned() });
+        }
+        if let Some(Value::Object(ovmap)) = obj.get("ocel:ovmap") {
+            checker.check_object(&mut issues, oid, ovmap.iter());
+        }
+    }
+
+    let mut event_ids: AHashSet<&str> = AHashSet::new();
+    let mut referenced: AHashSet<&str> = AHashSet::new();
+    for (eid, ev) in &raw.events.0 {
+        if !event_ids.insert(eid) {
+            issues.push(ValidationIssue::DuplicateEventId { event: eid.to_owned() });
+        }
+        match ev.get("ocel:timestamp") {
+            Some(Value::String(ts)) if timestamps.parses(ts) => {},
+            Some(value) => issues.push(ValidationIssue::FallbackTimestamp { event: eid.to_owned(), value: value.as_str().map(|s| s.to_owned()).unwrap_or(value.to_string()) }),
+            None => issues.push(ValidationIssue::FallbackTimestamp { event: eid.to_owned(), value: String::new() })
+        }
+
+        let omap: Vec<&str> = match ev.get("ocel:omap") {
+            Some(Value::Array(oids)) => oids.iter().filter_map(|oid| oid.as_str()).collect(),
+            _ => vec![]
+        };
+        if omap.is_empty() {
+            issues.push(ValidationIssue::EventWithoutObjects { event: eid.to_owned() });
+        }
+        for oid in omap {
+            if object_ids.contains(oid) {
+                referenced.insert(oid);
+            } else {
+                issues.push(ValidationIssue::DanglingObjectReference { event: eid.to_owned(), object: oid.to_owned() });
+            }
+        }
+
+        if let Some(Value::Object(vmap)) = ev.get("ocel:vmap") {
+            checker.check_event(&mut issues, eid, vmap.iter());
+        }
+    }
+
+    for (oid, _) in raw.objects.0.iter().unique_by(|(oid, _)| oid) {
+        if !referenced.contains(oid.as_str()) {
+            issues.push(ValidationIssue::ObjectWithoutEvents { object: oid.to_owned() });
+        }
+    }
+
+    issues
+}
+
+/// Checks of a log that was already imported. Duplicate ids and fallback timestamps cannot be
+/// seen anymore at this point, so only the references and attributes are checked.
+pub(crate) fn validate_imported_log(log: &Ocel) -> Vec<ValidationIssue> {
+    let global_log: Map<String, Value> = log.global_log.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect();
+    let mut checker = AttributeChecker::new(&global_log, log.global_event.keys(), log.global_object.keys());
+    let mut issues: Vec<ValidationIssue> = vec![];
+    let object_name = |oid: &usize| log.object_map.get_by_right(oid).map(|s| s.to_owned()).unwrap_or(format!("#{}", oid));
+    let event_name = |eid: &usize| log.event_map.get_by_right(eid).map(|s| s.to_owned()).unwrap_or(format!("#{}", eid));
+
+    for oid in log.objects.keys().sorted() {
+        checker.check_object(&mut issues, &object_name(oid), log.objects[oid].ovmap.iter().sorted_by(|a, b| a.0.cmp(b.0)));
+    }
+
+    let mut referenced: AHashSet<usize> = AHashSet::new();
+    for eid in log.events.keys().sorted() {
+        let ev = &log.events[eid];
+        if ev.omap.is_empty() {
+            issues.push(ValidationIssue::EventWithoutObjects { event: event_name(eid) });
+        }
+        for oid in ev.omap.iter().sorted() {
+            if log.objects.contains_key(oid) {
+                referenced.insert(*oid);
+            } else {
+                issues.push(ValidationIssue::DanglingObjectReference { event: event_name(eid), object: object_name(oid) });
+            }
+        }
+        checker.check_event(&mut issues, &event_name(eid), ev.vmap.iter().sorted_by(|a, b| a.0.cmp(b.0)));
+    }
+
+    for oid in log.objects.keys().sorted() {
+        if !referenced.contains(oid) {
+            issues.push(ValidationIssue::ObjectWithoutEvents { object: object_name(oid) });
+        }
+    }
+
+    issues
+}
+
+/// Attribute keys have to be declared in `ocel:attribute-names` or in the global event or
+/// object section, and each key keeps the json type it was first seen with.
+struct AttributeChecker {
+    declared_event: AHashSet<String>,
+    declared_object: AHashSet<String>,
+    event_types: AHashMap<String, &'static str>,
+    object_types: AHashMap<String, &'static str>
+}
+
+impl AttributeChecker {
+    fn new<'a>(global_log: &Map<String, Value>, global_event: impl Iterator<Item = &'a String>, global_object: impl Iterator<Item = &'a String>) -> Self {
+        let attribute_names: Vec<String> = match global_log.get("ocel:attribute-names") {
+            Some(Value::Array(names)) => names.iter().filter_map(|name| name.as_str()).map(|name| name.to_owned()).collect(),
+            _ => vec![]
+        };
+        let with_globals = |globals: Vec<&String>| -> AHashSet<String> {
+            attribute_names.iter().cloned()
+                           .chain(globals.iter().map(|key| key.strip_prefix("ocel:").unwrap_or(key).to_owned()))
+                           .collect()
+        };
+
+        AttributeChecker { declared_event: with_globals(global_event.collect()),
+                           declared_object: with_globals(global_object.collect()),
+                           event_types: AHashMap::new(),
+                           object_types: AHashMap::new() }
+    }
+
+    fn check_event<'v>(&mut self, issues: &mut Vec<ValidationIssue>, eid: &str, attributes: impl Iterator<Item = (&'v String, &'v Value)>) {
+        for (key, value) in attributes {
+            if !self.declared_event.contains(key) {
+                issues.push(ValidationIssue::UndeclaredEventAttribute { event: eid.to_owned(), key: key.to_owned() });
+            }
+            if let Some(found) = json_type(value) {
+                let expected = *self.event_types.entry(key.to_owned()).or_insert(found);
+                if expected != found {
+                    issues.push(ValidationIssue::InconsistentEventAttributeType { event: eid.to_owned(), key: key.to_owned(), expected, found });
+                }
+            }
+        }
+    }
+
+    fn check_object<'v>(&mut self, issues: &mut Vec<ValidationIssue>, oid: &str, attributes: impl Iterator<Item = (&'v String, &'v Value)>) {
+        for (key, value) in attributes {
+            if !self.declared_object.contains(key) {
+                issues.push(ValidationIssue::UndeclaredObjectAttribute { object: oid.to_owned(), key: key.to_owned() });
+            }
+            if let Some(found) = json_type(value) {
+                let expected = *self.object_types.entry(key.to_owned()).or_insert(found);
+                if expected != found {
+                    issues.push(ValidationIssue::InconsistentObjectAttributeType { object: oid.to_owned(), key: key.to_owned(), expected, found });
+                }
+            }
+        }
+    }
+}
+
+/// Integers and floats are both numbers, nulls fit every type.
+fn json_type(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some("boolean"),
+        Value::Number(_) => Some("number"),
+        Value::String(_) => Some("string"),
+        Value::Array(_) => Some("list"),
+        Value::Object(_) => Some("map")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ocel::importer::import_ocel;
+
+    #[test]
+    fn test_valid_log_has_no_issues() {
+        assert_eq!(validate_json_semantics("logs/min.jsonocel", &ImportOptions::default()).expect("What did you do to the file?"), vec![]);
+    }
+
+    #[test]
+    fn test_imported_log_matches_file() {
+        // the attributes of this log are not all declared in the global log
+        let issues = validate_json_semantics("logs/ocel-complex-test.jsonocel", &ImportOptions::default()).expect("What did you do to the file?");
+        assert!(issues.iter().any(|issue| matches!(issue, ValidationIssue::UndeclaredObjectAttribute { .. })));
+        let log = import_ocel("logs/ocel-complex-test.jsonocel").unwrap();
+        assert_eq!(validate_imported_log(&log).into_iter().sorted_by_key(|issue| format!("{:?}", issue)).collect::<Vec<_>>(),
+                   issues.into_iter().sorted_by_key(|issue| format!("{:?}", issue)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_semantic_issues() {
+        let issues = validate_json_semantics_from_reader(br#"{
+            "ocel:global-log": {"ocel:attribute-names": ["price", "resource"]},
+            "ocel:global-event": {"ocel:activity": "__INVALID__"}, "ocel:global-object": {"ocel:type": "__INVALID__"},
+            "ocel:events": {
+                "e1": {"ocel:activity": "a", "ocel:timestamp": "2022-01-01T10:00:00Z", "ocel:omap": ["o1", "o9"], "ocel:vmap": {"resource": "Pete"}},
+                "e2": {"ocel:activity": "b", "ocel:timestamp": "yesterday", "ocel:omap": [], "ocel:vmap": {"resource": 3, "weight": 1}},
+                "e1": {"ocel:activity": "c", "ocel:timestamp": "2022-01-01T12:00:00", "ocel:omap": ["o1"], "ocel:vmap": {}}
+            },
+            "ocel:objects": {"o1": {"ocel:type": "order", "ocel:ovmap": {"price": 3}}, "o2": {"ocel:type": "order", "ocel:ovmap": {"price": 2.5}}}
+        }"#.as_slice(), &ImportOptions::default()).unwrap();
+
+        assert_eq!(issues, vec![
+            ValidationIssue::DanglingObjectReference { event: "e1".to_string(), object: "o9".to_string() },
+            ValidationIssue::FallbackTimestamp { event: "e2".to_string(), value: "yesterday".to_string() },
+            ValidationIssue::EventWithoutObjects { event: "e2".to_string() },
+            ValidationIssue::InconsistentEventAttributeType { event: "e2".to_string(), key: "resource".to_string(), expected: "string", found: "number" },
+            ValidationIssue::UndeclaredEventAttribute { event: "e2".to_string(), key: "weight".to_string() },
+            ValidationIssue::DuplicateEventId { event: "e1".to_string() },
+            ValidationIssue::ObjectWithoutEvents { object: "o2".to_string() }
+        ]);
+    }
+
+    #[test]
+    fn test_custom_timestamp_formats() {
+        let input = br#"{"ocel:global-log": {}, "ocel:global-event": {}, "ocel:global-object": {}, "ocel:objects": {"o1": {"ocel:type": "order", "ocel:ovmap": {}}},
+            "ocel:events": {"e1": {"ocel:activity": "a", "ocel:timestamp": "01.02.2022 10:00", "ocel:omap": ["o1"], "ocel:vmap": {}}}}"#.as_slice();
+        let fallback = ValidationIssue::FallbackTimestamp { event: "e1".to_string(), value: "01.02.2022 10:00".to_string() };
+        assert_eq!(validate_json_semantics_from_reader(input, &ImportOptions::default()).unwrap(), vec![fallback]);
+
+        let options = ImportOptions { timestamp_formats: vec!["%d.%m.%Y %H:%M".to_string()], ..Default::default() };
+        assert_eq!(validate_json_semantics_from_reader(input, &options).unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,93 @@
+pub(crate) mod jsonocel2;
+pub(crate) mod sqliteocel;
+pub(crate) mod xmlocel;
+
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use nohash_hasher::{IntMap, IntSet};
+use serde_json::{Value, json};
+use crate::Error;
+
+use super::{AttributeHistory, Ocel, OcelEvent, OcelObject, ParsedTimestamp};
+
+/// OCEL 2.0 and XES have no global sections, fill them in the way a jsonocel log would have them.
+pub(crate) fn fill_globals(log: &mut Ocel, version: &str, mut attribute_names: Vec<&str>, object_types: Vec<&str>) {
+    attribute_names.sort_unstable();
+    attribute_names.dedup();
+
+    log.global_log.insert("ocel:version".to_string(), json!(version));
+    log.global_log.insert("ocel:ordering".to_string(), json!("timestamp"));
+    log.global_log.insert("ocel:attribute-names".to_string(), json!(attribute_names));
+    log.global_log.insert("ocel:object-types".to_string(), json!(object_types));
+    log.global_event.insert("ocel:activity".to_string(), json!("__INVALID__"));
+    log.global_object.insert("ocel:type".to_string(), json!("__INVALID__"));
+}
+
+/// Split the timed values of object attributes into the earliest value of each attribute and the changes after it.
+/// Values with the same time keep their order.
+pub(crate) fn split_history(timed: AttributeHistory) -> (AHashMap<String, Value>, AttributeHistory) {
+    let mut ovmap: AHashMap<String, Value> = AHashMap::new();
+    let mut history = AttributeHistory::new();
+    for (key, mut values) in timed {
+        values.sort_by_key(|(time, _)| *time);
+        let mut values = values.into_iter();
+        if let Some((_, initial)) = values.next() {
+            ovmap.insert(key.to_owned(), initial);
+        }
+        let changes: Vec<(DateTime<Utc>, Value)> = values.collect();
+        if !changes.is_empty() {
+            history.insert(key, changes);
+        }
+    }
+    (ovmap, history)
+}
+
+/// Add an object under the next free id, an object id can only be declared once.
+pub(crate) fn declare_object(log: &mut Ocel, oid: &str, obj_type: &str, ovmap: AHashMap<String, Value>, history: AttributeHistory) -> Result<usize, Error> {
+    let oid_nh = log.objects.len();
+    if log.object_map.insert_no_overwrite(oid.to_owned(), oid_nh).is_err() {
+        return Err(Error::ReferentialIntegrity(format!("Object id {} is declared more than once.", oid)));
+    }
+    let obj_type = log.intern_object_type(obj_type);
+    log.objects.insert(oid_nh, OcelObject { obj_type, ovmap, events: vec![], history });
+    Ok(oid_nh)
+}
+
+/// Add an event without objects under the next free id, an event id can only be declared once.
+pub(crate) fn declare_event(log: &mut Ocel, eid: &str, activity: &str, (timestamp, offset): ParsedTimestamp, vmap: AHashMap<String, Value>) -> Result<usize, Error> {
+    let eid_nh = log.events.len();
+    if log.event_map.insert_no_overwrite(eid.to_owned(), eid_nh).is_err() {
+        return Err(Error::ReferentialIntegrity(format!("Event id {} is declared more than once.", eid)));
+    }
+    let activity = log.intern_activity(activity);
+    log.events.insert(eid_nh, OcelEvent { activity, timestamp, vmap, omap: IntSet::default(), offset, qualifiers: IntMap::default() });
+    Ok(eid_nh)
+}
+
+/// Id of an event that a relationship refers to.
+pub(crate) fn declared_event(log: &Ocel, eid: &str) -> Result<usize, Error> {
+    log.event_map.get_by_left(eid).copied().ok_or_else(|| Error::ReferentialIntegrity(format!("A relationship references the undeclared event {}.", eid)))
+}
+
+/// Add a declared object to the omap of an event, an empty qualifier leaves the link unqualified.
+/// The events of the objects are sorted once the log is reindexed.
+pub(crate) fn relate_object(log: &mut Ocel, eid_nh: usize, oid: &str, qualifier: &str) -> Result<(), Error> {
+    let Some(oid_nh) = log.object_map.get_by_left(oid).copied() else {
+        let eid = log.event_map.get_by_right(&eid_nh).map(|eid| eid.as_str()).unwrap_or_default();
+        return Err(Error::ReferentialIntegrity(format!("Event {} references the undeclared object {}.", eid, oid)));
+    };
+    let event = log.events.get_mut(&eid_nh).expect("events are declared before they are related");
+    if event.omap.insert(oid_nh) {
+        log.objects.get_mut(&oid_nh).expect("object map and objects are filled together").events.push(eid_nh);
+    }
+    event.qualify(oid_nh, qualifier);
+    Ok(())
+}
+
+/// Link two declared objects.
+pub(crate) fn relate_objects(log: &mut Ocel, source: &str, target: &str, qualifier: &str) -> Result<(), Error> {
+    let source_nh = log.object_map.get_by_left(source).copied().ok_or_else(|| Error::ReferentialIntegrity(format!("A relationship references the undeclared object {}.", source)))?;
+    let target_nh = log.object_map.get_by_left(target).copied().ok_or_else(|| Error::ReferentialIntegrity(format!("Object {} references the undeclared object {}.", source, target)))?;
+    log.link_object(source_nh, target_nh, qualifier);
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Ocel2Serde {
+    #[serde(rename = "objectTypes", default)]
+    pub object_types: Vec<Ocel2TypeSerde>,
+    #[serde(rename = "eventTypes", default)]
+    pub event_types: Vec<Ocel2TypeSerde>,
+    #[serde(default)]
+    pub objects: Vec<Ocel2ObjectSerde>,
+    #[serde(default)]
+    pub events: Vec<Ocel2EventSerde>
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Ocel2TypeSerde {
+    pub name: String,
+    #[serde(default)]
+    pub attributes: Vec<Ocel2AttributeDeclSerde>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Ocel2AttributeDeclSerde {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub attr_type: String
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Ocel2ObjectSerde {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub obj_type: String,
+    #[serde(default)]
+    pub attributes: Vec<Ocel2AttributeSerde>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relationships: Vec<Ocel2RelationshipSerde>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Ocel2EventSerde {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub activity: String,
+    pub time: String,
+    #[serde(default)]
+    pub attributes: Vec<Ocel2AttributeSerde>,
+    #[serde(default)]
+    pub relationships: Vec<Ocel2RelationshipSerde>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Ocel2AttributeSerde {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    pub value: Value
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Ocel2RelationshipSerde {
+    #[serde(rename = "objectId")]
+    pub object_id: String,
+    #[serde(default)]
+    pub qualifier: String
+}
+
+/// Infer the OCEL 2.0 attribute type name of a json value.
+pub(crate) fn infer_attribute_type(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::Number(n) => {if n.is_f64() {"float"} else {"integer"}},
+        Value::String(s) if chrono::DateTime::parse_from_rfc3339(s).is_ok() => "time",
+        _ => "string"
+    }
+}
+
+/// Convert a value that has been written as a string back into its declared OCEL 2.0 type.
+/// Values that cannot be converted are kept as they are.
+pub(crate) fn convert_attribute_value(value: Value, attr_type: Option<&str>) -> Value {
+    let s = match &value {
+        Value::String(s) => s,
+        _ => return value
+    };
+
+    match attr_type {
+        Some("integer") => s.parse::<i64>().map(Value::from).unwrap_or(value),
+        Some("float") => s.parse::<f64>().map(Value::from).unwrap_or(value),
+        Some("boolean") => s.parse::<bool>().map(Value::from).unwrap_or(value),
+        _ => value
+    }
+}
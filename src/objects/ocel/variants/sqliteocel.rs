@@ -0,0 +1,82 @@
+use ahash::AHashSet;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use serde_json::Value;
+
+pub(crate) const EVENT_TABLE: &str = "event";
+pub(crate) const EVENT_MAP_TYPE_TABLE: &str = "event_map_type";
+pub(crate) const OBJECT_TABLE: &str = "object";
+pub(crate) const OBJECT_MAP_TYPE_TABLE: &str = "object_map_type";
+pub(crate) const EVENT_OBJECT_TABLE: &str = "event_object";
+pub(crate) const OBJECT_OBJECT_TABLE: &str = "object_object";
+
+/// Columns of the per-type tables that are not attributes.
+pub(crate) const RESERVED_COLUMNS: [&str; 3] = ["ocel_id", "ocel_time", "ocel_changed_field"];
+
+/// Generate a unique table suffix for a type name, as the standard only allows alphanumeric
+/// characters in the `ocel_type_map` column.
+pub(crate) fn type_map_name(type_name: &str, used: &mut AHashSet<String>) -> String {
+    let base: String = type_name.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    let base = if base.is_empty() {"type".to_string()} else {base};
+    let mut candidate = base.clone();
+    let mut counter: usize = 1;
+    while !used.insert(candidate.to_lowercase()) {
+        candidate = format!("{}{}", base, counter);
+        counter += 1;
+    }
+    candidate
+}
+
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Column type used when writing an attribute.
+pub(crate) fn infer_column_type(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "BOOLEAN",
+        Value::Number(n) => {if n.is_f64() {"REAL"} else {"INTEGER"}},
+        Value::String(s) if chrono::DateTime::parse_from_rfc3339(s).is_ok() => "TIMESTAMP",
+        _ => "TEXT"
+    }
+}
+
+/// Column type that can hold values of both types without sqlite converting them.
+/// Mixed columns are declared without a type, so no type affinity is applied.
+pub(crate) fn merge_column_type(current: &'static str, other: &'static str) -> &'static str {
+    if current == other {current} else {""}
+}
+
+pub(crate) fn json_to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => {
+            match n.as_i64() {
+                Some(i) => SqlValue::Integer(i),
+                None => SqlValue::Real(n.as_f64().unwrap_or(f64::NAN))
+            }
+        },
+        Value::String(s) => SqlValue::Text(s.to_owned()),
+        _ => SqlValue::Text(value.to_string())
+    }
+}
+
+pub(crate) fn sql_to_json(value: ValueRef, column_type: &str) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => {
+            if column_type.eq_ignore_ascii_case("BOOLEAN") {Value::Bool(i != 0)} else {Value::from(i)}
+        },
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(t) => {
+            let s = String::from_utf8_lossy(t).to_string();
+            match column_type.to_uppercase().as_str() {
+                "INTEGER" => s.parse::<i64>().map(Value::from).unwrap_or(Value::String(s)),
+                "REAL" | "FLOAT" | "DOUBLE" => s.parse::<f64>().map(Value::from).unwrap_or(Value::String(s)),
+                "BOOLEAN" => s.parse::<bool>().map(Value::from).unwrap_or(Value::String(s)),
+                _ => Value::String(s)
+            }
+        },
+        ValueRef::Blob(b) => Value::String(String::from_utf8_lossy(b).to_string())
+    }
+}
@@ -0,0 +1,172 @@
+use ahash::AHashMap;
+use itertools::Itertools;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use serde_json::{Map, Value};
+use crate::Error;
+use std::io::{BufRead, Write};
+
+/// Child key used for the entries of a json array, as lists only hold keyed elements.
+pub(crate) const LIST_ITEM_KEY: &str = "item";
+
+/// Generic element of an xmlocel document.
+#[derive(Debug, Default)]
+pub(crate) struct XmlNode {
+    pub tag: String,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub children: Vec<XmlNode>
+}
+
+impl XmlNode {
+    pub(crate) fn child(&self, tag: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.tag == tag)
+    }
+
+    pub(crate) fn keyed_child(&self, key: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.key.as_deref() == Some(key))
+    }
+
+    pub(crate) fn keyed_value(&self, key: &str) -> Option<&str> {
+        self.keyed_child(key).and_then(|c| c.value.as_deref())
+    }
+}
+
+/// Read an xml document into a tree of nodes, the document element has to be `log`.
+/// Shared by xmlocel and XES, which both describe attributes as typed key/value elements.
+pub(crate) fn parse_xml_tree<R: BufRead>(reader: R) -> Result<XmlNode, Error> {
+    let mut reader = Reader::from_reader(reader);
+    reader.trim_text(true);
+    let mut buf: Vec<u8> = vec![];
+    // the bottom of the stack collects the document element
+    let mut stack: Vec<XmlNode> = vec![XmlNode::default()];
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => stack.push(start_to_node(&e)?),
+            Event::Empty(e) => {
+                let node = start_to_node(&e)?;
+                stack.last_mut().expect("the document node is never removed").children.push(node);
+            },
+            Event::End(_) => {
+                let node = stack.pop().expect("the document node is never removed");
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Err(Error::Parse("The xml document closes more elements than it opens.".to_string()))
+                }
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let mut document = stack.pop().expect("the document node is never removed");
+    if !stack.is_empty() {
+        return Err(Error::Parse("The xml document ends before all elements are closed.".to_string()));
+    }
+    match document.children.pop() {
+        Some(root) if root.tag == "log" => Ok(root),
+        _ => Err(Error::Schema("The xml document has no log element.".to_string()))
+    }
+}
+
+fn start_to_node(e: &BytesStart) -> Result<XmlNode, Error> {
+    let mut node = XmlNode { tag: String::from_utf8(e.name().as_ref().to_vec())?, ..Default::default() };
+    for attr in e.attributes() {
+        let attr = attr?;
+        match attr.key.as_ref() {
+            // the scope of a global element is stored as its key
+            b"key" | b"scope" => node.key = Some(attr.unescape_value()?.to_string()),
+            b"value" => node.value = Some(attr.unescape_value()?.to_string()),
+            _ => {}
+        }
+    }
+    Ok(node)
+}
+
+/// Convert a typed xmlocel element into a json value.
+/// Values that do not fit their declared type are kept as strings.
+pub(crate) fn xml_to_value(node: &XmlNode) -> Value {
+    let s = node.value.to_owned().unwrap_or_default();
+    match node.tag.as_str() {
+        "int" => s.parse::<i64>().map(Value::from).unwrap_or(Value::String(s)),
+        "float" => s.parse::<f64>().map(Value::from).unwrap_or(Value::String(s)),
+        "boolean" => s.to_lowercase().parse::<bool>().map(Value::from).unwrap_or(Value::String(s)),
+        "list" => {
+            if node.children.iter().all(|c| c.key.as_deref() == Some(LIST_ITEM_KEY)) {
+                Value::Array(node.children.iter().map(xml_to_value).collect())
+            } else {
+                Value::Object(node.children.iter().map(|c| (c.key.to_owned().unwrap_or_default(), xml_to_value(c))).collect::<Map<String, Value>>())
+            }
+        },
+        _ => Value::String(s)
+    }
+}
+
+/// Element name used to write a json value.
+pub(crate) fn value_tag(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::Number(n) => {if n.is_f64() {"float"} else {"int"}},
+        Value::String(s) if chrono::DateTime::parse_from_rfc3339(s).is_ok() => "date",
+        Value::Array(_) | Value::Object(_) => "list",
+        _ => "string"
+    }
+}
+
+pub(crate) fn write_typed<W: Write>(w: &mut Writer<W>, tag: &str, key: &str, value: &str) -> quick_xml::Result<()> {
+    w.create_element(tag).with_attributes([("key", key), ("value", value)]).write_empty()?;
+    Ok(())
+}
+
+pub(crate) fn write_attributes<W: Write>(w: &mut Writer<W>, key: &str, attributes: &AHashMap<String, Value>) -> quick_xml::Result<()> {
+    w.create_element("list").with_attribute(("key", key)).write_inner_content(|w| {
+        for (key, value) in attributes.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            write_value(w, key, value, LIST_ITEM_KEY)?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Write qualified links as strings keyed by the linked object, lists without links are left out.
+pub(crate) fn write_relationships<W: Write>(w: &mut Writer<W>, key: &str, links: Vec<(&str, &str)>) -> quick_xml::Result<()> {
+    if links.is_empty() {
+        return Ok(());
+    }
+    w.create_element("list").with_attribute(("key", key)).write_inner_content(|w| {
+        for (oid, qualifier) in &links {
+            write_typed(w, "string", oid, qualifier)?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+pub(crate) fn write_value<W: Write>(w: &mut Writer<W>, key: &str, value: &Value, item_key: &str) -> quick_xml::Result<()> {
+    match value {
+        // xmlocel has no null, an unset value is left out
+        Value::Null => Ok(()),
+        Value::String(s) => write_typed(w, value_tag(value), key, s),
+        Value::Array(items) => {
+            w.create_element("list").with_attribute(("key", key)).write_inner_content(|w| {
+                for item in items {
+                    write_value(w, item_key, item, LIST_ITEM_KEY)?;
+                }
+                Ok(())
+            })?;
+            Ok(())
+        },
+        Value::Object(map) => {
+            w.create_element("list").with_attribute(("key", key)).write_inner_content(|w| {
+                for (k, v) in map {
+                    write_value(w, k, v, LIST_ITEM_KEY)?;
+                }
+                Ok(())
+            })?;
+            Ok(())
+        },
+        _ => write_typed(w, value_tag(value), key, &value.to_string())
+    }
+}
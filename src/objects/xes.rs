@@ -0,0 +1,28 @@
+pub mod exporter;
+pub mod importer;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+pub const CONCEPT_NAME: &str = "concept:name";
+pub const TIME_TIMESTAMP: &str = "time:timestamp";
+pub const IDENTITY_ID: &str = "identity:id";
+
+/// Case-centric event log, attribute values are typed the same way as in `Ocel`.
+/// Timestamps are RFC 3339 strings and are written as XES dates.
+#[derive(Debug, Clone, Default)]
+pub struct XesLog {
+    pub attributes: IndexMap<String, Value>,
+    pub traces: Vec<XesTrace>
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XesTrace {
+    pub attributes: IndexMap<String, Value>,
+    pub events: Vec<XesEvent>
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XesEvent {
+    pub attributes: IndexMap<String, Value>
+}
@@ -0,0 +1,66 @@
+use std::{fs::OpenOptions, io::{BufWriter, Write}};
+use crate::Error;
+
+use quick_xml::events::{BytesDecl, Event};
+use quick_xml::Writer;
+
+use crate::objects::ocel::variants::xmlocel::{LIST_ITEM_KEY, write_value};
+
+use super::{XesLog, CONCEPT_NAME, TIME_TIMESTAMP};
+
+const EXTENSIONS: [(&str, &str, &str); 4] = [("Concept", "concept", "http://www.xes-standard.org/concept.xesext"),
+                                             ("Time", "time", "http://www.xes-standard.org/time.xesext"),
+                                             ("Identity", "identity", "http://www.xes-standard.org/identity.xesext"),
+                                             ("Organizational", "org", "http://www.xes-standard.org/org.xesext")];
+
+pub fn export_xes(log: &XesLog, file_path: &str) -> Result<bool, Error> {
+    let output_file = OpenOptions::new().create(true).write(true).truncate(true).open(file_path)?;
+    export_xes_to_writer(log, BufWriter::new(output_file))
+}
+
+pub fn export_xes_to_writer<W: Write>(log: &XesLog, writer: W) -> Result<bool, Error> {
+    let mut writer = Writer::new_with_indent(writer, b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer.create_element("log")
+          .with_attributes([("xes.version", "1.0"), ("xes.features", "nested-attributes"), ("xmlns", "http://www.xes-standard.org/")])
+          .write_inner_content(|w| {
+        for (name, prefix, uri) in EXTENSIONS {
+            w.create_element("extension").with_attributes([("name", name), ("prefix", prefix), ("uri", uri)]).write_empty()?;
+        }
+        w.create_element("global").with_attribute(("scope", "trace")).write_inner_content(|w| {
+            w.create_element("string").with_attributes([("key", CONCEPT_NAME), ("value", "__INVALID__")]).write_empty()?;
+            Ok(())
+        })?;
+        w.create_element("global").with_attribute(("scope", "event")).write_inner_content(|w| {
+            w.create_element("string").with_attributes([("key", CONCEPT_NAME), ("value", "__INVALID__")]).write_empty()?;
+            w.create_element("date").with_attributes([("key", TIME_TIMESTAMP), ("value", "1970-01-01T00:00:00+00:00")]).write_empty()?;
+            Ok(())
+        })?;
+        w.create_element("classifier").with_attributes([("name", "Activity"), ("keys", CONCEPT_NAME)]).write_empty()?;
+
+        for (key, value) in &log.attributes {
+            write_value(w, key, value, LIST_ITEM_KEY)?;
+        }
+
+        for trace in &log.traces {
+            w.create_element("trace").write_inner_content(|w| {
+                for (key, value) in &trace.attributes {
+                    write_value(w, key, value, LIST_ITEM_KEY)?;
+                }
+                for event in &trace.events {
+                    w.create_element("event").write_inner_content(|w| {
+                        for (key, value) in &event.attributes {
+                            write_value(w, key, value, LIST_ITEM_KEY)?;
+                        }
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    })?;
+    writer.into_inner().flush()?;
+
+    Ok(true)
+}
@@ -0,0 +1,245 @@
+use crate::Error;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+use ahash::{AHashMap, AHashSet};
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::objects::ocel::{Ocel, AttributeHistory, TimestampParser};
+use crate::objects::ocel::importer::ImportOptions;
+use crate::objects::ocel::variants::{declare_event, declare_object, fill_globals, relate_object};
+use crate::objects::ocel::variants::xmlocel::{XmlNode, parse_xml_tree, xml_to_value};
+
+use super::{XesLog, XesTrace, XesEvent, CONCEPT_NAME, IDENTITY_ID, TIME_TIMESTAMP};
+
+/// Elements that hold an attribute, everything else (`global`, `extension`, `classifier`) describes the log.
+const ATTRIBUTE_TAGS: [&str; 7] = ["string", "date", "int", "float", "boolean", "id", "list"];
+
+/// How the cases of a XES log are turned into objects.
+#[derive(Debug, Clone)]
+pub struct XesImportConfig {
+    /// Object type given to the object of every trace.
+    pub trace_type: String,
+    /// Event attributes whose values become objects in the `omap`, as pairs of attribute key
+    /// and object type, e.g. `("org:resource", "resource")`.
+    pub promoted_attributes: Vec<(String, String)>,
+    /// How the `time:timestamp` values are read.
+    pub timestamps: ImportOptions
+}
+
+impl Default for XesImportConfig {
+    fn default() -> Self {
+        XesImportConfig { trace_type: "case".to_string(), promoted_attributes: vec![], timestamps: ImportOptions::default() }
+    }
+}
+
+pub fn import_xes(file_path: &str) -> Result<XesLog, Error> {
+    import_xes_from_reader(File::open(file_path)?)
+}
+
+pub fn import_xes_from_reader<R: Read>(reader: R) -> Result<XesLog, Error> {
+    let root = parse_xml_tree(BufReader::new(reader))?;
+    let mut log = XesLog::default();
+
+    for node in &root.children {
+        match node.tag.as_str() {
+            "trace" => {
+                let mut trace = XesTrace::default();
+                for child in &node.children {
+                    if child.tag == "event" {
+                        trace.events.push(XesEvent { attributes: xes_attributes(&child.children) });
+                    } else if let Some((key, value)) = xes_attribute(child) {
+                        trace.attributes.insert(key, value);
+                    }
+                }
+                log.traces.push(trace);
+            },
+            _ => {
+                if let Some((key, value)) = xes_attribute(node) {
+                    log.attributes.insert(key, value);
+                }
+            }
+        }
+    }
+
+    Ok(log)
+}
+
+/// Import a XES log as an `Ocel` with one object per trace.
+pub fn import_xes_ocel(file_path: &str, config: &XesImportConfig) -> Result<Ocel, Error> {
+    xes_to_ocel(&import_xes(file_path)?, config)
+}
+
+pub fn import_xes_ocel_from_reader<R: Read>(reader: R, config: &XesImportConfig) -> Result<Ocel, Error> {
+    xes_to_ocel(&import_xes_from_reader(reader)?, config)
+}
+
+/// Turn every trace into an object of the configured type, with `concept:name` as its id and
+/// the remaining trace attributes as its `ovmap`. Events keep their `identity:id` if they have
+/// one, so an event that was copied into several traces becomes a single event again.
+pub fn xes_to_ocel(xes: &XesLog, config: &XesImportConfig) -> Result<Ocel, Error> {
+    let mut log_internal = Ocel::default();
+    let mut timestamps = TimestampParser::new(&config.timestamps);
+    let promoted: AHashMap<&str, &str> = config.promoted_attributes.iter().map(|(key, obj_type)| (key.as_str(), obj_type.as_str())).collect();
+    let mut attribute_names: AHashSet<&str> = AHashSet::new();
+    let mut object_types: Vec<&str> = vec![];
+
+    for (trace_num, trace) in xes.traces.iter().enumerate() {
+        let trace_oid = match trace.attributes.get(CONCEPT_NAME) {
+            Some(Value::String(name)) => name.to_owned(),
+            Some(value) => value.to_string(),
+            None => format!("trace {}", trace_num)
+        };
+        let ovmap: AHashMap<String, Value> = trace.attributes.iter()
+                                                  .filter(|(key, _)| key.as_str() != CONCEPT_NAME)
+                                                  .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                                                  .collect();
+        attribute_names.extend(trace.attributes.keys().filter(|key| ovmap.contains_key(*key)).map(|key| key.as_str()));
+        declare_object(&mut log_internal, &trace_oid, &config.trace_type, ovmap, AttributeHistory::new())?;
+        if !object_types.contains(&config.trace_type.as_str()) {
+            object_types.push(&config.trace_type);
+        }
+
+        for (event_num, event) in trace.events.iter().enumerate() {
+            let eid = match event.attributes.get(IDENTITY_ID) {
+                Some(Value::String(id)) => id.to_owned(),
+                _ => format!("{}:{}", trace_oid, event_num)
+            };
+
+            let eid_nh = match log_internal.event_map.get_by_left(&eid) {
+                Some(eid_nh) => *eid_nh,
+                None => {
+                    let activity = match event.attributes.get(CONCEPT_NAME) {
+                        Some(Value::String(activity)) => activity,
+                        _ => return Err(Error::Schema(format!("Event {} has no concept:name.", eid)))
+                    };
+                    let timestamp = match event.attributes.get(TIME_TIMESTAMP) {
+                        Some(Value::String(timestamp)) => timestamps.parse(timestamp)?,
+                        _ => return Err(Error::Schema(format!("Event {} has no time:timestamp.", eid)))
+                    };
+                    let vmap: AHashMap<String, Value> = event.attributes.iter()
+                                                             .filter(|(key, _)| ![CONCEPT_NAME, TIME_TIMESTAMP, IDENTITY_ID].contains(&key.as_str()) && !promoted.contains_key(key.as_str()))
+                                                             .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                                                             .collect();
+                    attribute_names.extend(event.attributes.keys().filter(|key| vmap.contains_key(*key)).map(|key| key.as_str()));
+
+                    let eid_nh = declare_event(&mut log_internal, &eid, activity, timestamp, vmap)?;
+
+                    for (key, value) in event.attributes.iter().filter(|(key, _)| promoted.contains_key(key.as_str())) {
+                        let obj_type = promoted[key.as_str()];
+                        if !object_types.contains(&obj_type) {
+                            object_types.push(obj_type);
+                        }
+                        let values = match value {
+                            Value::Array(items) => items.iter().collect(),
+                            _ => vec![value]
+                        };
+                        for value in values {
+                            let oid = match value {
+                                Value::String(s) => s.to_owned(),
+                                _ => value.to_string()
+                            };
+                            match log_internal.object_map.get_by_left(&oid) {
+                                Some(oid_nh) if log_internal.object_type(&log_internal.objects[oid_nh]) == obj_type => {},
+                                Some(_) => return Err(Error::ReferentialIntegrity(format!("The {} {} has the same id as an object of another type.", obj_type, oid))),
+                                None => {
+                                    declare_object(&mut log_internal, &oid, obj_type, AHashMap::new(), AttributeHistory::new())?;
+                                }
+                            }
+                            relate_object(&mut log_internal, eid_nh, &oid, "")?;
+                        }
+                    }
+                    eid_nh
+                }
+            };
+
+            relate_object(&mut log_internal, eid_nh, &trace_oid, "")?;
+        }
+    }
+
+    log_internal.reindex_chronologically();
+    log_internal.infer_schema();
+    fill_globals(&mut log_internal, "1.0", attribute_names.into_iter().collect(), object_types);
+    Ok(log_internal)
+}
+
+fn xes_attributes(nodes: &[XmlNode]) -> IndexMap<String, Value> {
+    nodes.iter().filter_map(xes_attribute).collect()
+}
+
+/// XES 2.0 wraps the entries of a list into a `values` element, their keys are meaningless.
+fn xes_attribute(node: &XmlNode) -> Option<(String, Value)> {
+    if !ATTRIBUTE_TAGS.contains(&node.tag.as_str()) {
+        return None;
+    }
+    let key = node.key.to_owned()?;
+    let value = match (node.tag.as_str(), node.children.as_slice()) {
+        ("list", [values]) if values.tag == "values" => Value::Array(values.children.iter().map(xml_to_value).collect()),
+        _ => xml_to_value(node)
+    };
+    Some((key, value))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::transformation::ocel::flattening::{flatten_ocel, FlatteningPolicy};
+    use crate::objects::ocel::importer::import_ocel;
+    use crate::objects::xes::exporter::export_xes_to_writer;
+
+    lazy_static::lazy_static!{
+        static ref OCEL: Ocel = import_ocel("logs/ocel-complex-test.jsonocel").expect("What did you do to the file?");
+    }
+
+    #[test]
+    fn test_import_xes() {
+        let xes = import_xes("logs/xes-test.xes").expect("What did you do to the file?");
+        assert_eq!(xes.attributes[CONCEPT_NAME], Value::String("xes-test".to_string()));
+        assert!(!xes.attributes.contains_key("trace") && !xes.attributes.contains_key("event"));
+        assert_eq!(xes.attributes.len(), 1);
+        assert_eq!(xes.traces.len(), 2);
+        assert_eq!(xes.traces[0].events.len(), 3);
+        assert_eq!(xes.traces[0].events[0].attributes["cost"], Value::from(12));
+        assert_eq!(xes.traces[1].events[1].attributes["tags"], serde_json::json!(["urgent", "manual"]));
+    }
+
+    #[test]
+    fn test_promote_resources() {
+        let config = XesImportConfig { promoted_attributes: vec![("org:resource".to_string(), "resource".to_string())], ..Default::default() };
+        let log = import_xes_ocel("logs/xes-test.xes", &config).expect("What did you do to the file?");
+
+        assert_eq!(log.objects.values().filter(|obj| log.object_type(obj) == "case").count(), 2);
+        assert_eq!(log.objects.values().filter(|obj| log.object_type(obj) == "resource").count(), 2);
+        assert_eq!(log.events.len(), 5);
+        assert_eq!(log.activities(), vec!["register", "check", "pay"]);
+
+        let pete = log.object_map.get_by_left("Pete").unwrap();
+        assert_eq!(log.objects[pete].events.len(), 3);
+        for ev in log.events.values() {
+            assert_eq!(ev.omap.len(), 2);
+            assert!(!ev.vmap.contains_key("org:resource"));
+        }
+    }
+
+    #[test]
+    fn test_flattened_round_trip() {
+        let mut buf: Vec<u8> = vec![];
+        export_xes_to_writer(&flatten_ocel(&OCEL, "item", FlatteningPolicy::Duplicate), &mut buf).unwrap();
+        let config = XesImportConfig { trace_type: "item".to_string(), ..Default::default() };
+        let log = import_xes_ocel_from_reader(buf.as_slice(), &config).expect("the flattened log is valid xes");
+
+        // events that were copied into several traces come back as a single event
+        for (eid, eid_nh) in log.event_map.iter() {
+            let original = &OCEL.events[OCEL.event_map.get_by_left(eid).unwrap()];
+            let ev = &log.events[eid_nh];
+            assert_eq!(log.activity(ev), OCEL.activity(original));
+            assert_eq!(ev.timestamp, original.timestamp);
+            assert_eq!(ev.omap.len(), original.omap.iter().filter(|oid| OCEL.object_type(&OCEL.objects[oid]) == "item").count());
+        }
+        for obj in log.objects.values() {
+            assert!(obj.events.windows(2).all(|w| log.events[&w[0]].timestamp <= log.events[&w[1]].timestamp));
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use pmrs::objects::ocel::importer::{import_ocel_streaming, ImportProgress};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// this file only holds a single test, so no other test allocates while it measures
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const OBJECTS: usize = 20_000;
+const EVENTS: usize = 100_000;
+
+fn generate_log(file_path: &str) -> std::io::Result<()> {
+    let mut f = BufWriter::new(File::create(file_path)?);
+    write!(f, r#"{{"ocel:global-log": {{"ocel:version": "0.1", "ocel:ordering": "timestamp", "ocel:attribute-names": ["price", "resource"], "ocel:object-types": ["order", "item"]}},"#)?;
+    write!(f, r#""ocel:global-event": {{"ocel:activity": "__INVALID__"}}, "ocel:global-object": {{"ocel:type": "__INVALID__"}}, "ocel:objects": {{"#)?;
+    for oid in 0..OBJECTS {
+        let obj_type = if oid % 4 == 0 {"order"} else {"item"};
+        write!(f, r#"{}"o{}": {{"ocel:type": "{}", "ocel:ovmap": {{"price": {}}}}}"#, if oid == 0 {""} else {","}, oid, obj_type, oid % 97)?;
+    }
+    write!(f, r#"}}, "ocel:events": {{"#)?;
+    for eid in 0..EVENTS {
+        write!(f, r#"{}"e{}": {{"ocel:activity": "activity {}", "ocel:timestamp": "2022-01-01T{:02}:{:02}:{:02}Z", "ocel:omap": ["o{}", "o{}"], "ocel:vmap": {{"resource": "r{}"}}}}"#,
+               if eid == 0 {""} else {","}, eid, eid % 10, (eid / 3600) % 24, (eid / 60) % 60, eid % 60, eid % OBJECTS, (eid * 7) % OBJECTS, eid % 13)?;
+    }
+    write!(f, "}}}}")?;
+    f.flush()
+}
+
+#[test]
+fn test_streaming_import_memory() {
+    let path = std::env::temp_dir().join("pmrs-streaming-memory.jsonocel");
+    let path = path.to_str().unwrap();
+    generate_log(path).expect("could not write the generated log");
+    let file_size = std::fs::metadata(path).unwrap().len() as usize;
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+
+    let mut reports: usize = 0;
+    let mut callback = |_: &ImportProgress| reports += 1;
+    let log = import_ocel_streaming(path, Some(&mut callback)).expect("could not import the generated log");
+
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+    let retained = ALLOCATED.load(Ordering::SeqCst) - baseline;
+    assert_eq!(log.events().len(), EVENTS);
+    assert_eq!(log.objects().len(), OBJECTS);
+    assert!(reports > 1);
+
+    // the serialized log is never held in memory, so apart from the imported log itself only
+    // a small fraction of the file size may be allocated at any time
+    assert!(peak - retained < file_size / 4, "peak {} is too far above the retained {} for a file of {}", peak, retained, file_size);
+
+    drop(log);
+    let _ = std::fs::remove_file(path);
+}